@@ -1,5 +1,10 @@
 use thiserror::Error;
 
+/// Returned when an operation is stopped early via its cancellation token.
+#[derive(Error, Debug)]
+#[error("operation cancelled")]
+pub struct Cancelled;
+
 #[derive(Error, Debug)]
 pub enum IntegrationError {
     #[error("No provider found for {:?}", url)]
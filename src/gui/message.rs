@@ -1,5 +1,3 @@
-use std::collections::BTreeSet;
-use std::ops::DerefMut;
 use std::time::SystemTime;
 use std::{
     collections::{HashMap, HashSet},
@@ -13,15 +11,14 @@ use tokio::{
     sync::mpsc::{self, Sender},
     task::JoinHandle,
 };
-use tracing::{debug, error, info};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
 
 use crate::integrate::{IntegrationErr, IntegrationErrKind};
-use crate::mod_lints::{LintId, LintReport};
-use crate::state::{ModData_v0_1_0 as ModData, ModOrGroup};
+use crate::mod_lints::{LintConfig, LintReport};
 use crate::{
     error::IntegrationError,
     providers::{FetchProgress, ModInfo, ModResolution, ModSpecification, ModStore},
-    state::ModConfig,
 };
 
 use super::SelfUpdateProgress;
@@ -34,9 +31,19 @@ use super::{
 pub struct MessageHandle<S> {
     pub rid: RequestID,
     pub handle: JoinHandle<()>,
+    pub cancel: CancellationToken,
     pub state: S,
 }
 
+impl<S> MessageHandle<S> {
+    /// Stop the underlying operation, including any network requests or integration still running
+    /// on a blocking thread.
+    pub fn cancel(self) {
+        self.cancel.cancel();
+        self.handle.abort();
+    }
+}
+
 #[derive(Debug)]
 pub enum Message {
     ResolveMods(ResolveMods),
@@ -83,8 +90,10 @@ impl ResolveMods {
         let store = app.state.store.clone();
         let tx = app.tx.clone();
         let ctx = ctx.clone();
+        let cancel = CancellationToken::new();
+        let token = cancel.clone();
         let handle = tokio::spawn(async move {
-            let result = store.resolve_mods(&specs, false).await;
+            let result = store.resolve_mods(&specs, false, &token).await;
             tx.send(Message::ResolveMods(Self {
                 rid,
                 specs,
@@ -99,6 +108,7 @@ impl ResolveMods {
         app.resolve_mod_rid = Some(MessageHandle {
             rid,
             handle,
+            cancel,
             state: (),
         });
     }
@@ -111,53 +121,23 @@ impl ResolveMods {
                         .specs
                         .into_iter()
                         .collect::<HashSet<ModSpecification>>();
+                    let active_profile = app.state.mod_data.active_profile.clone();
                     for (resolved_spec, info) in resolved_mods {
+                        // if mod is a dependency then check if there is a disabled mod that
+                        // satisfies the dependency and enable it. if it is not a dependency then
+                        // assume the user explicitly wants to add a specific mod version.
                         let is_dep = self.is_dependency || !primary_mods.contains(&resolved_spec);
-                        let add = if is_dep {
-                            // if mod is a dependency then check if there is a disabled
-                            // mod that satisfies the dependency and enable it. if it
-                            // is not a dependency then assume the user explicitly
-                            // wants to add a specific mod version.
-                            let active_profile = app.state.mod_data.active_profile.clone();
-                            !app.state.mod_data.any_mod_mut(
-                                &active_profile,
-                                |mc, mod_group_enabled| {
-                                    if mc.spec.satisfies_dependency(&resolved_spec) {
-                                        mc.enabled = true;
-                                        if let Some(mod_group_enabled) = mod_group_enabled {
-                                            *mod_group_enabled = true;
-                                        }
-                                        true
-                                    } else {
-                                        false
-                                    }
-                                },
-                            )
-                        } else {
-                            true
-                        };
-
-                        if add {
-                            let ModData {
-                                active_profile,
-                                profiles,
-                                ..
-                            } = app.state.mod_data.deref_mut().deref_mut();
-
-                            profiles.get_mut(active_profile).unwrap().mods.insert(
-                                0,
-                                ModOrGroup::Individual(ModConfig {
-                                    spec: info.spec.clone(),
-                                    required: info.suggested_require,
-                                    enabled: true,
-                                }),
-                            );
-                        }
+                        app.state.mod_data.add_resolved_mod(
+                            &active_profile,
+                            &resolved_spec,
+                            &info,
+                            is_dep,
+                        );
                     }
                     app.resolve_mod.clear();
-                    app.state.mod_data.save().unwrap();
                     app.last_action_status =
                         LastActionStatus::Success("mods successfully resolved".to_string());
+                    app.save_mod_data();
                 }
                 Err(e) => match e.downcast::<IntegrationError>() {
                     Ok(IntegrationError::NoProvider { url: _, factory }) => {
@@ -184,24 +164,43 @@ pub struct Integrate {
 }
 
 impl Integrate {
+    #[allow(clippy::too_many_arguments)]
     pub fn send(
         rc: &mut RequestCounter,
         store: Arc<ModStore>,
         mods: Vec<ModSpecification>,
         fsd_pak: PathBuf,
+        block_incompatible: bool,
+        deny_lints: Option<LintConfig>,
+        merge_tables: bool,
         tx: Sender<Message>,
         ctx: egui::Context,
     ) -> MessageHandle<HashMap<ModSpecification, SpecFetchProgress>> {
         let rid = rc.next();
+        let cancel = CancellationToken::new();
+        let token = cancel.clone();
         MessageHandle {
             rid,
             handle: tokio::task::spawn(async move {
-                let res = integrate_async(store, ctx.clone(), mods, fsd_pak, rid, tx.clone()).await;
+                let res = integrate_async(
+                    store,
+                    ctx.clone(),
+                    mods,
+                    fsd_pak,
+                    block_incompatible,
+                    deny_lints,
+                    merge_tables,
+                    rid,
+                    tx.clone(),
+                    token,
+                )
+                .await;
                 tx.send(Message::Integrate(Integrate { rid, result: res }))
                     .await
                     .unwrap();
                 ctx.request_repaint();
             }),
+            cancel,
             state: Default::default(),
         }
     }
@@ -278,8 +277,10 @@ impl UpdateCache {
         let rid = app.request_counter.next();
         let tx = app.tx.clone();
         let store = app.state.store.clone();
+        let cancel = CancellationToken::new();
+        let token = cancel.clone();
         let handle = tokio::spawn(async move {
-            let res = store.update_cache().await;
+            let res = store.update_cache(&token).await;
             tx.send(Message::UpdateCache(UpdateCache { rid, result: res }))
                 .await
                 .unwrap();
@@ -288,6 +289,7 @@ impl UpdateCache {
         app.update_rid = Some(MessageHandle {
             rid,
             handle,
+            cancel,
             state: (),
         });
     }
@@ -354,6 +356,7 @@ impl CheckUpdates {
         app.check_updates_rid = Some(MessageHandle {
             rid,
             handle,
+            cancel: Default::default(),
             state: (),
         });
     }
@@ -378,18 +381,23 @@ impl CheckUpdates {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn integrate_async(
     store: Arc<ModStore>,
     ctx: egui::Context,
     mod_specs: Vec<ModSpecification>,
     fsd_pak: PathBuf,
+    block_incompatible: bool,
+    deny_lints: Option<LintConfig>,
+    merge_tables: bool,
     rid: RequestID,
     message_tx: Sender<Message>,
+    cancel: CancellationToken,
 ) -> Result<(), IntegrationErr> {
     let update = false;
 
     let mods = store
-        .resolve_mods(&mod_specs, update)
+        .resolve_mods(&mod_specs, update, &cancel)
         .await
         .map_err(|e| IntegrationErr {
             mod_ctxt: None,
@@ -400,6 +408,9 @@ async fn integrate_async(
         .iter()
         .map(|u| mods[u].clone())
         .collect::<Vec<_>>();
+
+    crate::check_compatibility(&fsd_pak, &to_integrate, block_incompatible)?;
+
     let res_map: HashMap<ModResolution, ModSpecification> = mods
         .iter()
         .map(|(spec, info)| (info.resolution.clone(), spec.clone()))
@@ -428,15 +439,37 @@ async fn integrate_async(
     });
 
     let paths = store
-        .fetch_mods(&urls, update, Some(tx))
+        .fetch_mods(&urls, update, Some(tx), &cancel)
         .await
         .map_err(|e| IntegrationErr {
             mod_ctxt: None,
             kind: IntegrationErrKind::Generic(e),
         })?;
 
-    tokio::task::spawn_blocking(|| {
-        crate::integrate::integrate(fsd_pak, to_integrate.into_iter().zip(paths).collect())
+    tokio::task::spawn_blocking(move || {
+        if let Some(config) = deny_lints {
+            crate::mod_lints::check_denied_lints(
+                &config,
+                to_integrate
+                    .iter()
+                    .map(|m| m.spec.clone())
+                    .zip(paths.iter().cloned())
+                    .collect(),
+                Some(fsd_pak.clone()),
+                mods,
+                &cancel,
+            )
+            .map_err(|e| IntegrationErr {
+                mod_ctxt: None,
+                kind: IntegrationErrKind::Generic(e),
+            })?;
+        }
+        crate::integrate::integrate(
+            fsd_pak,
+            to_integrate.into_iter().zip(paths).collect(),
+            merge_tables,
+            &cancel,
+        )
     })
     .await
     .map_err(|e| IntegrationErr {
@@ -458,25 +491,41 @@ impl LintMods {
         rc: &mut RequestCounter,
         store: Arc<ModStore>,
         mods: Vec<ModSpecification>,
-        enabled_lints: BTreeSet<LintId>,
+        config: LintConfig,
         game_pak_path: Option<PathBuf>,
         tx: Sender<Message>,
         ctx: egui::Context,
     ) -> MessageHandle<()> {
         let rid = rc.next();
+        let cancel = CancellationToken::new();
+        let token = cancel.clone();
 
         let handle = tokio::task::spawn(async move {
-            let paths_res =
-                resolve_async_ordered(store, ctx.clone(), mods.clone(), rid, tx.clone()).await;
+            let paths_res = resolve_async_ordered(
+                store.clone(),
+                ctx.clone(),
+                mods.clone(),
+                rid,
+                tx.clone(),
+                &token,
+            )
+            .await;
             let mod_path_pairs_res =
                 paths_res.map(|paths| mods.into_iter().zip(paths).collect::<Vec<_>>());
 
             let report_res = match mod_path_pairs_res {
                 Ok(pairs) => tokio::task::spawn_blocking(move || {
-                    crate::mod_lints::run_lints(
-                        &enabled_lints,
+                    // only available once the mods have been resolved
+                    let mod_infos = pairs
+                        .iter()
+                        .filter_map(|(m, _)| store.get_mod_info(m).map(|info| (m.clone(), info)))
+                        .collect();
+                    crate::mod_lints::run_lints_with_config(
+                        &config,
                         pairs.into_iter().collect(),
                         game_pak_path,
+                        mod_infos,
+                        &token,
                     )
                 })
                 .await
@@ -496,6 +545,7 @@ impl LintMods {
         MessageHandle {
             rid,
             handle,
+            cancel,
             state: Default::default(),
         }
     }
@@ -532,10 +582,11 @@ async fn resolve_async_ordered(
     mod_specs: Vec<ModSpecification>,
     rid: RequestID,
     message_tx: Sender<Message>,
+    cancel: &CancellationToken,
 ) -> Result<Vec<PathBuf>> {
     let update = false;
 
-    let mods = store.resolve_mods(&mod_specs, update).await?;
+    let mods = store.resolve_mods(&mod_specs, update, cancel).await?;
 
     let to_integrate = mod_specs
         .iter()
@@ -568,7 +619,9 @@ async fn resolve_async_ordered(
         }
     });
 
-    store.fetch_mods_ordered(&urls, update, Some(tx)).await
+    store
+        .fetch_mods_ordered(&urls, update, Some(tx), cancel)
+        .await
 }
 
 #[derive(Debug)]
@@ -593,6 +646,7 @@ impl SelfUpdate {
                     .unwrap();
                 ctx.request_repaint();
             }),
+            cancel: Default::default(),
             state: SelfUpdateProgress::Pending,
         }
     }
@@ -6,7 +6,7 @@ mod toggle_switch;
 
 //#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::BTreeMap;
 use std::time::{Duration, SystemTime};
 use std::{
     collections::{HashMap, HashSet},
@@ -27,9 +27,11 @@ use tokio::{
     sync::mpsc::{self, Receiver, Sender},
     task::JoinHandle,
 };
-use tracing::{debug, info, trace};
+use tracing::{debug, error, info, trace};
 
-use crate::mod_lints::{LintId, LintReport, SplitAssetPair};
+use crate::mod_lints::{
+    lint_factories, Diagnostic, LintConfig, LintId, LintLevel, LintReport, ReportFormat,
+};
 use crate::Dirs;
 use crate::{
     integrate::uninstall,
@@ -38,7 +40,11 @@ use crate::{
         ApprovalStatus, FetchProgress, ModInfo, ModSpecification, ModStore, ModioTags,
         ProviderFactory, RequiredStatus,
     },
-    state::{ModConfig, ModData_v0_1_0 as ModData, ModOrGroup, ModProfile, State},
+    state::{
+        diff::{ProfileDiff, ProfileMods},
+        entry_at_path_mut, walk_mods, ModConfig, ModData_v0_2_0 as ModData, ModOrGroup, ModProfile,
+        State,
+    },
 };
 use find_string::FindString;
 use message::MessageHandle;
@@ -47,6 +53,17 @@ use request_counter::{RequestCounter, RequestID};
 use self::toggle_switch::toggle_switch;
 
 pub fn gui(dirs: Dirs, args: Option<Vec<String>>) -> Result<()> {
+    let state = State::init_with_recovery(dirs, |corrupt| {
+        rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title("Corrupt configuration")
+            .set_description(&format!(
+                "{corrupt}\n\nRestore the most recent valid backup ({})?",
+                corrupt.backup.as_ref().unwrap().display()
+            ))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show()
+    })?;
     let options = eframe::NativeOptions {
         initial_window_size: Some(egui::vec2(800.0, 400.0)),
         drag_and_drop_support: true,
@@ -55,7 +72,7 @@ pub fn gui(dirs: Dirs, args: Option<Vec<String>>) -> Result<()> {
     eframe::run_native(
         &format!("DRG Mod Integration {}", env!("CARGO_PKG_VERSION")),
         options,
-        Box::new(|cc| Box::new(App::new(cc, dirs, args).unwrap())),
+        Box::new(|cc| Box::new(App::new(cc, state, args))),
     )
     .map_err(|e| anyhow!("{e}"))?;
     Ok(())
@@ -114,27 +131,15 @@ pub struct App {
     lint_report_window: Option<WindowLintReport>,
     lint_report: Option<LintReport>,
     lints_toggle_window: Option<WindowLintsToggle>,
-    lint_options: LintOptions,
+    /// Whether each lint is enabled in the lint toggle window.
+    lint_options: BTreeMap<LintId, bool>,
+    profile_diff_window: Option<WindowProfileDiff>,
     cache: CommonMarkCache,
     needs_restart: bool,
     self_update_rid: Option<MessageHandle<SelfUpdateProgress>>,
     original_exe_path: Option<PathBuf>,
 }
 
-#[derive(Default)]
-struct LintOptions {
-    archive_with_multiple_paks: bool,
-    archive_with_only_non_pak_files: bool,
-    asset_register_bin: bool,
-    conflicting: bool,
-    empty_archive: bool,
-    outdated_pak_version: bool,
-    shader_files: bool,
-    non_asset_files: bool,
-    split_asset_pairs: bool,
-    unmodified_game_assets: bool,
-}
-
 enum LastActionStatus {
     Idle,
     Success(String),
@@ -142,13 +147,12 @@ enum LastActionStatus {
 }
 
 impl App {
-    fn new(cc: &eframe::CreationContext, dirs: Dirs, args: Option<Vec<String>>) -> Result<Self> {
+    fn new(cc: &eframe::CreationContext, state: State, args: Option<Vec<String>>) -> Self {
         let (tx, rx) = mpsc::channel(10);
-        let state = State::init(dirs)?;
         info!("config dir = {}", state.dirs.config_dir.display());
         info!("cache dir = {}", state.dirs.cache_dir.display());
 
-        Ok(Self {
+        Self {
             default_visuals: cc
                 .integration_info
                 .system_theme
@@ -178,15 +182,41 @@ impl App {
             lint_report_window: None,
             lint_report: None,
             lints_toggle_window: None,
-            lint_options: LintOptions::default(),
+            lint_options: BTreeMap::new(),
+            profile_diff_window: None,
             cache: Default::default(),
             needs_restart: false,
             self_update_rid: None,
             original_exe_path: None,
-        })
+        }
     }
 
     fn ui_profile(&mut self, ui: &mut Ui, profile: &str) {
+        // inherited mods belong to the parent and are only changed through the profile's
+        // exclusions and overrides
+        let inherited = self
+            .state
+            .mod_data
+            .walk_mods(profile)
+            .into_iter()
+            .filter_map(|walked| {
+                Some((
+                    walked.inherited_from?.to_string(),
+                    walked.config.clone(),
+                    walked.enabled,
+                    walked.groups_enabled,
+                ))
+            })
+            .collect::<Vec<_>>();
+        let parents = self
+            .state
+            .mod_data
+            .profiles
+            .keys()
+            .filter(|name| !self.state.mod_data.profile_reaches(name, profile))
+            .cloned()
+            .collect::<Vec<_>>();
+
         let ModData {
             profiles, groups, ..
         } = self.state.mod_data.deref_mut().deref_mut();
@@ -205,33 +235,45 @@ impl App {
         };
 
         let mut ui_profile = |ui: &mut Ui, profile: &mut ModProfile| {
-            let enabled_specs = profile
-                .mods
-                .iter()
-                .enumerate()
-                .flat_map(|(i, m)| -> Box<dyn Iterator<Item = _>> {
-                    match m {
-                        ModOrGroup::Individual(mc) => {
-                            Box::new(mc.enabled.then_some((Some(i), mc.spec.clone())).into_iter())
-                        }
-                        ModOrGroup::Group {
-                            group_name,
-                            enabled,
-                        } => Box::new(
-                            enabled
-                                .then(|| groups.get(group_name))
-                                .flatten()
-                                .into_iter()
-                                .flat_map(|g| {
-                                    g.mods
-                                        .iter()
-                                        .filter_map(|m| m.enabled.then_some((None, m.spec.clone())))
-                                }),
-                        ),
-                    }
+            let enabled_specs = walk_mods(groups, &profile.mods)
+                .into_iter()
+                .filter(|walked| walked.is_active())
+                .map(|walked| {
+                    (
+                        (walked.path.len() == 1).then_some(walked.path[0]),
+                        walked.config.spec.clone(),
+                    )
                 })
+                .chain(
+                    inherited
+                        .iter()
+                        .filter(|(_, _, enabled, groups_enabled)| *enabled && *groups_enabled)
+                        .map(|(_, config, _, _)| (None, config.spec.clone())),
+                )
                 .collect::<Vec<_>>();
 
+            if !parents.is_empty() || profile.parent.is_some() {
+                ui.horizontal(|ui| {
+                    ui.label("Inherits from");
+                    let previous = profile.parent.clone();
+                    egui::ComboBox::from_id_source("profile_parent")
+                        .selected_text(previous.as_deref().unwrap_or("nothing"))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut profile.parent, None, "nothing");
+                            for name in &parents {
+                                ui.selectable_value(
+                                    &mut profile.parent,
+                                    Some(name.clone()),
+                                    name.as_str(),
+                                );
+                            }
+                        });
+                    if profile.parent != previous {
+                        ctx.needs_save = true;
+                    }
+                });
+            }
+
             let ui_mod_tags = |ctx: &mut Ctx, ui: &mut Ui, info: &ModInfo| {
                 if let Some(ModioTags {
                     qol,
@@ -584,6 +626,26 @@ impl App {
                         };
                     });
 
+                    // mods of a group as seen from this profile, with nested groups flattened
+                    let walked = walk_mods(groups, std::slice::from_ref(&*mc))
+                        .into_iter()
+                        .map(|walked| {
+                            (
+                                walked.path,
+                                walked.config.clone(),
+                                walked.enabled,
+                                walked
+                                    .groups
+                                    .iter()
+                                    .skip(1)
+                                    .copied()
+                                    .collect::<Vec<_>>()
+                                    .join(" / "),
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    let mut changed_specs = vec![];
+
                     match mc {
                         ModOrGroup::Individual(mc) => {
                             ui_mod(ctx, ui, None, state, mc);
@@ -591,6 +653,7 @@ impl App {
                         ModOrGroup::Group {
                             ref group_name,
                             enabled,
+                            overrides,
                         } => {
                             if ui
                                 .add(toggle_switch(enabled))
@@ -600,14 +663,17 @@ impl App {
                                 ctx.needs_save = true;
                             }
                             ui.collapsing(group_name, |ui| {
-                                for (index, m) in groups
-                                    .get_mut(group_name)
-                                    .unwrap()
-                                    .mods
-                                    .iter_mut()
-                                    .enumerate()
+                                for (index, (path, config, effective, nested)) in
+                                    walked.into_iter().enumerate()
                                 {
+                                    let mut shown = ModConfig {
+                                        enabled: effective,
+                                        ..config.clone()
+                                    };
                                     ui.horizontal(|ui| {
+                                        if !nested.is_empty() {
+                                            ui.label(format!("{nested}:"));
+                                        }
                                         ui_mod(
                                             ctx,
                                             ui,
@@ -616,13 +682,34 @@ impl App {
                                                 index,
                                                 dragged: false,
                                             },
-                                            m,
+                                            &mut shown,
                                         )
                                     });
+                                    // toggling only overrides the mod in this profile
+                                    if shown.enabled != effective {
+                                        if path.len() == 2 && shown.enabled == config.enabled {
+                                            overrides.remove(&config.spec.url);
+                                        } else {
+                                            overrides
+                                                .insert(config.spec.url.clone(), shown.enabled);
+                                        }
+                                    }
+                                    if shown.spec != config.spec {
+                                        changed_specs.push((path, shown.spec));
+                                    }
                                 }
                             });
                         }
                     }
+
+                    // the version of a mod in a group is shared by every profile including it
+                    for (path, spec) in changed_specs {
+                        if let Some(ModOrGroup::Individual(mc)) =
+                            entry_at_path_mut(groups, std::slice::from_mut(mc), &path)
+                        {
+                            mc.spec = spec;
+                        }
+                    }
                 };
 
             let res = egui_dnd::dnd(ui, ui.id()).show(
@@ -655,6 +742,50 @@ impl App {
                 profile.mods.remove(remove);
                 ctx.needs_save = true;
             }
+
+            if !inherited.is_empty() || !profile.excluded.is_empty() {
+                ui.separator();
+                ui.collapsing(format!("Inherited mods ({})", inherited.len()), |ui| {
+                    for (parent, config, enabled, groups_enabled) in &inherited {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .button(" 🗑 ")
+                                .on_hover_text_at_pointer("Exclude from this profile")
+                                .clicked()
+                            {
+                                profile.excluded.insert(config.spec.url.clone());
+                                ctx.needs_save = true;
+                            }
+                            let mut enabled = *enabled;
+                            if ui
+                                .add(toggle_switch(&mut enabled))
+                                .on_hover_text_at_pointer("Enabled in this profile?")
+                                .changed()
+                            {
+                                profile.overrides.insert(config.spec.url.clone(), enabled);
+                                ctx.needs_save = true;
+                            }
+                            match self.state.store.get_mod_info(&config.spec) {
+                                Some(info) => ui.hyperlink_to(info.name, &config.spec.url),
+                                None => ui.hyperlink(&config.spec.url),
+                            };
+                            let mut from = format!("from `{parent}`");
+                            if !groups_enabled {
+                                from.push_str(", group disabled");
+                            }
+                            ui.weak(from);
+                        });
+                    }
+                    if !profile.excluded.is_empty()
+                        && ui
+                            .button(format!("Restore {} excluded", profile.excluded.len()))
+                            .clicked()
+                    {
+                        profile.excluded.clear();
+                        ctx.needs_save = true;
+                    }
+                });
+            }
         };
 
         egui::ScrollArea::vertical().show(ui, |ui| {
@@ -672,7 +803,53 @@ impl App {
         self.scroll_to_match = ctx.scroll_to_match;
 
         if ctx.needs_save {
-            self.state.mod_data.save().unwrap();
+            self.save_mod_data();
+        }
+    }
+
+    fn save_mod_data(&mut self) {
+        if let Err(e) = self.state.mod_data.save() {
+            error!("failed to save mod data: {e:#}");
+            self.last_action_status =
+                LastActionStatus::Failure(format!("failed to save mod data: {e:#}"));
+        }
+    }
+
+    fn integrate(&mut self, ctx: &egui::Context, profile: &str, mods: Vec<ModSpecification>) {
+        self.last_action_status = LastActionStatus::Idle;
+        let deny_lints = match self
+            .state
+            .config
+            .block_denied_lints
+            .then(|| self.state.lint_config(Some(profile)))
+            .transpose()
+        {
+            Ok(deny_lints) => deny_lints,
+            Err(e) => {
+                error!("failed to load lint config: {e:#}");
+                self.last_action_status =
+                    LastActionStatus::Failure(format!("failed to load lint config: {e:#}"));
+                return;
+            }
+        };
+        self.integrate_rid = Some(message::Integrate::send(
+            &mut self.request_counter,
+            self.state.store.clone(),
+            mods,
+            self.state.config.drg_pak_path.as_ref().unwrap().clone(),
+            self.state.config.block_incompatible_mods,
+            deny_lints,
+            self.state.config.merge_tables,
+            self.tx.clone(),
+            ctx.clone(),
+        ));
+    }
+
+    fn save_config(&mut self) {
+        if let Err(e) = self.state.config.save() {
+            error!("failed to save config: {e:#}");
+            self.last_action_status =
+                LastActionStatus::Failure(format!("failed to save config: {e:#}"));
         }
     }
 
@@ -790,11 +967,15 @@ impl App {
                 match res {
                     Ok(()) => {
                         let window = self.window_provider_parameters.take().unwrap();
-                        self.state
-                            .config
-                            .provider_parameters
-                            .insert(window.factory.id.to_string(), window.parameters);
-                        self.state.config.save().unwrap();
+                        if let Err(e) = self
+                            .state
+                            .set_provider_parameters(window.factory, window.parameters)
+                        {
+                            error!("failed to save provider parameters: {e:#}");
+                            self.last_action_status = LastActionStatus::Failure(format!(
+                                "failed to save provider parameters: {e:#}"
+                            ));
+                        }
                         return;
                     }
                     Err(e) => {
@@ -887,6 +1068,7 @@ impl App {
         if let Some(window) = &mut self.settings_window {
             let mut open = true;
             let mut try_save = false;
+            let mut config_changed = false;
             egui::Window::new("Settings")
                 .open(&mut open)
                 .resizable(false)
@@ -951,12 +1133,33 @@ impl App {
                                     ui.selectable_value(&mut config.gui_theme, None, "System").changed();
                                 if changed {
                                     ctx.set_visuals(config.gui_theme.map(GuiTheme::visuals).unwrap_or_else(|| self.default_visuals.clone()));
-                                    config.save().unwrap();
+                                    config_changed = true;
                                 }
                             });
                         });
                         ui.end_row();
 
+                        ui.label("Block incompatible mods:")
+                            .on_hover_text("Refuse to install mods not marked compatible with the installed game version");
+                        if ui.add(toggle_switch(&mut self.state.config.block_incompatible_mods)).changed() {
+                            config_changed = true;
+                        }
+                        ui.end_row();
+
+                        ui.label("Block on deny-level lints:")
+                            .on_hover_text("Refuse to install the profile if a lint set to deny in lints.json finds problems");
+                        if ui.add(toggle_switch(&mut self.state.config.block_denied_lints)).changed() {
+                            config_changed = true;
+                        }
+                        ui.end_row();
+
+                        ui.label("Merge conflicting tables:")
+                            .on_hover_text("Merge rows of data tables modified by several mods instead of using only the first mod's table");
+                        if ui.add(toggle_switch(&mut self.state.config.merge_tables)).changed() {
+                            config_changed = true;
+                        }
+                        ui.end_row();
+
                         ui.label("Mod providers:");
                         ui.end_row();
 
@@ -990,16 +1193,19 @@ impl App {
                     self.state.config.drg_pak_path = Some(PathBuf::from(
                         self.settings_window.take().unwrap().drg_pak_path,
                     ));
-                    self.state.config.save().unwrap();
+                    config_changed = true;
                 }
             } else if !open {
                 self.settings_window = None;
             }
+            if config_changed {
+                self.save_config();
+            }
         }
     }
 
     fn show_lints_toggle(&mut self, ctx: &egui::Context) {
-        if let Some(_lints_toggle) = &self.lints_toggle_window {
+        if self.lints_toggle_window.is_some() {
             let mut open = true;
 
             egui::Window::new("Toggle lints")
@@ -1012,55 +1218,19 @@ impl App {
                             ui.heading("Enabled?");
                             ui.end_row();
 
-                            ui.label("Archive with multiple paks");
-                            ui.add(toggle_switch(
-                                &mut self.lint_options.archive_with_multiple_paks,
-                            ));
-                            ui.end_row();
-
-                            ui.label("Archive with only non-pak files");
-                            ui.add(toggle_switch(
-                                &mut self.lint_options.archive_with_only_non_pak_files,
-                            ));
-                            ui.end_row();
-
-                            ui.label("Mods containing AssetRegister.bin");
-                            ui.add(toggle_switch(&mut self.lint_options.asset_register_bin));
-                            ui.end_row();
-
-                            ui.label("Mods containing conflicting files");
-                            ui.add(toggle_switch(&mut self.lint_options.conflicting));
-                            ui.end_row();
-
-                            ui.label("Mods containing empty archives");
-                            ui.add(toggle_switch(&mut self.lint_options.empty_archive));
-                            ui.end_row();
-
-                            ui.label("Mods containing oudated pak version");
-                            ui.add(toggle_switch(&mut self.lint_options.outdated_pak_version));
-                            ui.end_row();
-
-                            ui.label("Mods containing shader files");
-                            ui.add(toggle_switch(&mut self.lint_options.shader_files));
-                            ui.end_row();
-
-                            ui.label("Mods containing non-asset files");
-                            ui.add(toggle_switch(&mut self.lint_options.non_asset_files));
-                            ui.end_row();
-
-                            ui.label("Mods containing split {uexp, uasset} pairs");
-                            ui.add(toggle_switch(&mut self.lint_options.split_asset_pairs));
-                            ui.end_row();
-
-                            ui.label("Mods containing unmodified game assets");
-                            ui.add_enabled(
-                                self.state.config.drg_pak_path.is_some(),
-                                toggle_switch(&mut self.lint_options.unmodified_game_assets),
-                            )
-                            .on_disabled_hover_text(
-                                "This lint requires DRG pak path to be specified",
-                            );
-                            ui.end_row();
+                            for factory in lint_factories() {
+                                ui.label(factory.description);
+                                let enabled = self.lint_options.entry(factory.id).or_default();
+                                ui.add_enabled(
+                                    !factory.requires_game_pak
+                                        || self.state.config.drg_pak_path.is_some(),
+                                    toggle_switch(enabled),
+                                )
+                                .on_disabled_hover_text(
+                                    "This lint requires DRG pak path to be specified",
+                                );
+                                ui.end_row();
+                            }
                         });
                     });
 
@@ -1078,38 +1248,21 @@ impl App {
                             )
                             .clicked()
                         {
-                            let lint_options = BTreeMap::from([
-                                (
-                                    LintId::ARCHIVE_WITH_MULTIPLE_PAKS,
-                                    self.lint_options.archive_with_multiple_paks,
-                                ),
-                                (
-                                    LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES,
-                                    self.lint_options.archive_with_only_non_pak_files,
-                                ),
-                                (
-                                    LintId::ASSET_REGISTRY_BIN,
-                                    self.lint_options.asset_register_bin,
-                                ),
-                                (LintId::CONFLICTING, self.lint_options.conflicting),
-                                (LintId::EMPTY_ARCHIVE, self.lint_options.empty_archive),
-                                (
-                                    LintId::OUTDATED_PAK_VERSION,
-                                    self.lint_options.outdated_pak_version,
-                                ),
-                                (LintId::SHADER_FILES, self.lint_options.shader_files),
-                                (LintId::NON_ASSET_FILES, self.lint_options.non_asset_files),
-                                (
-                                    LintId::SPLIT_ASSET_PAIRS,
-                                    self.lint_options.split_asset_pairs,
-                                ),
-                                (
-                                    LintId::UNMODIFIED_GAME_ASSETS,
-                                    self.lint_options.unmodified_game_assets,
-                                ),
-                            ]);
-
-                            trace!(?lint_options);
+                            trace!(?self.lint_options);
+
+                            // toggles override the configured level, keeping deny where enabled
+                            let mut config = self
+                                .lints_toggle_window
+                                .as_ref()
+                                .map(|w| w.config.clone())
+                                .unwrap_or_default();
+                            for (lint, enabled) in &self.lint_options {
+                                if !enabled {
+                                    config.levels.insert(*lint, LintLevel::Allow);
+                                } else if config.level(*lint) == LintLevel::Allow {
+                                    config.levels.insert(*lint, LintLevel::Warn);
+                                }
+                            }
 
                             let mut mods = Vec::new();
                             self.state.mod_data.for_each_enabled_mod(
@@ -1124,11 +1277,7 @@ impl App {
                                 &mut self.request_counter,
                                 self.state.store.clone(),
                                 mods,
-                                BTreeSet::from_iter(
-                                    lint_options
-                                        .into_iter()
-                                        .filter_map(|(lint, enabled)| enabled.then_some(lint)),
-                                ),
+                                config,
                                 self.state.config.drg_pak_path.clone(),
                                 self.tx.clone(),
                                 ctx.clone(),
@@ -1145,6 +1294,98 @@ impl App {
         }
     }
 
+    fn show_profile_diff(&mut self, ctx: &egui::Context) {
+        let Some(window) = &mut self.profile_diff_window else {
+            return;
+        };
+        let mut open = true;
+
+        egui::Window::new("Compare profiles")
+            .open(&mut open)
+            .resizable(true)
+            .show(ctx, |ui| {
+                let profiles = self
+                    .state
+                    .mod_data
+                    .profiles
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<_>>();
+                egui::Grid::new("profile-diff-grid").show(ui, |ui| {
+                    ui.label("Compare:");
+                    egui::ComboBox::from_id_source("profile-diff-old")
+                        .selected_text(window.old.as_str())
+                        .show_ui(ui, |ui| {
+                            for profile in &profiles {
+                                ui.selectable_value(
+                                    &mut window.old,
+                                    profile.clone(),
+                                    profile.as_str(),
+                                );
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("With:");
+                    egui::ComboBox::from_id_source("profile-diff-new")
+                        .selected_text(window.new.as_deref().unwrap_or("Profile code"))
+                        .show_ui(ui, |ui| {
+                            for profile in &profiles {
+                                ui.selectable_value(
+                                    &mut window.new,
+                                    Some(profile.clone()),
+                                    profile.as_str(),
+                                );
+                            }
+                            ui.selectable_value(&mut window.new, None, "Profile code");
+                        });
+                    ui.end_row();
+                });
+                if window.new.is_none() {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut window.code)
+                            .hint_text("Paste a profile code, one mod URL per line"),
+                    );
+                }
+
+                let diff = self
+                    .state
+                    .mod_data
+                    .profile_mods(&window.old)
+                    .and_then(|old| {
+                        let new = match &window.new {
+                            Some(new) => self.state.mod_data.profile_mods(new)?,
+                            None => ProfileMods::from_code(&window.code),
+                        };
+                        Ok(ProfileDiff::new(&old, &new, Some(&self.state.store)))
+                    });
+
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| match diff {
+                    Ok(diff) if diff.is_empty() => {
+                        ui.label("Profiles are identical");
+                    }
+                    Ok(diff) => {
+                        for (marker, line) in diff.lines() {
+                            let color = match marker {
+                                '+' => Color32::LIGHT_GREEN,
+                                '-' => Color32::LIGHT_RED,
+                                _ => ui.visuals().text_color(),
+                            };
+                            ui.label(RichText::new(format!("{marker} {line}")).color(color));
+                        }
+                    }
+                    Err(e) => {
+                        ui.colored_label(Color32::LIGHT_RED, format!("{e:#}"));
+                    }
+                });
+            });
+
+        if !open {
+            self.profile_diff_window = None;
+        }
+    }
+
     fn show_lint_report(&mut self, ctx: &egui::Context) {
         if self.lint_report_window.is_some() {
             let mut open = true;
@@ -1161,276 +1402,70 @@ impl App {
                             .show(ui, |ui| {
                                 const AMBER: Color32 = Color32::from_rgb(255, 191, 0);
 
-                                if let Some(conflicting_mods) = &report.conflicting_mods {
-                                    if !conflicting_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new("⚠ Mods(s) with conflicting asset modifications detected")
-                                                .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            conflicting_mods.iter().for_each(|(path, mods)| {
-                                                CollapsingHeader::new(
-                                                    RichText::new(format!(
-                                                        "⚠ Conflicting modification of asset `{}`",
-                                                        path
-                                                    ))
-                                                    .color(AMBER),
-                                                )
-                                                .show(
-                                                    ui,
-                                                    |ui| {
-                                                        mods.iter().for_each(|mod_spec| {
-                                                            ui.label(&mod_spec.url);
-                                                        });
-                                                    },
-                                                );
-                                            });
-                                        });
-                                    }
-                                }
-
-                                if let Some(asset_register_bin_mods) = &report.asset_register_bin_mods {
-                                    if !asset_register_bin_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new("ℹ Mod(s) with `AssetRegistry.bin` included detected")
-                                                .color(Color32::LIGHT_BLUE),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            asset_register_bin_mods.iter().for_each(
-                                                |(r#mod, paths)| {
-                                                    CollapsingHeader::new(
-                                                        RichText::new(format!(
-                                                        "ℹ {} includes one or more `AssetRegistry.bin`",
-                                                        r#mod.url
-                                                    ))
-                                                        .color(Color32::LIGHT_BLUE),
-                                                    )
-                                                    .show(ui, |ui| {
-                                                        paths.iter().for_each(|path| {
-                                                            ui.label(path);
-                                                        });
-                                                    });
-                                                },
-                                            );
-                                        });
-                                    }
-                                }
-
-                                if let Some(shader_file_mods) = &report.shader_file_mods {
-                                    if !shader_file_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new(
-                                                "⚠ Mods(s) with shader files included detected",
-                                            )
-                                            .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            shader_file_mods.iter().for_each(
-                                                |(r#mod, shader_files)| {
-                                                    CollapsingHeader::new(
-                                                        RichText::new(format!(
-                                                            "⚠ {} includes one or more shader files",
-                                                            r#mod.url
-                                                        ))
-                                                        .color(AMBER),
-                                                    )
-                                                    .show(ui, |ui| {
-                                                        shader_files.iter().for_each(|shader_file| {
-                                                            ui.label(shader_file);
-                                                        });
-                                                    });
-                                                },
-                                            );
-                                        });
-                                    }
-                                }
-
-                                if let Some(outdated_pak_version_mods) = &report.outdated_pak_version_mods {
-                                    if !outdated_pak_version_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new(
-                                                "⚠ Mod(s) with outdated pak version detected",
-                                            )
-                                            .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            outdated_pak_version_mods.iter().for_each(
-                                                |(r#mod, version)| {
-                                                    ui.label(
-                                                        RichText::new(format!(
-                                                            "⚠ {} includes outdated pak version {}",
-                                                            r#mod.url, version
-                                                        ))
-                                                        .color(AMBER),
-                                                    );
-                                                },
-                                            );
-                                        });
-                                    }
-                                }
-
-                                if let Some(empty_archive_mods) = &report.empty_archive_mods {
-                                    if !empty_archive_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new(
-                                                "⚠ Mod(s) with empty archives detected",
-                                            )
-                                            .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            empty_archive_mods.iter().for_each(|r#mod| {
-                                                ui.label(
-                                                    RichText::new(format!(
-                                                        "⚠ {} contains an empty archive",
-                                                        r#mod.url
-                                                    ))
-                                                    .color(AMBER),
-                                                );
-                                            });
-                                        });
-                                    }
-                                }
-
-                                if let Some(archive_with_only_non_pak_files_mods) = &report.archive_with_only_non_pak_files_mods {
-                                    if !archive_with_only_non_pak_files_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new(
-                                                "⚠ Mod(s) with only non-`.pak` files detected",
-                                            )
-                                            .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            archive_with_only_non_pak_files_mods.iter().for_each(|r#mod| {
-                                                ui.label(
-                                                    RichText::new(format!(
-                                                        "⚠ {} contains only non-`.pak` files, perhaps the author forgot to pack it?",
-                                                        r#mod.url
-                                                    ))
-                                                    .color(AMBER),
-                                                );
-                                            });
-                                        });
-                                    }
-                                }
-
-                                if let Some(archive_with_multiple_paks_mods) = &report.archive_with_multiple_paks_mods {
-                                    if !archive_with_multiple_paks_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new(
-                                                "⚠ Mod(s) with multiple `.pak`s detected",
-                                            )
-                                            .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            archive_with_multiple_paks_mods.iter().for_each(|r#mod| {
-                                                ui.label(RichText::new(format!(
-                                                    "⚠ {} contains multiple `.pak`s, only the first encountered `.pak` will be loaded",
-                                                    r#mod.url
-                                                ))
-                                                .color(AMBER));
-                                            });
-                                        });
-                                    }
-                                }
-
-                                if let Some(non_asset_file_mods) = &report.non_asset_file_mods {
-                                    if !non_asset_file_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new(
-                                                "⚠ Mod(s) with non-asset files detected",
-                                            )
-                                            .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            non_asset_file_mods.iter().for_each(|(r#mod, files)| {
-                                                CollapsingHeader::new(
-                                                    RichText::new(format!(
-                                                        "⚠ {} includes non-asset files",
-                                                        r#mod.url
-                                                    ))
-                                                    .color(AMBER),
-                                                )
-                                                .show(ui, |ui| {
-                                                    files.iter().for_each(|file| {
-                                                        ui.label(file);
-                                                    });
-                                                });
-                                            });
-                                        });
-                                    }
+                                let denied = report.denied();
+                                if !denied.is_empty() {
+                                    ui.label(
+                                        RichText::new(format!(
+                                            "⛔ Deny-level lints found problems: {}",
+                                            denied
+                                                .iter()
+                                                .map(LintId::name)
+                                                .collect::<Vec<_>>()
+                                                .join(", ")
+                                        ))
+                                        .color(Color32::LIGHT_RED),
+                                    );
                                 }
-
-                                if let Some(split_asset_pairs_mods) = &report.split_asset_pairs_mods {
-                                    if !split_asset_pairs_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new(
-                                                "⚠ Mod(s) with split {uexp, uasset} pairs detected",
-                                            )
-                                            .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            split_asset_pairs_mods.iter().for_each(|(r#mod, files)| {
-                                                CollapsingHeader::new(
-                                                    RichText::new(format!(
-                                                        "⚠ {} includes split {{uexp, uasset}} pairs",
-                                                        r#mod.url
-                                                    ))
-                                                    .color(AMBER),
-                                                )
-                                                .show(ui, |ui| {
-                                                    files.iter().for_each(|(file, kind)| {
-                                                        match kind {
-                                                            SplitAssetPair::MissingUasset => {
-                                                                ui.label(format!("`{file}` missing matching .uasset file"));
-                                                            },
-                                                            SplitAssetPair::MissingUexp => {
-                                                                ui.label(format!("`{file}` missing matching .uexp file"));
-                                                            }
-                                                        }
-                                                    });
-                                                });
-                                            });
-                                        });
-                                    }
+                                if report.is_empty() {
+                                    ui.label("No problems found");
                                 }
 
-                                if let Some(unmodified_game_assets_mods) = &report.unmodified_game_assets_mods {
-                                    if !unmodified_game_assets_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new(
-                                                "⚠ Mod(s) with unmodified game assets detected",
-                                            )
-                                            .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            unmodified_game_assets_mods.iter().for_each(|(r#mod, files)| {
-                                                CollapsingHeader::new(
-                                                    RichText::new(format!(
-                                                        "⚠ {} includes unmodified game assets",
-                                                        r#mod.url
-                                                    ))
-                                                    .color(AMBER),
-                                                )
-                                                .show(ui, |ui| {
-                                                    files.iter().for_each(|file| {
-                                                        ui.label(file);
-                                                    });
-                                                });
-                                            });
-                                        });
+                                for (lint, level) in &report.levels {
+                                    let diagnostics = report.for_lint(*lint).collect::<Vec<_>>();
+                                    if diagnostics.is_empty() {
+                                        continue;
                                     }
+                                    let (icon, color) = match level {
+                                        LintLevel::Deny => ("⛔", Color32::LIGHT_RED),
+                                        _ => ("⚠", AMBER),
+                                    };
+                                    let description =
+                                        lint.factory().map_or(lint.name(), |f| f.description);
+                                    CollapsingHeader::new(
+                                        RichText::new(format!(
+                                            "{icon} {description} ({})",
+                                            diagnostics.len()
+                                        ))
+                                        .color(color),
+                                    )
+                                    .id_source(lint.name())
+                                    .default_open(true)
+                                    .show(ui, |ui| {
+                                        for diagnostic in diagnostics {
+                                            ui.label(diagnostic_text(diagnostic));
+                                        }
+                                    });
                                 }
                             });
+                        if ui.button("Export report").clicked()
+                            && let Some(path) = rfd::FileDialog::new()
+                                .add_filter("HTML", &["html"])
+                                .add_filter("Markdown", &["md"])
+                                .set_file_name("lint-report.html")
+                                .save_file()
+                        {
+                            let format = ReportFormat::from_path(&path);
+                            self.last_action_status =
+                                match std::fs::write(&path, report.render(format)) {
+                                    Ok(()) => LastActionStatus::Success(format!(
+                                        "exported lint report to {}",
+                                        path.display()
+                                    )),
+                                    Err(e) => LastActionStatus::Failure(format!(
+                                        "failed to export lint report: {e}"
+                                    )),
+                                };
+                        }
                     } else {
                         ui.spinner();
                         ui.label("Lint report generating...");
@@ -1439,12 +1474,28 @@ impl App {
 
             if !open {
                 self.lint_report_window = None;
-                self.lint_rid = None;
+                if let Some(rid) = self.lint_rid.take() {
+                    rid.cancel();
+                }
             }
         }
     }
 }
 
+/// One line summary of a diagnostic: the mods involved, the offending path and the message.
+fn diagnostic_text(diagnostic: &Diagnostic) -> String {
+    let mods = diagnostic
+        .mods
+        .iter()
+        .map(|m| m.url.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    match &diagnostic.path {
+        Some(path) => format!("{mods}: `{path}` {}", diagnostic.message),
+        None => format!("{mods}: {}", diagnostic.message),
+    }
+}
+
 struct WindowProviderParameters {
     tx: Sender<(RequestID, Result<()>)>,
     rx: Receiver<(RequestID, Result<()>)>,
@@ -1462,12 +1513,7 @@ impl WindowProviderParameters {
             rx,
             check_rid: None,
             check_error: None,
-            parameters: state
-                .config
-                .provider_parameters
-                .get(factory.id)
-                .cloned()
-                .unwrap_or_default(),
+            parameters: state.provider_parameters(factory.id),
             factory,
         }
     }
@@ -1495,7 +1541,27 @@ impl WindowSettings {
 
 struct WindowLintReport;
 
-struct WindowLintsToggle;
+struct WindowProfileDiff {
+    old: String,
+    /// Profile to compare, or `None` to compare the pasted profile code.
+    new: Option<String>,
+    code: String,
+}
+
+impl WindowProfileDiff {
+    fn new(state: &State) -> Self {
+        Self {
+            old: state.mod_data.active_profile.clone(),
+            new: Some(state.mod_data.active_profile.clone()),
+            code: String::new(),
+        }
+    }
+}
+
+struct WindowLintsToggle {
+    /// Lint config of the active profile the toggles were initialized from.
+    config: LintConfig,
+}
 
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
@@ -1542,6 +1608,7 @@ impl eframe::App for App {
         self.show_settings(ctx);
         self.show_lints_toggle(ctx);
         self.show_lint_report(ctx);
+        self.show_profile_diff(ctx);
 
         egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
             ui.with_layout(egui::Layout::right_to_left(Align::TOP), |ui| {
@@ -1590,15 +1657,33 @@ impl eframe::App for App {
                                 });
 
                             if button.clicked() {
-                                self.last_action_status = LastActionStatus::Idle;
-                                self.integrate_rid = Some(message::Integrate::send(
-                                    &mut self.request_counter,
-                                    self.state.store.clone(),
-                                    mods,
-                                    self.state.config.drg_pak_path.as_ref().unwrap().clone(),
-                                    self.tx.clone(),
-                                    ctx.clone(),
-                                ));
+                                self.integrate(ctx, &active_profile, mods);
+                            }
+                        });
+
+                        ui.add_enabled_ui(self.state.config.drg_pak_path.is_some(), |ui| {
+                            let active_profile = self.state.mod_data.active_profile.clone();
+                            let subset = self
+                                .state
+                                .mod_data
+                                .client_only_subset_cached(&active_profile, &self.state.store);
+                            let button = ui
+                                .button(format!("Install client-only ({})", subset.mods.len()))
+                                .on_hover_ui(|ui| {
+                                    ui.label(
+                                        "Install only mods which are safe to use in public lobbies: \
+                                        not required, verified or approved on mod.io and tagged \
+                                        QoL, visual or audio.",
+                                    );
+                                    for (spec, exclusion) in &subset.excluded {
+                                        ui.label(format!("Skipped {}: {exclusion}", spec.url));
+                                    }
+                                });
+                            if button.clicked() {
+                                for (spec, exclusion) in &subset.excluded {
+                                    info!("skipping {}: {exclusion}", spec.url);
+                                }
+                                self.integrate(ctx, &active_profile, subset.mods);
                             }
                         });
 
@@ -1658,13 +1743,13 @@ impl eframe::App for App {
                 );
                 if self.integrate_rid.is_some() {
                     if ui.button("Cancel").clicked() {
-                        self.integrate_rid.take().unwrap().handle.abort();
+                        self.integrate_rid.take().unwrap().cancel();
                     }
                     ui.spinner();
                 }
                 if self.update_rid.is_some() {
                     if ui.button("Cancel").clicked() {
-                        self.update_rid.take().unwrap().handle.abort();
+                        self.update_rid.take().unwrap().cancel();
                     }
                     ui.spinner();
                 }
@@ -1673,11 +1758,33 @@ impl eframe::App for App {
                     .on_hover_text("Lint mods in the current profile")
                     .clicked()
                 {
-                    self.lints_toggle_window = Some(WindowLintsToggle);
+                    let config = self
+                        .state
+                        .lint_config(Some(&self.state.mod_data.active_profile))
+                        .unwrap_or_else(|e| {
+                            error!("failed to load lint config: {e:#}");
+                            self.last_action_status = LastActionStatus::Failure(format!(
+                                "failed to load lint config: {e:#}"
+                            ));
+                            LintConfig::default()
+                        });
+                    self.lint_options = lint_factories()
+                        .into_iter()
+                        .map(|f| (f.id, config.level(f.id) != LintLevel::Allow))
+                        .collect();
+                    self.lints_toggle_window = Some(WindowLintsToggle { config });
                 }
                 if ui.button("⚙").on_hover_text("Open settings").clicked() {
                     self.settings_window = Some(WindowSettings::new(&self.state));
                 }
+                let metrics = self.request_counter.network_metrics();
+                if metrics.requests > 0 {
+                    ui.label(format!("⇅ {}", metrics.requests))
+                        .on_hover_text(format!(
+                        "Network requests: {}\nRetried after being rate limited: {}\nThrottled: {}",
+                        metrics.requests, metrics.retried, metrics.throttled
+                    ));
+                }
                 if let Some(available_update) = &self.available_update {
                     if ui
                         .button(egui::RichText::new("\u{26A0}").color(ui.visuals().warn_fg_color))
@@ -1726,6 +1833,7 @@ impl eframe::App for App {
             );
             // profile selection
 
+            let mut compare_profiles = false;
             let buttons = |ui: &mut Ui, mod_data: &mut ModData| {
                 if ui
                     .button("📋")
@@ -1740,6 +1848,13 @@ impl eframe::App for App {
                     let mods = Self::build_mod_string(&mods);
                     ui.output_mut(|o| o.copied_text = mods);
                 }
+                if ui
+                    .button("⇄")
+                    .on_hover_text_at_pointer("Compare profiles")
+                    .clicked()
+                {
+                    compare_profiles = true;
+                }
 
                 // TODO find better icon, flesh out multiple-view usage, fix GUI locking
                 /*
@@ -1759,7 +1874,10 @@ impl eframe::App for App {
                 self.state.mod_data.deref_mut().deref_mut(),
                 Some(buttons),
             ) {
-                self.state.mod_data.save().unwrap();
+                self.save_mod_data();
+            }
+            if compare_profiles {
+                self.profile_diff_window = Some(WindowProfileDiff::new(&self.state));
             }
 
             ui.separator();
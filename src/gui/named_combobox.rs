@@ -4,7 +4,7 @@ use super::{colors, custom_popup_above_or_below_widget, is_committed};
 
 use eframe::egui;
 
-use crate::state::{ModData_v0_1_0 as ModData, ModProfile_v0_1_0 as ModProfile};
+use crate::state::{ModData_v0_2_0 as ModData, ModProfile_v0_2_0 as ModProfile};
 
 #[derive(Debug, Clone)]
 struct NamePopup {
@@ -57,9 +57,9 @@ impl NamedEntries<ModProfile> for ModData {
         self.remove_active_profile();
     }
     fn rename_selected(&mut self, new_name: String) {
-        let tmp = self.profiles.remove(&self.active_profile).unwrap();
-        self.profiles.insert(new_name.clone(), tmp);
-        self.active_profile = new_name;
+        let name = self.active_profile.clone();
+        // also updates the profiles inheriting from it
+        self.rename_profile(&name, &new_name).unwrap();
     }
     fn duplicate_selected(&mut self, new_name: String) {
         let new = self.get_active_profile().clone();
@@ -1,3 +1,5 @@
+use crate::providers::scheduler::{RequestMetricsSnapshot, RequestScheduler};
+
 /// Simple counter that returns a new ID each time it is called
 #[derive(Default)]
 pub struct RequestCounter(u32);
@@ -9,6 +11,11 @@ impl RequestCounter {
         self.0 += 1;
         RequestID { id }
     }
+
+    /// Network request metrics collected by the shared request scheduler
+    pub fn network_metrics(&self) -> RequestMetricsSnapshot {
+        RequestScheduler::global().metrics()
+    }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -1,16 +1,19 @@
+pub(crate) mod merge;
+
 use std::collections::{HashMap, HashSet};
 use std::ffi::{OsStr, OsString};
-use std::fs::OpenOptions;
 use std::io::{self, BufReader, BufWriter, Cursor, ErrorKind, Read, Seek};
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use repak::PakWriter;
-use tracing::info;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
 use uasset_utils::splice::{
     extract_tracked_statements, inject_tracked_statements, walk, AssetVersion, TrackedStatement,
 };
 
+use crate::error::Cancelled;
 use crate::providers::ModInfo;
 use crate::{get_pak_from_data, open_file, DRGInstallation};
 
@@ -36,6 +39,55 @@ use unreal_asset::{
     Asset,
 };
 
+/// Assets added to every installation, such as the mod integration's own blueprints.
+pub(crate) const INTEGRATION_PAK: &[u8] = include_bytes!("../assets/integration.pak");
+
+/// Asset the mod integration injects into to spawn mods, and on which the in-game mod list relies.
+pub(crate) const PCB_PATH: &str = "FSD/Content/Game/BP_PlayerControllerBase";
+
+/// Assets patched to mark the game as modded and to show and join modded servers.
+pub(crate) const PATCH_PATHS: [&str; 6] = [
+    "FSD/Content/Game/BP_GameInstance",
+    "FSD/Content/Game/SpaceRig/BP_PlayerController_SpaceRig",
+    "FSD/Content/Game/StartMenu/Bp_StartMenu_PlayerController",
+    "FSD/Content/UI/Menu_DeepDives/ITM_DeepDives_Join",
+    "FSD/Content/UI/Menu_ServerList/_MENU_ServerList",
+    "FSD/Content/UI/Menu_ServerList/WND_JoiningModded",
+];
+
+/// Directory of the mod integration's own assets.
+pub(crate) const INTEGRATION_DIR: &str = "FSD/Content/_AssemblyStorm/ModIntegration/";
+
+/// Effect of a mod shipping its own copy of the file at `path`, relative to the game root, if it
+/// is one the mod integration patches or provides.
+pub(crate) fn integration_critical_effect(path: &str) -> Option<&'static str> {
+    if path
+        .get(..INTEGRATION_DIR.len())
+        .is_some_and(|dir| dir.eq_ignore_ascii_case(INTEGRATION_DIR))
+    {
+        return Some(
+            "the mod integration provides this file itself; a different copy may break the in-game \
+             mod list and spawning of mods",
+        );
+    }
+    let stem = path
+        .strip_suffix(".uasset")
+        .or_else(|| path.strip_suffix(".uexp"))?;
+    if stem.eq_ignore_ascii_case(PCB_PATH) {
+        Some(
+            "the mod integration patches this asset to spawn mods and show the in-game mod list; \
+             the mod's copy replaces the game's as the base of the patch",
+        )
+    } else if PATCH_PATHS.iter().any(|p| stem.eq_ignore_ascii_case(p)) {
+        Some(
+            "the mod integration patches this asset to mark the game as modded and show modded \
+             servers; the mod's copy replaces the game's as the base of the patch",
+        )
+    } else {
+        None
+    }
+}
+
 /// Why does the uninstall function require a list of Modio mod IDs?
 /// Glad you ask. The official integration enables *every mod the user has installed* once it gets
 /// re-enabled. We do the user a favor and collect all the installed mods and explicitly add them
@@ -153,10 +205,30 @@ pub enum IntegrationErrKind {
     UnrealAsset(unreal_asset::Error),
 }
 
+/// Build `mods_P.pak` from the given mods. The pak is written to a temporary file and only moved
+/// into place once complete, so cancelling via `cancel` leaves any existing installation untouched.
+///
+/// Where several mods contain the same file, the one listed first wins. With `merge_tables`,
+/// data tables contained in several mods are instead merged row by row against the game's
+/// version. Curve and string tables keep their rows in data which is not parsed, so they are not
+/// merged.
 pub fn integrate<P: AsRef<Path>>(
     path_pak: P,
     mods: Vec<(ModInfo, PathBuf)>,
+    merge_tables: bool,
+    cancel: &CancellationToken,
 ) -> Result<(), IntegrationErr> {
+    let check_cancelled = || {
+        if cancel.is_cancelled() {
+            Err(IntegrationErr {
+                mod_ctxt: None,
+                kind: IntegrationErrKind::Generic(Cancelled.into()),
+            })
+        } else {
+            Ok(())
+        }
+    };
+
     let installation = DRGInstallation::from_pak_path(&path_pak).map_err(|e| IntegrationErr {
         mod_ctxt: None,
         kind: IntegrationErrKind::Generic(e),
@@ -265,20 +337,10 @@ pub fn integrate<P: AsRef<Path>>(
         )
     }
 
-    let pcb_path = "FSD/Content/Game/BP_PlayerControllerBase";
-    let patch_paths = [
-        "FSD/Content/Game/BP_GameInstance",
-        "FSD/Content/Game/SpaceRig/BP_PlayerController_SpaceRig",
-        "FSD/Content/Game/StartMenu/Bp_StartMenu_PlayerController",
-        "FSD/Content/UI/Menu_DeepDives/ITM_DeepDives_Join",
-        "FSD/Content/UI/Menu_ServerList/_MENU_ServerList",
-        "FSD/Content/UI/Menu_ServerList/WND_JoiningModded",
-    ];
-
     let mut deferred_assets: HashMap<&str, RawAsset> = HashMap::from_iter(
-        [pcb_path]
+        [PCB_PATH]
             .iter()
-            .chain(patch_paths.iter())
+            .chain(PATCH_PATHS.iter())
             .map(|path| (*path, RawAsset::default())),
     );
 
@@ -305,23 +367,24 @@ pub fn integrate<P: AsRef<Path>>(
         })?;
     }
 
+    // removed on drop unless persisted, so an aborted integration never leaves a partial pak
+    let (mod_pak_file, mod_pak_tmp_path) =
+        tempfile::NamedTempFile::new_in(installation.paks_path())
+            .map_err(|e| IntegrationErr {
+                mod_ctxt: None,
+                kind: IntegrationErrKind::Generic(e.into()),
+            })?
+            .into_parts();
     let mut mod_pak = repak::PakWriter::new(
-        BufWriter::new(
-            OpenOptions::new()
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open(&path_mod_pak)
-                .map_err(|e| IntegrationErr {
-                    mod_ctxt: None,
-                    kind: IntegrationErrKind::Generic(e.into()),
-                })?,
-        ),
+        BufWriter::new(mod_pak_file),
         repak::Version::V11,
         "../../../".to_string(),
         None,
     );
 
+    // nothing in the installation has been modified yet
+    check_cancelled()?;
+
     #[cfg(feature = "hook")]
     {
         let path_hook_dll = installation
@@ -347,7 +410,25 @@ pub fn integrate<P: AsRef<Path>>(
 
     let mut added_paths = HashSet::new();
 
+    // assets contained in several mods which may be mergeable tables, collected from every mod
+    // and written once all mods have been read
+    let mergeable_paths = if merge_tables {
+        let mut paths = merge::conflicting_assets(&mods).map_err(|e| IntegrationErr {
+            mod_ctxt: None,
+            kind: IntegrationErrKind::Generic(e),
+        })?;
+        for path in deferred_assets.keys() {
+            paths.remove(&path.to_ascii_lowercase());
+        }
+        paths
+    } else {
+        HashSet::new()
+    };
+    let mut mergeable_assets: HashMap<String, (String, Vec<(&ModInfo, RawAsset)>)> = HashMap::new();
+
     for (mod_info, path) in &mods {
+        check_cancelled()?;
+
         let raw_mod_file = open_file(path).map_err(|e| IntegrationErr {
             mod_ctxt: Some(mod_info.clone()),
             kind: IntegrationErrKind::Generic(e),
@@ -366,6 +447,8 @@ pub fn integrate<P: AsRef<Path>>(
         let mount = Path::new(pak.mount_point());
 
         for p in pak.files() {
+            check_cancelled()?;
+
             let j = mount.join(&p);
             let new_path = j
                 .strip_prefix("../../../")
@@ -376,6 +459,9 @@ pub fn integrate<P: AsRef<Path>>(
                 })?;
             let new_path_str = &new_path.to_string_lossy().replace('\\', "/");
             let lowercase = new_path_str.to_ascii_lowercase();
+            if let Some(effect) = integration_critical_effect(new_path_str) {
+                warn!("{} contains {new_path_str}: {effect}", mod_info.name);
+            }
             if added_paths.contains(&lowercase) {
                 continue;
             }
@@ -401,7 +487,30 @@ pub fn integrate<P: AsRef<Path>>(
                 mod_ctxt: Some(mod_info.clone()),
                 kind: IntegrationErrKind::Repak(e),
             })?;
-            if let Some(raw) = new_path_str
+            let mergeable = [".uasset", ".uexp"].into_iter().find_map(|ext| {
+                let stem = lowercase.strip_suffix(ext)?;
+                mergeable_paths.contains(stem).then_some((stem, ext))
+            });
+            if let Some((stem, ext)) = mergeable {
+                let (_, copies) = mergeable_assets.entry(stem.to_string()).or_insert_with(|| {
+                    (
+                        new_path_str[..new_path_str.len() - ext.len()].to_string(),
+                        vec![],
+                    )
+                });
+                if !copies
+                    .last()
+                    .is_some_and(|(m, _)| std::ptr::eq(*m, mod_info))
+                {
+                    copies.push((mod_info, RawAsset::default()));
+                }
+                let raw = &mut copies.last_mut().unwrap().1;
+                if ext == ".uasset" {
+                    raw.uasset = Some(file_data);
+                } else {
+                    raw.uexp = Some(file_data);
+                }
+            } else if let Some(raw) = new_path_str
                 .strip_suffix(".uasset")
                 .and_then(|path| deferred_assets.get_mut(path))
             {
@@ -421,20 +530,101 @@ pub fn integrate<P: AsRef<Path>>(
         }
     }
 
+    for (path, copies) in mergeable_assets.into_values() {
+        check_cancelled()?;
+
+        let winner = copies[0].0;
+        let mut base = vec![];
+        for ext in [".uasset", ".uexp"] {
+            base.push(
+                match fsd_pak.get(&format!("{path}{ext}"), &mut fsd_pak_reader) {
+                    Ok(file) => Ok(Some(file)),
+                    Err(repak::Error::MissingEntry(_)) => Ok(None),
+                    Err(e) => Err(e),
+                }
+                .map_err(|e| IntegrationErr {
+                    mod_ctxt: None,
+                    kind: IntegrationErrKind::Repak(e),
+                })?,
+            );
+        }
+        let base = match (base[0].take(), base[1].take()) {
+            (Some(uasset), Some(uexp)) => Some((uasset, uexp)),
+            _ => None,
+        };
+        // only complete pairs can be parsed, otherwise the files are added as they are
+        let complete = copies
+            .iter()
+            .map(|(_, raw)| Some((raw.uasset.clone()?, raw.uexp.clone()?)))
+            .collect::<Option<Vec<_>>>();
+        let merged = match complete {
+            Some(complete) => merge::merge_table(base.as_ref(), &complete),
+            None => Ok(None),
+        };
+
+        match merged {
+            Ok(Some(merged)) => {
+                info!("merged {} mods' changes to {path}", copies.len());
+                if !merged.conflicts.is_empty() {
+                    warn!(
+                        "mods make conflicting changes to {path}, using those of {}: {}",
+                        winner.name,
+                        merged
+                            .conflicts
+                            .iter()
+                            .map(String::as_str)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                }
+                for (data, ext) in [(&merged.data.0, ".uasset"), (&merged.data.1, ".uexp")] {
+                    write_file(&mut mod_pak, data, &format!("{path}{ext}")).map_err(|e| {
+                        IntegrationErr {
+                            mod_ctxt: None,
+                            kind: IntegrationErrKind::Generic(e),
+                        }
+                    })?;
+                }
+            }
+            merged => {
+                if let Err(e) = merged {
+                    warn!(
+                        "failed to merge {path}, using {}'s copy: {e:#}",
+                        winner.name
+                    );
+                }
+                // same as without merging, the first mod containing each file wins
+                let uasset = copies.iter().find_map(|(_, raw)| raw.uasset.as_ref());
+                let uexp = copies.iter().find_map(|(_, raw)| raw.uexp.as_ref());
+                for (data, ext) in [(uasset, ".uasset"), (uexp, ".uexp")] {
+                    let Some(data) = data else {
+                        continue;
+                    };
+                    write_file(&mut mod_pak, data, &format!("{path}{ext}")).map_err(|e| {
+                        IntegrationErr {
+                            mod_ctxt: None,
+                            kind: IntegrationErrKind::Generic(e),
+                        }
+                    })?;
+                }
+            }
+        }
+    }
+
     {
-        let mut pcb_asset = deferred_assets[&pcb_path]
+        let mut pcb_asset = deferred_assets[&PCB_PATH]
             .parse()
             .map_err(|e| IntegrationErr {
                 mod_ctxt: None,
                 kind: IntegrationErrKind::Generic(e),
             })?;
         hook_pcb(&mut pcb_asset);
-        write_asset(&mut mod_pak, pcb_asset, pcb_path).map_err(|e| IntegrationErr {
+        write_asset(&mut mod_pak, pcb_asset, PCB_PATH).map_err(|e| IntegrationErr {
             mod_ctxt: None,
             kind: IntegrationErrKind::Generic(e),
         })?;
     }
-    for patch_path in patch_paths {
+    for patch_path in PATCH_PATHS {
         let mut asset = deferred_assets[&patch_path]
             .parse()
             .map_err(|e| IntegrationErr {
@@ -451,7 +641,7 @@ pub fn integrate<P: AsRef<Path>>(
         })?;
     }
 
-    let mut int_pak_reader = Cursor::new(include_bytes!("../assets/integration.pak"));
+    let mut int_pak_reader = Cursor::new(INTEGRATION_PAK);
     let int_pak = repak::PakReader::new_any(&mut int_pak_reader).map_err(|e| IntegrationErr {
         mod_ctxt: None,
         kind: IntegrationErrKind::Repak(e),
@@ -560,11 +750,20 @@ pub fn integrate<P: AsRef<Path>>(
         }
     })?;
 
+    check_cancelled()?;
+
     mod_pak.write_index().map_err(|e| IntegrationErr {
         mod_ctxt: None,
         kind: IntegrationErrKind::Repak(e),
     })?;
 
+    mod_pak_tmp_path
+        .persist(&path_mod_pak)
+        .map_err(|e| IntegrationErr {
+            mod_ctxt: None,
+            kind: IntegrationErrKind::Generic(e.into()),
+        })?;
+
     info!(
         "{} mods installed to {}",
         mods.len(),
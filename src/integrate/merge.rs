@@ -0,0 +1,426 @@
+//! Three-way merging of table assets which several mods modify.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::{BufReader, Cursor, Read, Seek};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use unreal_asset::exports::{Export, ExportBaseTrait, ExportNormalTrait};
+use unreal_asset::properties::{Property, PropertyDataTrait};
+use unreal_asset::Asset;
+
+use crate::providers::ModInfo;
+use crate::{game_path, get_pak_from_data, normalize_path, open_file, parse_asset, AssetData};
+
+/// Classes of assets which are merged row by row. Curve and string tables serialize their rows
+/// after the properties where they are only available as unparsed data, so they are left out.
+const TABLE_CLASSES: [&str; 2] = ["DataTable", "CompositeDataTable"];
+
+/// Lowercase paths, without extension, of `.uasset`s contained in more than one mod.
+pub(crate) fn conflicting_assets(mods: &[(ModInfo, PathBuf)]) -> Result<HashSet<String>> {
+    let mut seen = HashSet::new();
+    let mut conflicting = HashSet::new();
+    for (mod_info, path) in mods {
+        let mut buf = get_pak_from_data(Box::new(BufReader::new(open_file(path)?)))
+            .with_context(|| format!("failed to read mod {}", mod_info.spec.url))?;
+        let pak = repak::PakReader::new_any(&mut buf)?;
+        let mount = Path::new(pak.mount_point());
+        let stems = pak
+            .files()
+            .into_iter()
+            .filter_map(|p| {
+                let path = normalize_path(&game_path(mount, &p)?);
+                path.strip_suffix(".uasset").map(str::to_string)
+            })
+            .collect::<HashSet<_>>();
+        for stem in stems {
+            if !seen.insert(stem.clone()) {
+                conflicting.insert(stem);
+            }
+        }
+    }
+    Ok(conflicting)
+}
+
+pub(crate) struct Merged {
+    pub data: AssetData,
+    /// Rows and properties which more than one mod changed differently. The highest priority
+    /// mod's value was kept for each.
+    pub conflicts: BTreeSet<String>,
+}
+
+/// Three-way merge `copies` of an asset, highest priority first, against the game's version.
+/// Returns `None` if the asset is not a table.
+pub(crate) fn merge_table(
+    base: Option<&AssetData>,
+    copies: &[AssetData],
+) -> Result<Option<Merged>> {
+    let base = base
+        .map(|(uasset, uexp)| parse_asset(uasset.as_slice(), uexp.as_slice()))
+        .transpose()
+        .context("failed to parse game asset")?;
+    let mut parsed = copies
+        .iter()
+        .map(|(uasset, uexp)| parse_asset(uasset.as_slice(), uexp.as_slice()))
+        .collect::<Result<Vec<_>>>()?;
+
+    if !parsed.iter().all(is_table) {
+        return Ok(None);
+    }
+    for copy in &parsed[1..] {
+        if !same_imports(&parsed[0], copy) {
+            bail!("mods import different packages");
+        }
+    }
+    if let Some(base) = &base
+        && base.asset_data.exports.len() != parsed[0].asset_data.exports.len()
+    {
+        bail!("mods add or remove exports");
+    }
+    let export_count = parsed[0].asset_data.exports.len();
+    if parsed
+        .iter()
+        .any(|copy| copy.asset_data.exports.len() != export_count)
+    {
+        bail!("mods add or remove exports");
+    }
+
+    let mut conflicts = BTreeSet::new();
+    for i in 0..export_count {
+        let export_name = parsed[0].asset_data.exports[i]
+            .get_base_export()
+            .object_name
+            .get_owned_content();
+        let base_export = base.as_ref().map(|b| &b.asset_data.exports[i]);
+
+        // properties
+        let merged = {
+            let base_properties = base_export.map(properties).transpose()?.unwrap_or_default();
+            let copy_properties = parsed
+                .iter()
+                .map(|c| properties(&c.asset_data.exports[i]))
+                .collect::<Result<Vec<_>>>()?;
+            merge_keyed(
+                &base_properties,
+                &copy_properties,
+                property_key,
+                &export_name,
+                &mut conflicts,
+            )
+        };
+        set_properties(&mut parsed[0].asset_data.exports[i], merged);
+
+        // data table rows
+        if let Export::DataTableExport(_) = &parsed[0].asset_data.exports[i] {
+            let rows = |export: &Export| match export {
+                Export::DataTableExport(data_table) => Ok(data_table.table.data.clone()),
+                _ => bail!("export {export_name} is not a data table in every copy"),
+            };
+            let base_rows = base_export.map(rows).transpose()?.unwrap_or_default();
+            let copy_rows = parsed
+                .iter()
+                .map(|c| rows(&c.asset_data.exports[i]))
+                .collect::<Result<Vec<_>>>()?;
+            let merged = merge_keyed(
+                &base_rows,
+                &copy_rows,
+                |row| row.name.get_owned_content(),
+                &export_name,
+                &mut conflicts,
+            );
+            if let Export::DataTableExport(data_table) = &mut parsed[0].asset_data.exports[i] {
+                data_table.table.data = merged;
+            }
+        }
+
+        // data following the properties which is not parsed
+        let extras = |export: &Export| export.get_normal_export().map(|e| e.extras.clone());
+        let base_extras = base_export.and_then(extras);
+        let copy_extras = parsed
+            .iter()
+            .map(|c| extras(&c.asset_data.exports[i]))
+            .collect::<Vec<_>>();
+        let changed = copy_extras
+            .iter()
+            .filter(|e| **e != base_extras)
+            .collect::<Vec<_>>();
+        if changed.windows(2).any(|w| w[0] != w[1]) {
+            bail!("mods change unparsed data of export {export_name}");
+        }
+        if let Some(Some(extras)) = changed.first()
+            && let Some(normal_export) = parsed[0].asset_data.exports[i].get_normal_export_mut()
+        {
+            normal_export.extras = extras.clone();
+        }
+    }
+
+    let mut merged = parsed.swap_remove(0);
+    merged.rebuild_name_map();
+    let mut data = (Cursor::new(vec![]), Cursor::new(vec![]));
+    merged.write_data(&mut data.0, Some(&mut data.1))?;
+
+    Ok(Some(Merged {
+        data: (data.0.into_inner(), data.1.into_inner()),
+        conflicts,
+    }))
+}
+
+/// Whether any of the asset's exports is of a table class.
+fn is_table<C: Read + Seek>(asset: &Asset<C>) -> bool {
+    asset.asset_data.exports.iter().any(|export| {
+        let class_index = export.get_base_export().class_index;
+        class_index.is_import()
+            && asset
+                .imports
+                .get((-class_index.index - 1) as usize)
+                .is_some_and(|import| {
+                    import
+                        .object_name
+                        .get_content(|name| TABLE_CLASSES.contains(&name))
+                })
+    })
+}
+
+/// Object references in properties are indices into the import table, so copies can only be
+/// merged or compared if their import tables match.
+pub(crate) fn same_imports<C: Read + Seek>(a: &Asset<C>, b: &Asset<C>) -> bool {
+    let names = |asset: &Asset<C>| {
+        asset
+            .imports
+            .iter()
+            .map(|import| {
+                (
+                    import.class_package.get_owned_content(),
+                    import.class_name.get_owned_content(),
+                    import.object_name.get_owned_content(),
+                    import.outer_index,
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    names(a) == names(b)
+}
+
+fn properties(export: &Export) -> Result<Vec<Property>> {
+    export
+        .get_normal_export()
+        .map(|e| e.properties.clone())
+        .context("export has no properties")
+}
+
+fn set_properties(export: &mut Export, properties: Vec<Property>) {
+    if let Some(normal_export) = export.get_normal_export_mut() {
+        normal_export.properties = properties;
+    }
+}
+
+fn property_key(property: &Property) -> String {
+    format!(
+        "{}[{}]",
+        property.get_name().get_owned_content(),
+        property.get_duplication_index()
+    )
+}
+
+/// Changes which several copies of a keyed collection make to a common base, shared by merging
+/// and the `asset_conflicts` lint.
+pub(crate) struct Diff<'a, V> {
+    /// Values each changed key was changed to, in the order of the copies changing it, `None`
+    /// where a copy removed the key.
+    changes: BTreeMap<&'a str, Vec<Option<&'a V>>>,
+}
+
+impl<'a, V: PartialEq> Diff<'a, V> {
+    pub fn new(base: &'a BTreeMap<String, V>, copies: &'a [BTreeMap<String, V>]) -> Self {
+        let mut changes: BTreeMap<&str, Vec<Option<&V>>> = BTreeMap::new();
+        for copy in copies {
+            for key in base.keys().chain(copy.keys()).collect::<BTreeSet<_>>() {
+                let value = copy.get(key);
+                if base.get(key) != value {
+                    changes.entry(key.as_str()).or_default().push(value);
+                }
+            }
+        }
+        Self { changes }
+    }
+
+    /// Keys which more than one copy changes to different values.
+    pub fn colliding(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.changes
+            .iter()
+            .filter(|(_, values)| values.windows(2).any(|w| w[0] != w[1]))
+            .map(|(key, _)| *key)
+    }
+
+    /// Value of `key` in the first copy which changes it, `None` if no copy does.
+    pub fn first_change(&self, key: &str) -> Option<Option<&'a V>> {
+        self.changes.get(key).map(|values| values[0])
+    }
+}
+
+/// Three-way merge lists of keyed items, `copies` highest priority first. Keys changed
+/// differently by several copies are added to `conflicts` and get the highest priority copy's
+/// value. Items keep the base order, with items added by lower priority copies appended first.
+fn merge_keyed<T: Clone + PartialEq>(
+    base: &[T],
+    copies: &[Vec<T>],
+    key: impl Fn(&T) -> String,
+    export_name: &str,
+    conflicts: &mut BTreeSet<String>,
+) -> Vec<T> {
+    fn keyed<'a, T>(items: &'a [T], key: &impl Fn(&T) -> String) -> BTreeMap<String, &'a T> {
+        items.iter().map(|item| (key(item), item)).collect()
+    }
+    let base_items = keyed(base, &key);
+    let copy_items = copies
+        .iter()
+        .map(|copy| keyed(copy, &key))
+        .collect::<Vec<_>>();
+    let diff = Diff::new(&base_items, &copy_items);
+    conflicts.extend(diff.colliding().map(|k| format!("{export_name}.{k}")));
+
+    let mut ordered = HashSet::new();
+    base.iter()
+        .map(&key)
+        .chain(
+            copy_items
+                .iter()
+                .rev()
+                .flat_map(|items| items.keys().cloned()),
+        )
+        .filter(|k| ordered.insert(k.clone()))
+        .filter_map(|k| match diff.first_change(&k) {
+            Some(change) => change.map(|item| (*item).clone()),
+            None => base_items.get(&k).map(|item| (*item).clone()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Row = (&'static str, i32);
+
+    fn merge(base: &[Row], copies: &[&[Row]]) -> (Vec<Row>, BTreeSet<String>) {
+        let mut conflicts = BTreeSet::new();
+        let copies = copies.iter().map(|c| c.to_vec()).collect::<Vec<_>>();
+        let merged = merge_keyed(
+            base,
+            &copies,
+            |row| row.0.to_string(),
+            "Table",
+            &mut conflicts,
+        );
+        (merged, conflicts)
+    }
+
+    const BASE: &[Row] = &[("a", 1), ("b", 1), ("c", 1)];
+
+    #[test]
+    fn test_merge_disjoint_edits() {
+        let (merged, conflicts) = merge(
+            BASE,
+            &[
+                &[("a", 2), ("b", 1), ("c", 1)],
+                &[("a", 1), ("b", 1), ("c", 3)],
+            ],
+        );
+        assert_eq!(merged, [("a", 2), ("b", 1), ("c", 3)]);
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_merge_identical_edits() {
+        let copy: &[Row] = &[("a", 1), ("b", 2), ("c", 1)];
+        let (merged, conflicts) = merge(BASE, &[copy, copy]);
+        assert_eq!(merged, copy);
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_merge_conflicting_edits_first_wins() {
+        let (merged, conflicts) = merge(
+            BASE,
+            &[
+                &[("a", 2), ("b", 1), ("c", 1)],
+                &[("a", 3), ("b", 1), ("c", 1)],
+            ],
+        );
+        assert_eq!(merged, [("a", 2), ("b", 1), ("c", 1)]);
+        assert_eq!(conflicts, ["Table.a".to_string()].into());
+
+        // removing a row conflicts with editing it
+        let (merged, conflicts) = merge(
+            BASE,
+            &[&[("a", 1), ("c", 1)], &[("a", 1), ("b", 2), ("c", 1)]],
+        );
+        assert_eq!(merged, [("a", 1), ("c", 1)]);
+        assert_eq!(conflicts, ["Table.b".to_string()].into());
+    }
+
+    #[test]
+    fn test_merge_added_and_removed_rows() {
+        let (merged, conflicts) = merge(
+            BASE,
+            &[
+                &[("a", 1), ("c", 1), ("e", 1)],
+                &[("a", 1), ("b", 1), ("c", 1), ("d", 1)],
+            ],
+        );
+        // base order is kept, additions of lower priority copies are appended first
+        assert_eq!(merged, [("a", 1), ("c", 1), ("d", 1), ("e", 1)]);
+        assert!(conflicts.is_empty());
+    }
+
+    fn values(values: &[(&str, i32)]) -> BTreeMap<String, i32> {
+        values.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    }
+
+    fn colliding(base: &BTreeMap<String, i32>, copies: &[BTreeMap<String, i32>]) -> Vec<String> {
+        Diff::new(base, copies)
+            .colliding()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_colliding() {
+        let base = values(&[("A.x", 1), ("A.y", 1), ("A.z", 1)]);
+
+        // disjoint changes
+        let copies = [
+            values(&[("A.x", 2), ("A.y", 1), ("A.z", 1)]),
+            values(&[("A.x", 1), ("A.y", 1), ("A.z", 2)]),
+        ];
+        assert!(colliding(&base, &copies).is_empty());
+
+        // the same change by both
+        let copies = [
+            values(&[("A.x", 2), ("A.y", 1), ("A.z", 1)]),
+            values(&[("A.x", 2), ("A.y", 1), ("A.z", 1)]),
+        ];
+        assert!(colliding(&base, &copies).is_empty());
+
+        // different changes, a removal colliding with a change and different additions
+        let copies = [
+            values(&[("A.x", 2), ("A.y", 2), ("A.z", 1), ("A.w", 1)]),
+            values(&[("A.x", 3), ("A.z", 1), ("A.w", 2)]),
+        ];
+        assert_eq!(colliding(&base, &copies), ["A.w", "A.x", "A.y"]);
+        let diff = Diff::new(&base, &copies);
+        assert_eq!(diff.first_change("A.x"), Some(Some(&2)));
+        assert_eq!(diff.first_change("A.z"), None);
+
+        // without the game's version every key is a change
+        let copies = [values(&[("A.x", 1)]), values(&[("A.y", 1)])];
+        assert!(colliding(&BTreeMap::new(), &copies).is_empty());
+    }
+
+    #[test]
+    fn test_merge_without_base() {
+        let (merged, conflicts) = merge(&[], &[&[("b", 1), ("a", 1)], &[("a", 2)]]);
+        assert_eq!(merged, [("a", 1), ("b", 1)]);
+        assert_eq!(conflicts, ["Table.a".to_string()].into());
+    }
+}
@@ -8,9 +8,8 @@ pub mod providers;
 pub mod state;
 
 use std::io::{Cursor, Read};
-use std::str::FromStr;
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
 };
 
@@ -19,8 +18,10 @@ use anyhow::{bail, Context, Result};
 use directories::ProjectDirs;
 use error::IntegrationError;
 use integrate::IntegrationErr;
-use providers::{ModResolution, ModSpecification, ProviderFactory, ReadSeek};
+use mod_lints::LintConfig;
+use providers::{ModInfo, ModResolution, ModSpecification, ProviderFactory, ReadSeek};
 use state::State;
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn};
 
 pub struct Dirs {
@@ -145,6 +146,10 @@ impl DRGInstallation {
             .join("Paks")
             .join(self.installation_type.main_pak_name())
     }
+    /// Game version of this installation, see [`read_game_version`].
+    pub fn game_version(&self) -> Result<String> {
+        read_game_version(self.main_pak())
+    }
     pub fn modio_directory(&self) -> Option<PathBuf> {
         match self.installation_type {
             DRGInstallationType::Steam => {
@@ -190,15 +195,86 @@ pub fn is_drg_pak<P: AsRef<Path>>(path: P) -> Result<()> {
     Ok(())
 }
 
+/// Reads the game version (e.g. `1.38.91207.0`) from the project settings packed into the main
+/// game pak.
+pub fn read_game_version<P: AsRef<Path>>(fsd_pak: P) -> Result<String> {
+    let mut reader = std::io::BufReader::new(open_file(fsd_pak)?);
+    let pak = repak::PakReader::new_any(&mut reader)?;
+    let buf = pak.get("FSD/Config/DefaultGame.ini", &mut reader)?;
+    let text = String::from_utf8_lossy(&buf);
+    let config = ini::Ini::load_from_str_noescape(text.trim_start_matches('\u{feff}'))
+        .context("failed to parse `DefaultGame.ini`")?;
+    config
+        .section(Some("/Script/EngineSettings.GeneralProjectSettings"))
+        .and_then(|s| s.get("ProjectVersion"))
+        .map(|v| v.trim().to_string())
+        .context("`DefaultGame.ini` does not contain a `ProjectVersion`")
+}
+
+/// Returns the detected game version along with the mods whose mod.io version tags do not include
+/// it. Mods without any version tags are assumed to be compatible.
+pub fn find_incompatible_mods<'a, P: AsRef<Path>>(
+    game_path: P,
+    mods: &'a [ModInfo],
+) -> Result<(String, Vec<&'a ModInfo>)> {
+    let game_version = read_game_version(game_path)?;
+    let incompatible = mods
+        .iter()
+        .filter(|m| {
+            m.modio_tags
+                .as_ref()
+                .map_or(false, |t| !t.supports_game_version(&game_version))
+        })
+        .collect();
+    Ok((game_version, incompatible))
+}
+
+/// Warn about mods not marked compatible with the installed game version, failing if
+/// `block_incompatible` is set and there are any. The check is skipped with a warning if the game
+/// version cannot be detected.
+pub fn check_compatibility<P: AsRef<Path>>(
+    game_path: P,
+    mods: &[ModInfo],
+    block_incompatible: bool,
+) -> Result<(), IntegrationErr> {
+    match find_incompatible_mods(game_path, mods) {
+        Ok((game_version, incompatible)) if !incompatible.is_empty() => {
+            warn!("the following mods are not marked compatible with game version {game_version}:");
+            for m in &incompatible {
+                warn!("  {}", m.spec.url);
+            }
+            if block_incompatible {
+                return Err(IntegrationErr {
+                    mod_ctxt: None,
+                    kind: integrate::IntegrationErrKind::Generic(anyhow::anyhow!(
+                        "refusing to install {} mod(s) not marked compatible with game version {game_version}",
+                        incompatible.len()
+                    )),
+                });
+            }
+        }
+        Ok(_) => {}
+        Err(e) => warn!("failed to detect game version, skipping compatibility check: {e:#}"),
+    }
+    Ok(())
+}
+
+/// Resolve, fetch and integrate mods. If `deny_lints` is given, installing is refused when any of
+/// its deny-level lints finds a problem. See [`integrate::integrate`] for `merge_tables`.
+#[allow(clippy::too_many_arguments)]
 pub async fn resolve_unordered_and_integrate<P: AsRef<Path>>(
     game_path: P,
     state: &State,
     mod_specs: &[ModSpecification],
     update: bool,
+    block_incompatible: bool,
+    deny_lints: Option<&LintConfig>,
+    merge_tables: bool,
+    cancel: &CancellationToken,
 ) -> Result<(), IntegrationErr> {
     let mods = state
         .store
-        .resolve_mods(mod_specs, update)
+        .resolve_mods(mod_specs, update, cancel)
         .await
         .map_err(|e| IntegrationErr {
             mod_ctxt: None,
@@ -231,6 +307,9 @@ pub async fn resolve_unordered_and_integrate<P: AsRef<Path>>(
         .iter()
         .map(|u| mods[u].clone())
         .collect::<Vec<_>>();
+
+    check_compatibility(&game_path, &to_integrate, block_incompatible)?;
+
     let urls = to_integrate
         .iter()
         .map(|m| &m.resolution)
@@ -239,21 +318,46 @@ pub async fn resolve_unordered_and_integrate<P: AsRef<Path>>(
     info!("fetching mods...");
     let paths = state
         .store
-        .fetch_mods(&urls, update, None)
+        .fetch_mods(&urls, update, None, cancel)
         .await
         .map_err(|e| IntegrationErr {
             mod_ctxt: None,
             kind: integrate::IntegrationErrKind::Generic(e),
         })?;
 
-    integrate::integrate(game_path, to_integrate.into_iter().zip(paths).collect())
+    if let Some(config) = deny_lints {
+        info!("checking deny-level lints...");
+        mod_lints::check_denied_lints(
+            config,
+            to_integrate
+                .iter()
+                .map(|m| m.spec.clone())
+                .zip(paths.iter().cloned())
+                .collect(),
+            Some(game_path.as_ref().to_path_buf()),
+            mods.clone(),
+            cancel,
+        )
+        .map_err(|e| IntegrationErr {
+            mod_ctxt: None,
+            kind: integrate::IntegrationErrKind::Generic(e),
+        })?;
+    }
+
+    integrate::integrate(
+        game_path,
+        to_integrate.into_iter().zip(paths).collect(),
+        merge_tables,
+        cancel,
+    )
 }
 
 async fn resolve_into_urls<'b>(
     state: &State,
     mod_specs: &[ModSpecification],
+    cancel: &CancellationToken,
 ) -> Result<Vec<ModResolution>> {
-    let mods = state.store.resolve_mods(mod_specs, false).await?;
+    let mods = state.store.resolve_mods(mod_specs, false, cancel).await?;
 
     let mods_set = mod_specs
         .iter()
@@ -289,17 +393,23 @@ async fn resolve_into_urls<'b>(
 pub async fn resolve_ordered(
     state: &State,
     mod_specs: &[ModSpecification],
+    cancel: &CancellationToken,
 ) -> Result<Vec<PathBuf>> {
-    let urls = resolve_into_urls(state, mod_specs).await?;
+    let urls = resolve_into_urls(state, mod_specs, cancel).await?;
     let urls = urls.iter().collect::<Vec<_>>();
-    state.store.fetch_mods(&urls, false, None).await
+    state.store.fetch_mods(&urls, false, None, cancel).await
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn resolve_unordered_and_integrate_with_provider_init<P, F>(
     game_path: P,
     state: &mut State,
     mod_specs: &[ModSpecification],
     update: bool,
+    block_incompatible: bool,
+    deny_lints: Option<&LintConfig>,
+    merge_tables: bool,
+    cancel: &CancellationToken,
     init: F,
 ) -> Result<()>
 where
@@ -307,7 +417,18 @@ where
     F: Fn(&mut State, String, &ProviderFactory) -> Result<()>,
 {
     loop {
-        match resolve_unordered_and_integrate(&game_path, state, mod_specs, update).await {
+        match resolve_unordered_and_integrate(
+            &game_path,
+            state,
+            mod_specs,
+            update,
+            block_incompatible,
+            deny_lints,
+            merge_tables,
+            cancel,
+        )
+        .await
+        {
             Ok(()) => return Ok(()),
             Err(IntegrationErr { mod_ctxt, kind }) => match kind {
                 integrate::IntegrationErrKind::Generic(e) => match e.downcast::<IntegrationError>()
@@ -346,13 +467,14 @@ where
 pub async fn resolve_ordered_with_provider_init<F>(
     state: &mut State,
     mod_specs: &[ModSpecification],
+    cancel: &CancellationToken,
     init: F,
 ) -> Result<Vec<PathBuf>>
 where
     F: Fn(&mut State, String, &ProviderFactory) -> Result<()>,
 {
     loop {
-        match resolve_ordered(state, mod_specs).await {
+        match resolve_ordered(state, mod_specs, cancel).await {
             Ok(mod_paths) => return Ok(mod_paths),
             Err(e) => match e.downcast::<IntegrationError>() {
                 Ok(IntegrationError::NoProvider { url, factory }) => init(state, url, factory)?,
@@ -362,6 +484,58 @@ where
     }
 }
 
+/// Resolve mods and their dependencies, initializing providers as they are needed.
+pub async fn resolve_mods_with_provider_init<F>(
+    state: &mut State,
+    mod_specs: &[ModSpecification],
+    update: bool,
+    cancel: &CancellationToken,
+    init: F,
+) -> Result<HashMap<ModSpecification, ModInfo>>
+where
+    F: Fn(&mut State, String, &ProviderFactory) -> Result<()>,
+{
+    loop {
+        match state.store.resolve_mods(mod_specs, update, cancel).await {
+            Ok(mods) => return Ok(mods),
+            Err(e) => match e.downcast::<IntegrationError>() {
+                Ok(IntegrationError::NoProvider { url, factory }) => init(state, url, factory)?,
+                Err(e) => return Err(e),
+            },
+        }
+    }
+}
+
+/// Path relative to the game root of the pak `entry` under `mount`, or `None` if the pak does not
+/// mount it there.
+pub(crate) fn game_path(mount: &Path, entry: &str) -> Option<PathBuf> {
+    let path = mount.join(entry);
+    Some(path.strip_prefix("../../../").ok()?.to_path_buf())
+}
+
+/// `path` lowercased and `/` separated, for comparing paths across paks.
+pub(crate) fn normalize_path(path: &Path) -> String {
+    path.to_string_lossy()
+        .replace('\\', "/")
+        .to_ascii_lowercase()
+}
+
+/// An asset's `.uasset` and `.uexp` data.
+pub(crate) type AssetData = (Vec<u8>, Vec<u8>);
+
+/// Parse an asset from its `.uasset` and `.uexp` data as cooked for the game's engine version.
+pub(crate) fn parse_asset<C: AsRef<[u8]>>(
+    uasset: C,
+    uexp: C,
+) -> Result<unreal_asset::Asset<Cursor<C>>> {
+    Ok(unreal_asset::Asset::new(
+        Cursor::new(uasset),
+        Some(Cursor::new(uexp)),
+        unreal_asset::engine_version::EngineVersion::VER_UE4_27,
+        None,
+    )?)
+}
+
 pub(crate) fn get_pak_from_data(mut data: Box<dyn ReadSeek>) -> Result<Box<dyn ReadSeek>> {
     if let Ok(mut archive) = zip::ZipArchive::new(&mut data) {
         (0..archive.len())
@@ -387,71 +561,3 @@ pub(crate) fn get_pak_from_data(mut data: Box<dyn ReadSeek>) -> Result<Box<dyn R
         Ok(data)
     }
 }
-
-pub(crate) enum PakOrNotPak {
-    Pak(Box<dyn ReadSeek>),
-    NotPak(Box<dyn ReadSeek>),
-}
-
-pub(crate) enum GetAllFilesFromDataError {
-    EmptyArchive,
-    OnlyNonPakFiles,
-    Other(anyhow::Error),
-}
-
-pub(crate) fn lint_get_all_files_from_data(
-    mut data: Box<dyn ReadSeek>,
-) -> Result<Vec<(PathBuf, PakOrNotPak)>, GetAllFilesFromDataError> {
-    if let Ok(mut archive) = zip::ZipArchive::new(&mut data) {
-        if archive.is_empty() {
-            return Err(GetAllFilesFromDataError::EmptyArchive);
-        }
-
-        let mut files = Vec::new();
-        for i in 0..archive.len() {
-            let mut file = archive
-                .by_index(i)
-                .map_err(|e| GetAllFilesFromDataError::Other(e.into()))?;
-
-            if let Some(p) = file.enclosed_name().map(Path::to_path_buf) {
-                if file.is_file() {
-                    if p.extension().filter(|e| e == &"pak").is_some() {
-                        let mut buf = vec![];
-                        file.read_to_end(&mut buf)
-                            .map_err(|e| GetAllFilesFromDataError::Other(e.into()))?;
-                        files.push((
-                            p.to_path_buf(),
-                            PakOrNotPak::Pak(Box::new(Cursor::new(buf))),
-                        ));
-                    } else {
-                        let mut buf = vec![];
-                        file.read_to_end(&mut buf)
-                            .map_err(|e| GetAllFilesFromDataError::Other(e.into()))?;
-                        files.push((
-                            p.to_path_buf(),
-                            PakOrNotPak::NotPak(Box::new(Cursor::new(buf))),
-                        ));
-                    }
-                }
-            }
-        }
-
-        if files
-            .iter()
-            .filter(|(_, pak_or_not_pak)| matches!(pak_or_not_pak, PakOrNotPak::Pak(..)))
-            .count()
-            >= 1
-        {
-            Ok(files)
-        } else {
-            Err(GetAllFilesFromDataError::OnlyNonPakFiles)
-        }
-    } else {
-        data.rewind()
-            .map_err(|e| GetAllFilesFromDataError::Other(e.into()))?;
-        Ok(vec![(
-            PathBuf::from_str(".").unwrap(),
-            PakOrNotPak::Pak(data),
-        )])
-    }
-}
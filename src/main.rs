@@ -1,19 +1,29 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
 use std::io::BufWriter;
 use std::path::PathBuf;
+use std::process::ExitCode;
 
-use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use indexmap::IndexSet;
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, info};
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::filter;
 
-use drg_mod_integration::mod_lints::{run_lints, LintId};
-use drg_mod_integration::providers::ProviderFactory;
+use drg_mod_integration::mod_lints::{
+    fix_mods, LintConfigFile, LintCtxt, LintLevel, LintReport, ReportFormat,
+};
+use drg_mod_integration::providers::{ModInfo, ModStore, ProviderFactory, ProviderParameter};
+use drg_mod_integration::state::diff::{ProfileDiff, ProfileMods};
+use drg_mod_integration::state::secrets::SecretStoreKind;
+use drg_mod_integration::state::{walk_mods, ModConfig, ModData_v0_2_0 as ModData, ModOrGroup};
 use drg_mod_integration::{gui::gui, providers::ModSpecification, state::State, DRGInstallation};
 use drg_mod_integration::{
-    resolve_ordered_with_provider_init, resolve_unordered_and_integrate_with_provider_init, Dirs,
+    resolve_mods_with_provider_init, resolve_ordered_with_provider_init,
+    resolve_unordered_and_integrate_with_provider_init, Dirs,
 };
 
 /// Command line integration tool.
@@ -30,6 +40,20 @@ struct ActionIntegrate {
     #[arg(short, long)]
     update: bool,
 
+    /// Refuse to install if any mod is not marked compatible with the installed game version.
+    #[arg(long)]
+    block_incompatible: bool,
+
+    /// Refuse to install if a deny-level lint finds problems. Lint levels are configured in
+    /// `lints.json` in the config directory.
+    #[arg(long)]
+    deny_lints: bool,
+
+    /// Merge rows of data tables modified by several mods against the game's version instead of
+    /// using only the first mod's table.
+    #[arg(long)]
+    merge_tables: bool,
+
     /// Paths of mods to integrate
     ///
     /// Can be a file path or URL to a .pak or .zip file or a URL to a mod on https://mod.io/g/drg
@@ -55,6 +79,26 @@ struct ActionIntegrateProfile {
     #[arg(short, long)]
     update: bool,
 
+    /// Refuse to install if any mod is not marked compatible with the installed game version.
+    #[arg(long)]
+    block_incompatible: bool,
+
+    /// Refuse to install if a deny-level lint finds problems. Lint levels are configured in
+    /// `lints.json` in the config directory.
+    #[arg(long)]
+    deny_lints: bool,
+
+    /// Merge rows of data tables modified by several mods against the game's version instead of
+    /// using only the first mod's table.
+    #[arg(long)]
+    merge_tables: bool,
+
+    /// Only integrate the profile's client-only safe mods: mods not marked as required which are
+    /// verified or approved on mod.io and tagged as QoL, visual or audio. Use this to join public
+    /// lobbies without switching profiles.
+    #[arg(long)]
+    client_only: bool,
+
     /// Profile to integrate.
     profile: String,
 }
@@ -65,7 +109,10 @@ struct ActionLaunch {
     args: Vec<String>,
 }
 
-/// Lint the mod bundle that would be created for a profile.
+/// Lint the mod bundle that would be created for a profile, or individual mods.
+///
+/// Exits with code 2 if any deny-level lint reported problems, 3 if only warn-level lints did, and
+/// 1 if linting itself failed.
 #[derive(Parser, Debug)]
 struct ActionLint {
     /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
@@ -74,8 +121,216 @@ struct ActionLint {
     #[arg(short, long)]
     fsd_pak: Option<PathBuf>,
 
+    /// Lint config file to use instead of `lints.json` in the config directory.
+    #[arg(long)]
+    lint_config: Option<PathBuf>,
+
+    /// Write a cleaned copy of each mod with fixable problems to the cache, repacked as a current
+    /// version pak without the offending files.
+    #[arg(long)]
+    fix: bool,
+
+    /// Replace mods in the profile with their cleaned copies.
+    #[arg(long, requires_all = ["fix", "profile"])]
+    swap: bool,
+
+    /// Also write the report to this file, as HTML if it ends in `.html` or `.htm` and as
+    /// Markdown otherwise.
+    #[arg(long)]
+    report: Option<PathBuf>,
+
+    /// Mods to lint instead of a profile, e.g. before publishing one. Accepts the same URLs and
+    /// paths as `integrate`.
+    #[arg(short, long, num_args = 1.., conflicts_with = "profile")]
+    mods: Vec<String>,
+
     /// Profile to lint.
-    profile: String,
+    #[arg(required_unless_present = "mods")]
+    profile: Option<String>,
+}
+
+/// Manage mod profiles
+#[derive(Subcommand, Debug)]
+enum ActionProfiles {
+    /// List profiles, marking the active one with `*`
+    List,
+    /// Create an empty profile
+    Create { name: String },
+    /// Delete a profile
+    Delete { name: String },
+    /// Rename a profile
+    Rename { name: String, new_name: String },
+    /// Copy a profile under a new name
+    Duplicate { name: String, new_name: String },
+    /// Set the active profile
+    Activate { name: String },
+    /// Make a profile inherit the mods of another profile, loaded after its own mods
+    Inherit {
+        name: String,
+        /// Profile to inherit from. Omit to stop inheriting.
+        #[arg(long)]
+        from: Option<String>,
+    },
+    /// Show how a profile differs from another profile or a profile code
+    Diff {
+        /// Profile to compare against.
+        old: String,
+        /// Profile to compare. Defaults to the active profile.
+        #[arg(conflicts_with = "code")]
+        new: Option<String>,
+        /// Compare against a profile code, one mod URL per line as copied from the GUI, read from
+        /// this file (`-` for stdin) instead of a profile.
+        #[arg(long)]
+        code: Option<PathBuf>,
+    },
+}
+
+/// Manage mod groups and their use in profiles
+#[derive(Subcommand, Debug)]
+enum ActionGroups {
+    /// List groups
+    List,
+    /// Create an empty group
+    Create { name: String },
+    /// Delete a group and remove it from all profiles
+    Delete { name: String },
+    /// Rename a group
+    Rename { name: String, new_name: String },
+    /// Add a group to a profile or another group
+    Include {
+        name: String,
+        #[command(flatten)]
+        target: ModTarget,
+        /// Position in the profile or group. Defaults to the top.
+        #[arg(short, long, default_value_t = 0)]
+        index: usize,
+    },
+    /// Remove a group from a profile or another group
+    Exclude {
+        name: String,
+        #[command(flatten)]
+        target: ModTarget,
+    },
+    /// Enable or disable a group within a profile or another group
+    Set {
+        name: String,
+        #[command(flatten)]
+        target: ModTarget,
+        #[arg(long, action = clap::ArgAction::Set)]
+        enabled: bool,
+    },
+    /// Enable or disable a mod inside a group only where the group is included, leaving other
+    /// profiles using the group unchanged
+    Override {
+        name: String,
+        /// URL of a mod inside the group or groups nested in it
+        url: String,
+        #[command(flatten)]
+        target: ModTarget,
+        /// Omit to remove the override.
+        #[arg(long, action = clap::ArgAction::Set)]
+        enabled: Option<bool>,
+    },
+}
+
+/// Profile or group whose mods are edited.
+#[derive(Parser, Debug, Clone)]
+struct ModTarget {
+    /// Profile to edit. Defaults to the active profile.
+    #[arg(short, long)]
+    profile: Option<String>,
+
+    /// Edit the mods of a group instead of a profile.
+    #[arg(short, long, conflicts_with = "profile")]
+    group: Option<String>,
+}
+
+/// Manage the mods in a profile or group
+#[derive(Subcommand, Debug)]
+enum ActionMods {
+    /// List mods
+    List {
+        #[command(flatten)]
+        target: ModTarget,
+    },
+    /// Resolve mods and add them along with any missing dependencies
+    Add {
+        #[command(flatten)]
+        target: ModTarget,
+
+        /// Update mod metadata instead of using the offline cache.
+        #[arg(short, long)]
+        update: bool,
+
+        /// URLs or paths of mods to add
+        #[arg(required = true)]
+        mods: Vec<String>,
+    },
+    /// Remove a mod, or exclude it from the profile if it is inherited
+    Remove {
+        #[command(flatten)]
+        target: ModTarget,
+
+        url: String,
+    },
+    /// Move a mod to a new position
+    Move {
+        #[command(flatten)]
+        target: ModTarget,
+
+        url: String,
+        index: usize,
+    },
+    /// Change whether a mod is enabled or required. Whether an inherited mod is enabled is
+    /// overridden in the profile, which also restores it if it was excluded.
+    Set {
+        #[command(flatten)]
+        target: ModTarget,
+
+        url: String,
+        #[arg(long)]
+        enabled: Option<bool>,
+        #[arg(long)]
+        required: Option<bool>,
+    },
+}
+
+/// Manage stored provider parameters such as the mod.io OAuth token
+#[derive(Subcommand, Debug)]
+enum ActionProviders {
+    /// List providers and which of their parameters are set
+    List,
+    /// Store parameters for a provider after checking that they work. Missing parameters are
+    /// prompted for.
+    Set {
+        provider: String,
+        /// Parameters to store. Prefer prompting or `--provider-param` for secrets as command
+        /// line arguments may be visible to other users.
+        #[arg(value_name = "PARAM=VALUE")]
+        params: Vec<String>,
+    },
+    /// Check that the parameters for a provider work
+    Test { provider: String },
+    /// Remove stored parameters for a provider
+    Clear { provider: String },
+    /// Move secret parameters such as OAuth tokens to a different store
+    Store {
+        /// `machine-key` and `passphrase` encrypt secrets, the latter with a passphrase read from
+        /// `DRG_MOD_INTEGRATION_SECRET_PASSPHRASE`
+        kind: SecretStoreKind,
+    },
+}
+
+impl ActionMods {
+    fn target(&self) -> &ModTarget {
+        match self {
+            ActionMods::List { target }
+            | ActionMods::Add { target, .. }
+            | ActionMods::Remove { target, .. }
+            | ActionMods::Move { target, .. }
+            | ActionMods::Set { target, .. } => target,
+        }
+    }
 }
 
 #[derive(Subcommand, Debug)]
@@ -84,8 +339,30 @@ enum Action {
     Profile(ActionIntegrateProfile),
     Launch(ActionLaunch),
     Lint(ActionLint),
+    #[command(subcommand)]
+    Profiles(ActionProfiles),
+    #[command(subcommand)]
+    Groups(ActionGroups),
+    #[command(subcommand)]
+    Mods(ActionMods),
+    #[command(subcommand)]
+    Providers(ActionProviders),
 }
 
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum OutputFormat {
+    /// Human readable text
+    #[default]
+    Text,
+    /// JSON intended for scripts. Errors are printed as `{"error": "..."}`.
+    Json,
+}
+
+/// Exit code used by `lint` when a deny-level lint found problems.
+const EXIT_LINT_DENIED: u8 = 2;
+/// Exit code used by `lint` when only warn-level lints found problems.
+const EXIT_LINT_WARNED: u8 = 3;
+
 #[derive(Parser, Debug)]
 #[command(author, version)]
 struct Args {
@@ -95,9 +372,92 @@ struct Args {
     /// Location to store configs and data
     #[arg(long)]
     appdata: Option<PathBuf>,
+
+    /// Format of command results printed to stdout. Logs are always written to stderr.
+    #[arg(long, global = true, value_enum, default_value_t)]
+    output: OutputFormat,
+
+    /// Provider parameter to use instead of the stored one, e.g. `modio.oauth=<token>`. Can also
+    /// be supplied through environment variables such as `DRG_MOD_INTEGRATION_MODIO_OAUTH`.
+    #[arg(
+        long = "provider-param",
+        global = true,
+        value_name = "PROVIDER.PARAM=VALUE"
+    )]
+    provider_params: Vec<ProviderParam>,
+
+    /// Fail instead of prompting when input such as a missing provider parameter is needed.
+    #[arg(long, global = true)]
+    non_interactive: bool,
+}
+
+/// Options shared by all commands.
+struct Options {
+    output: OutputFormat,
+    non_interactive: bool,
+    /// Provider parameters from `--provider-param` and the environment, by provider id. These
+    /// take precedence over stored parameters and are never saved.
+    provider_parameters: HashMap<String, HashMap<String, String>>,
 }
 
-fn main() -> Result<()> {
+/// `--provider-param` value.
+#[derive(Clone)]
+struct ProviderParam {
+    provider: String,
+    id: String,
+    value: String,
+}
+
+impl std::str::FromStr for ProviderParam {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s.split_once('=').ok_or("expected PROVIDER.PARAM=VALUE")?;
+        let (provider, id) = key.split_once('.').ok_or("expected PROVIDER.PARAM=VALUE")?;
+        Ok(Self {
+            provider: provider.to_owned(),
+            id: id.to_owned(),
+            value: value.to_owned(),
+        })
+    }
+}
+
+// values are usually secrets so keep them out of the logs
+impl std::fmt::Debug for ProviderParam {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}=<redacted>", self.provider, self.id)
+    }
+}
+
+fn provider_parameter_env_var(factory: &ProviderFactory, param: &ProviderParameter) -> String {
+    format!("DRG_MOD_INTEGRATION_{}_{}", factory.id, param.id).to_uppercase()
+}
+
+/// Collect provider parameters from the environment, overridden by `--provider-param`.
+fn provider_parameter_overrides(
+    params: Vec<ProviderParam>,
+) -> HashMap<String, HashMap<String, String>> {
+    let mut overrides: HashMap<String, HashMap<String, String>> = HashMap::new();
+    for factory in ModStore::get_provider_factories() {
+        for param in factory.parameters {
+            if let Ok(value) = std::env::var(provider_parameter_env_var(factory, param)) {
+                overrides
+                    .entry(factory.id.to_owned())
+                    .or_default()
+                    .insert(param.id.to_owned(), value);
+            }
+        }
+    }
+    for param in params {
+        overrides
+            .entry(param.provider)
+            .or_default()
+            .insert(param.id, param.value);
+    }
+    overrides
+}
+
+fn main() -> Result<ExitCode> {
     #[cfg(target_os = "windows")]
     {
         // Try to enable ANSI code support on Windows 10 for console. If it fails, then whatever
@@ -123,33 +483,58 @@ fn main() -> Result<()> {
 
     debug!(?args);
 
-    match args.action {
+    let output = args.output;
+    let opts = Options {
+        output,
+        non_interactive: args.non_interactive,
+        provider_parameters: provider_parameter_overrides(args.provider_params),
+    };
+    let result = match args.action {
         Some(Action::Integrate(action)) => rt.block_on(async {
-            action_integrate(dirs, action).await?;
-            Ok(())
+            action_integrate(dirs, action, &opts).await?;
+            Ok(ExitCode::SUCCESS)
         }),
         Some(Action::Profile(action)) => rt.block_on(async {
-            action_integrate_profile(dirs, action).await?;
-            Ok(())
+            action_integrate_profile(dirs, action, &opts).await?;
+            Ok(ExitCode::SUCCESS)
         }),
         Some(Action::Launch(action)) => {
             std::thread::spawn(move || {
                 rt.block_on(std::future::pending::<()>());
             });
             gui(dirs, Some(action.args))?;
-            Ok(())
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(Action::Lint(action)) => rt.block_on(action_lint(dirs, action, &opts)),
+        Some(Action::Profiles(action)) => {
+            action_profiles(dirs, action, &opts).map(|()| ExitCode::SUCCESS)
+        }
+        Some(Action::Groups(action)) => {
+            action_groups(dirs, action, &opts).map(|()| ExitCode::SUCCESS)
         }
-        Some(Action::Lint(action)) => rt.block_on(async {
-            action_lint(dirs, action).await?;
-            Ok(())
+        Some(Action::Mods(action)) => rt.block_on(async {
+            action_mods(dirs, action, &opts).await?;
+            Ok(ExitCode::SUCCESS)
+        }),
+        Some(Action::Providers(action)) => rt.block_on(async {
+            action_providers(dirs, action, &opts).await?;
+            Ok(ExitCode::SUCCESS)
         }),
         None => {
             std::thread::spawn(move || {
                 rt.block_on(std::future::pending::<()>());
             });
             gui(dirs, None)?;
-            Ok(())
+            Ok(ExitCode::SUCCESS)
+        }
+    };
+
+    match result {
+        Err(e) if output == OutputFormat::Json => {
+            print_json(&serde_json::json!({ "error": format!("{e:#}") }))?;
+            Ok(ExitCode::FAILURE)
         }
+        result => result,
     }
 }
 
@@ -212,30 +597,128 @@ fn setup_logging(dirs: &Dirs) -> Result<WorkerGuard> {
     Ok(guard)
 }
 
-#[tracing::instrument(skip(state))]
-fn init_provider(state: &mut State, url: String, factory: &ProviderFactory) -> Result<()> {
+#[tracing::instrument(skip(state, opts))]
+fn init_provider(
+    state: &mut State,
+    url: String,
+    factory: &ProviderFactory,
+    opts: &Options,
+) -> Result<()> {
     info!("initializing provider for {:?}", url);
 
-    let params = state
-        .config
-        .provider_parameters
-        .entry(factory.id.to_owned())
-        .or_default();
+    let params = provider_parameters(state, factory, opts, &[])?;
+    state.store.add_provider(factory, &params)
+}
+
+/// Stored parameters for `factory` merged with `extra` and overrides from [`Options`], prompting
+/// for any still missing. Prompted and `extra` values are stored, overrides are not.
+fn provider_parameters(
+    state: &mut State,
+    factory: &ProviderFactory,
+    opts: &Options,
+    extra: &[ProviderParam],
+) -> Result<HashMap<String, String>> {
+    let mut stored = state.provider_parameters(factory.id);
+    let mut changed = false;
+    for param in extra {
+        if !factory.parameters.iter().any(|p| p.id == param.id) {
+            bail!("provider `{}` has no parameter `{}`", factory.id, param.id);
+        }
+        stored.insert(param.id.clone(), param.value.clone());
+        changed = true;
+    }
+    let overrides = opts.provider_parameters.get(factory.id);
     for p in factory.parameters {
-        if !params.contains_key(p.name) {
-            // this blocks but since we're calling it on the main thread it'll be fine
-            let value =
-                dialoguer::Password::with_theme(&dialoguer::theme::ColorfulTheme::default())
-                    .with_prompt(p.description)
-                    .interact()
-                    .unwrap();
-            params.insert(p.id.to_owned(), value);
+        if stored.contains_key(p.id) || overrides.is_some_and(|o| o.contains_key(p.id)) {
+            continue;
+        }
+        if opts.non_interactive {
+            bail!(
+                "missing parameter `{}.{}` ({}), supply it with `--provider-param {}.{}=...` or `{}`",
+                factory.id,
+                p.id,
+                p.name,
+                factory.id,
+                p.id,
+                provider_parameter_env_var(factory, p)
+            );
+        }
+        // this blocks but since we're calling it on the main thread it'll be fine
+        let value = dialoguer::Password::with_theme(&dialoguer::theme::ColorfulTheme::default())
+            .with_prompt(p.description)
+            .interact()?;
+        stored.insert(p.id.to_owned(), value);
+        changed = true;
+    }
+    if changed {
+        state.set_provider_parameters(factory, stored)?;
+    }
+
+    Ok(merged_provider_parameters(state, factory, opts))
+}
+
+/// Stored parameters for `factory` merged with overrides from [`Options`].
+fn merged_provider_parameters(
+    state: &State,
+    factory: &ProviderFactory,
+    opts: &Options,
+) -> HashMap<String, String> {
+    let mut params = state.provider_parameters(factory.id);
+    if let Some(overrides) = opts.provider_parameters.get(factory.id) {
+        params.extend(overrides.clone());
+    }
+    params
+}
+
+/// Load state and apply provider parameter overrides, prompting to restore a backup if
+/// `config.json` or `mod_data.json` is corrupt.
+fn init_state(dirs: Dirs, opts: &Options) -> Result<State> {
+    let state = State::init_with_recovery(dirs, |corrupt| {
+        !opts.non_interactive
+            && dialoguer::Confirm::with_theme(&dialoguer::theme::ColorfulTheme::default())
+                .with_prompt(format!(
+                    "{corrupt}\nRestore backup {}?",
+                    corrupt.backup.as_ref().unwrap().display()
+                ))
+                .default(true)
+                .interact()
+                .unwrap_or(false)
+    })?;
+
+    for factory in ModStore::get_provider_factories() {
+        if !opts.provider_parameters.contains_key(factory.id) {
+            continue;
+        }
+        let params = merged_provider_parameters(&state, factory, opts);
+        if factory.parameters.iter().all(|p| params.contains_key(p.id)) {
+            state.store.add_provider(factory, &params)?;
         }
     }
-    state.store.add_provider(factory, params)
+    Ok(state)
+}
+
+/// Exit code conventionally used by processes interrupted with ctrl-c.
+const EXIT_INTERRUPTED: i32 = 130;
+
+/// Returns a token that is cancelled on ctrl-c so in progress downloads and integration can stop
+/// without leaving a partially written mod bundle behind. Listening replaces the default handling
+/// of ctrl-c for the rest of the process, so a second ctrl-c exits immediately.
+fn cancel_on_ctrl_c() -> CancellationToken {
+    let cancel = CancellationToken::new();
+    let token = cancel.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            info!("cancelling, press ctrl-c again to exit");
+            token.cancel();
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(EXIT_INTERRUPTED);
+            }
+        }
+    });
+    cancel
 }
 
-async fn action_integrate(dirs: Dirs, action: ActionIntegrate) -> Result<()> {
+async fn action_integrate(dirs: Dirs, action: ActionIntegrate, opts: &Options) -> Result<()> {
     let game_pak_path = action
         .fsd_pak
         .or_else(|| {
@@ -246,7 +729,7 @@ async fn action_integrate(dirs: Dirs, action: ActionIntegrate) -> Result<()> {
         .context("Could not find DRG pak file, please specify manually with the --fsd_pak flag")?;
     debug!(?game_pak_path);
 
-    let mut state = State::init(dirs)?;
+    let mut state = init_state(dirs, opts)?;
 
     let mod_specs = action
         .mods
@@ -254,17 +737,33 @@ async fn action_integrate(dirs: Dirs, action: ActionIntegrate) -> Result<()> {
         .map(ModSpecification::new)
         .collect::<Vec<_>>();
 
+    let block_incompatible = action.block_incompatible || state.config.block_incompatible_mods;
+    let merge_tables = action.merge_tables || state.config.merge_tables;
+    let deny_lints = (action.deny_lints || state.config.block_denied_lints)
+        .then(|| state.lint_config(None))
+        .transpose()?;
+
     resolve_unordered_and_integrate_with_provider_init(
         game_pak_path,
         &mut state,
         &mod_specs,
         action.update,
-        init_provider,
+        block_incompatible,
+        deny_lints.as_ref(),
+        merge_tables,
+        &cancel_on_ctrl_c(),
+        |state, url, factory| init_provider(state, url, factory, opts),
     )
-    .await
+    .await?;
+
+    print_integrated(&state, &mod_specs, opts.output)
 }
 
-async fn action_integrate_profile(dirs: Dirs, action: ActionIntegrateProfile) -> Result<()> {
+async fn action_integrate_profile(
+    dirs: Dirs,
+    action: ActionIntegrateProfile,
+    opts: &Options,
+) -> Result<()> {
     let game_pak_path = action
         .fsd_pak
         .or_else(|| {
@@ -275,24 +774,59 @@ async fn action_integrate_profile(dirs: Dirs, action: ActionIntegrateProfile) ->
         .context("Could not find DRG pak file, please specify manually with the --fsd_pak flag")?;
     debug!(?game_pak_path);
 
-    let mut state = State::init(dirs)?;
+    let mut state = init_state(dirs, opts)?;
+    let cancel = cancel_on_ctrl_c();
 
     let mut mods = Vec::new();
     state.mod_data.for_each_enabled_mod(&action.profile, |mc| {
         mods.push(mc.spec.clone());
     });
 
+    if action.client_only {
+        // the subset is derived from mod.io metadata so it has to be resolved first
+        let resolved = resolve_mods_with_provider_init(
+            &mut state,
+            &mods,
+            action.update,
+            &cancel,
+            |state, url, factory| init_provider(state, url, factory, opts),
+        )
+        .await?;
+        let subset = state.mod_data.client_only_subset(&action.profile, |spec| {
+            resolved
+                .get(spec)
+                .cloned()
+                .or_else(|| state.store.get_mod_info(spec))
+        });
+        for (spec, exclusion) in &subset.excluded {
+            info!("skipping {}: {exclusion}", spec.url);
+        }
+        mods = subset.mods;
+    }
+
+    let block_incompatible = action.block_incompatible || state.config.block_incompatible_mods;
+    let merge_tables = action.merge_tables || state.config.merge_tables;
+    let deny_lints = (action.deny_lints || state.config.block_denied_lints)
+        .then(|| state.lint_config(Some(&action.profile)))
+        .transpose()?;
+
     resolve_unordered_and_integrate_with_provider_init(
         game_pak_path,
         &mut state,
         &mods,
         action.update,
-        init_provider,
+        block_incompatible,
+        deny_lints.as_ref(),
+        merge_tables,
+        &cancel,
+        |state, url, factory| init_provider(state, url, factory, opts),
     )
-    .await
+    .await?;
+
+    print_integrated(&state, &mods, opts.output)
 }
 
-async fn action_lint(dirs: Dirs, action: ActionLint) -> Result<()> {
+async fn action_lint(dirs: Dirs, action: ActionLint, opts: &Options) -> Result<ExitCode> {
     let game_pak_path = action
         .fsd_pak
         .or_else(|| {
@@ -303,33 +837,802 @@ async fn action_lint(dirs: Dirs, action: ActionLint) -> Result<()> {
         .context("Could not find DRG pak file, please specify manually with the --fsd_pak flag")?;
     debug!(?game_pak_path);
 
-    let mut state = State::init(dirs)?;
+    let mut state = init_state(dirs, opts)?;
+    let lint_config = match (&action.lint_config, &action.profile) {
+        (Some(path), Some(profile)) => LintConfigFile::read(path)?.for_profile(profile),
+        (Some(path), None) => LintConfigFile::read(path)?.default,
+        (None, profile) => state.lint_config(profile.as_deref())?,
+    };
 
-    let mut mods = Vec::new();
-    state.mod_data.for_each_mod(&action.profile, |mc| {
-        mods.push(mc.spec.clone());
-    });
+    let mods = match &action.profile {
+        Some(profile) => {
+            let mut mods = Vec::new();
+            state.mod_data.for_each_mod(profile, |mc| {
+                mods.push(mc.spec.clone());
+            });
+            mods
+        }
+        None => action
+            .mods
+            .iter()
+            .cloned()
+            .map(ModSpecification::new)
+            .collect(),
+    };
 
-    let mod_paths = resolve_ordered_with_provider_init(&mut state, &mods, init_provider).await?;
-
-    let report = tokio::task::spawn_blocking(move || {
-        run_lints(
-            &BTreeSet::from([
-                LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES,
-                LintId::ASSET_REGISTRY_BIN,
-                LintId::CONFLICTING,
-                LintId::EMPTY_ARCHIVE,
-                LintId::OUTDATED_PAK_VERSION,
-                LintId::SHADER_FILES,
-                LintId::ARCHIVE_WITH_MULTIPLE_PAKS,
-                LintId::NON_ASSET_FILES,
-                LintId::SPLIT_ASSET_PAIRS,
-            ]),
-            mods.into_iter().zip(mod_paths).collect(),
-            Some(game_pak_path),
-        )
+    let cancel = cancel_on_ctrl_c();
+    let mod_paths =
+        resolve_ordered_with_provider_init(&mut state, &mods, &cancel, |state, url, factory| {
+            init_provider(state, url, factory, opts)
+        })
+        .await?;
+    let mod_infos = mods
+        .iter()
+        .filter_map(|m| state.store.get_mod_info(m).map(|info| (m.clone(), info)))
+        .collect();
+
+    let fix = action.fix;
+    let (report, fixed) = tokio::task::spawn_blocking(move || -> Result<_> {
+        let mods = mods.into_iter().zip(mod_paths).collect::<IndexSet<_>>();
+        let lint_ctxt = LintCtxt::init(mods, Some(game_pak_path), mod_infos, lint_config, cancel)?;
+        let report = lint_ctxt.lint()?;
+        let fixed = if fix {
+            fix_mods(&report, &lint_ctxt)?
+        } else {
+            vec![]
+        };
+        Ok((report, fixed))
     })
     .await??;
-    println!("{:#?}", report);
+
+    let mut fixed_output = vec![];
+    for fixed_mod in fixed {
+        let path = state.store.add_blob(&fixed_mod.data)?;
+        if action.swap
+            && let Some(profile) = &action.profile
+        {
+            let cleaned = ModSpecification::new(path.to_string_lossy().to_string());
+            state.mod_data.for_each_mod_mut(profile, |mc| {
+                if mc.spec == fixed_mod.spec {
+                    mc.spec = cleaned.clone();
+                }
+            });
+        }
+        fixed_output.push(FixedModOutput {
+            url: fixed_mod.spec.url,
+            path,
+            removed: fixed_mod.removed.into_iter().collect(),
+        });
+    }
+    if action.swap {
+        state.mod_data.save()?;
+    }
+
+    if let Some(path) = &action.report {
+        std::fs::write(path, report.render(ReportFormat::from_path(path)))
+            .with_context(|| format!("failed to write report to {}", path.display()))?;
+    }
+
+    match opts.output {
+        OutputFormat::Text => {
+            for diagnostic in &report.diagnostics {
+                let level = match diagnostic.level {
+                    LintLevel::Deny => "error",
+                    _ => "warning",
+                };
+                println!("{level}[{}]: {}", diagnostic.lint, diagnostic.message);
+                for m in &diagnostic.mods {
+                    println!("  --> {}", m.url);
+                }
+                if let Some(path) = &diagnostic.path {
+                    println!("   = path: {path}");
+                }
+            }
+            if report.is_empty() {
+                println!("no problems found");
+            }
+            for fixed_mod in &fixed_output {
+                println!(
+                    "fixed {}: removed {} file(s), cleaned copy written to {}",
+                    fixed_mod.url,
+                    fixed_mod.removed.len(),
+                    fixed_mod.path.display()
+                );
+            }
+        }
+        OutputFormat::Json => print_json(&LintOutput {
+            report: &report,
+            fixed: fixed_output,
+        })?,
+    }
+    Ok(if !report.denied().is_empty() {
+        ExitCode::from(EXIT_LINT_DENIED)
+    } else if !report.diagnostics.is_empty() {
+        ExitCode::from(EXIT_LINT_WARNED)
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Profile as printed by `profiles list`.
+#[derive(Serialize)]
+struct ProfileOutput<'a> {
+    name: &'a str,
+    active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent: Option<&'a str>,
+    mods: &'a [ModOrGroup],
+}
+
+/// Group as printed by `groups list`.
+#[derive(Serialize)]
+struct GroupOutput<'a> {
+    name: &'a str,
+    mods: &'a [ModOrGroup],
+}
+
+/// Entry of a profile or group as printed by `mods list`.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ModListEntry {
+    Mod {
+        url: String,
+        name: Option<String>,
+        enabled: bool,
+        required: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        inherited_from: Option<String>,
+    },
+    Group {
+        group: String,
+        enabled: bool,
+        #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+        overrides: BTreeMap<String, bool>,
+    },
+}
+
+impl std::fmt::Display for ModListEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let check = |enabled: bool| if enabled { 'x' } else { ' ' };
+        match self {
+            ModListEntry::Mod {
+                url,
+                name,
+                enabled,
+                required,
+                inherited_from,
+            } => {
+                write!(f, "[{}] {url}", check(*enabled))?;
+                if *required {
+                    write!(f, " required")?;
+                }
+                if let Some(name) = name {
+                    write!(f, " ({name})")?;
+                }
+                if let Some(parent) = inherited_from {
+                    write!(f, " inherited from `{parent}`")?;
+                }
+                Ok(())
+            }
+            ModListEntry::Group {
+                group,
+                enabled,
+                overrides,
+            } => {
+                write!(f, "[{}] group `{group}`", check(*enabled))?;
+                for (url, enabled) in overrides {
+                    write!(f, "\n      [{}] {url} overridden", check(*enabled))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Result of `lint`.
+#[derive(Serialize)]
+struct LintOutput<'a> {
+    #[serde(flatten)]
+    report: &'a LintReport,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixed: Vec<FixedModOutput>,
+}
+
+/// Cleaned copy of a mod written by `lint --fix`.
+#[derive(Serialize)]
+struct FixedModOutput {
+    url: String,
+    path: PathBuf,
+    removed: Vec<String>,
+}
+
+/// Result of `integrate` and `profile`.
+#[derive(Serialize)]
+struct IntegrateOutput {
+    mods: Vec<ModInfo>,
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn print_integrated(state: &State, mods: &[ModSpecification], output: OutputFormat) -> Result<()> {
+    match output {
+        OutputFormat::Text => Ok(()),
+        OutputFormat::Json => print_json(&IntegrateOutput {
+            mods: mods
+                .iter()
+                .filter_map(|m| state.store.get_mod_info(m))
+                .collect(),
+        }),
+    }
+}
+
+/// In JSON mode commands that make changes print the resulting listing.
+fn action_profiles(dirs: Dirs, action: ActionProfiles, opts: &Options) -> Result<()> {
+    let mut state = init_state(dirs, opts)?;
+    if let ActionProfiles::Diff { old, new, code } = action {
+        return action_profile_diff(&state, &old, new, code, opts);
+    }
+    let mod_data = &mut **state.mod_data;
+
+    let list = matches!(action, ActionProfiles::List) || opts.output == OutputFormat::Json;
+    match action {
+        ActionProfiles::List => {}
+        ActionProfiles::Create { name } => mod_data.add_profile(&name)?,
+        ActionProfiles::Delete { name } => mod_data.remove_profile(&name)?,
+        ActionProfiles::Rename { name, new_name } => mod_data.rename_profile(&name, &new_name)?,
+        ActionProfiles::Duplicate { name, new_name } => {
+            mod_data.duplicate_profile(&name, &new_name)?
+        }
+        ActionProfiles::Activate { name } => mod_data.set_active_profile(&name)?,
+        ActionProfiles::Inherit { name, from } => mod_data.set_parent(&name, from.as_deref())?,
+        ActionProfiles::Diff { .. } => unreachable!(),
+    }
+    state.mod_data.save()?;
+
+    if list {
+        let profiles = state
+            .mod_data
+            .profiles
+            .iter()
+            .map(|(name, profile)| ProfileOutput {
+                name,
+                active: *name == state.mod_data.active_profile,
+                parent: profile.parent.as_deref(),
+                mods: &profile.mods,
+            })
+            .collect::<Vec<_>>();
+        match opts.output {
+            OutputFormat::Text => {
+                for profile in profiles {
+                    let active = if profile.active { '*' } else { ' ' };
+                    print!("{active} {} ({} entries)", profile.name, profile.mods.len());
+                    match profile.parent {
+                        Some(parent) => println!(" inherits from `{parent}`"),
+                        None => println!(),
+                    }
+                }
+            }
+            OutputFormat::Json => print_json(&profiles)?,
+        }
+    }
+    Ok(())
+}
+
+fn action_profile_diff(
+    state: &State,
+    old: &str,
+    new: Option<String>,
+    code: Option<PathBuf>,
+    opts: &Options,
+) -> Result<()> {
+    let old_mods = state.mod_data.profile_mods(old)?;
+    let new_mods = match code {
+        Some(path) => {
+            let code = if path.as_os_str() == "-" {
+                std::io::read_to_string(std::io::stdin())?
+            } else {
+                std::fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read {}", path.display()))?
+            };
+            ProfileMods::from_code(&code)
+        }
+        None => state
+            .mod_data
+            .profile_mods(new.as_deref().unwrap_or(&state.mod_data.active_profile))?,
+    };
+    let diff = ProfileDiff::new(&old_mods, &new_mods, Some(&state.store));
+
+    match opts.output {
+        OutputFormat::Text => {
+            for (marker, line) in diff.lines() {
+                println!("{marker} {line}");
+            }
+            if diff.is_empty() {
+                println!("profiles are identical");
+            }
+        }
+        OutputFormat::Json => print_json(&diff)?,
+    }
+    Ok(())
+}
+
+fn action_groups(dirs: Dirs, action: ActionGroups, opts: &Options) -> Result<()> {
+    let mut state = init_state(dirs, opts)?;
+    let mod_data = &mut **state.mod_data;
+
+    let list = matches!(action, ActionGroups::List) || opts.output == OutputFormat::Json;
+    match action {
+        ActionGroups::List => {}
+        ActionGroups::Create { name } => mod_data.add_group(&name)?,
+        ActionGroups::Delete { name } => mod_data.remove_group(&name)?,
+        ActionGroups::Rename { name, new_name } => mod_data.rename_group(&name, &new_name)?,
+        ActionGroups::Include {
+            name,
+            target,
+            index,
+        } => {
+            mod_data.group_mut(&name)?;
+            if let Some(parent) = &target.group
+                && mod_data.group_reaches(&name, parent)
+            {
+                bail!("including group `{name}` in group `{parent}` would make it contain itself");
+            }
+            let (list_name, mut mods) = ModList::get(mod_data, &target)?;
+            if mods.find_group(&name).is_some() {
+                bail!("group `{name}` is already in {list_name}");
+            }
+            mods.insert(
+                index,
+                ModOrGroup::Group {
+                    group_name: name,
+                    enabled: true,
+                    overrides: Default::default(),
+                },
+            );
+        }
+        ActionGroups::Exclude { name, target } => {
+            let (list_name, mut mods) = ModList::get(mod_data, &target)?;
+            let index = mods
+                .find_group(&name)
+                .with_context(|| format!("group `{name}` is not in {list_name}"))?;
+            mods.remove(index);
+        }
+        ActionGroups::Set {
+            name,
+            target,
+            enabled,
+        } => {
+            let (list_name, mut mods) = ModList::get(mod_data, &target)?;
+            let index = mods
+                .find_group(&name)
+                .with_context(|| format!("group `{name}` is not in {list_name}"))?;
+            if let ModOrGroup::Group { enabled: e, .. } = &mut mods.0[index] {
+                *e = enabled;
+            }
+        }
+        ActionGroups::Override {
+            name,
+            url,
+            target,
+            enabled,
+        } => {
+            let group = mod_data
+                .groups
+                .get(&name)
+                .with_context(|| format!("group `{name}` does not exist"))?;
+            if !walk_mods(&mod_data.groups, &group.mods)
+                .iter()
+                .any(|walked| walked.config.spec.url == url)
+            {
+                bail!("mod `{url}` is not in group `{name}`");
+            }
+            let (list_name, mut mods) = ModList::get(mod_data, &target)?;
+            let index = mods
+                .find_group(&name)
+                .with_context(|| format!("group `{name}` is not in {list_name}"))?;
+            if let ModOrGroup::Group { overrides, .. } = &mut mods.0[index] {
+                match enabled {
+                    Some(enabled) => {
+                        overrides.insert(url, enabled);
+                    }
+                    None => {
+                        overrides.remove(&url);
+                    }
+                }
+            }
+        }
+    }
+    state.mod_data.save()?;
+
+    if list {
+        let groups = state
+            .mod_data
+            .groups
+            .iter()
+            .map(|(name, group)| GroupOutput {
+                name,
+                mods: &group.mods,
+            })
+            .collect::<Vec<_>>();
+        match opts.output {
+            OutputFormat::Text => {
+                for group in groups {
+                    println!("{} ({} mods)", group.name, group.mods.len());
+                }
+            }
+            OutputFormat::Json => print_json(&groups)?,
+        }
+    }
+    Ok(())
+}
+
+/// Profile `target` refers to if it inherits or excludes the inherited mod `url` rather than
+/// containing it.
+fn inheriting_profile(mod_data: &ModData, target: &ModTarget, url: &str) -> Option<String> {
+    if target.group.is_some() {
+        return None;
+    }
+    let profile = target
+        .profile
+        .clone()
+        .unwrap_or_else(|| mod_data.active_profile.clone());
+    let inherited = mod_data.profiles.get(&profile)?.excluded.contains(url)
+        || mod_data
+            .walk_mods(&profile)
+            .iter()
+            .any(|walked| walked.inherited_from.is_some() && walked.config.spec.url == url);
+    inherited.then_some(profile)
+}
+
+/// Mods of a profile or group, see [`ModTarget`].
+struct ModList<'a>(&'a mut Vec<ModOrGroup>);
+
+impl ModList<'_> {
+    fn get<'a>(mod_data: &'a mut ModData, target: &ModTarget) -> Result<(String, ModList<'a>)> {
+        Ok(match &target.group {
+            Some(group) => (
+                format!("group `{group}`"),
+                ModList(&mut mod_data.group_mut(group)?.mods),
+            ),
+            None => {
+                let profile = target
+                    .profile
+                    .clone()
+                    .unwrap_or_else(|| mod_data.active_profile.clone());
+                (
+                    format!("profile `{profile}`"),
+                    ModList(&mut mod_data.profile_mut(&profile)?.mods),
+                )
+            }
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn position(&self, url: &str) -> Option<usize> {
+        self.0
+            .iter()
+            .position(|m| matches!(m, ModOrGroup::Individual(mc) if mc.spec.url == url))
+    }
+
+    fn find_group(&self, name: &str) -> Option<usize> {
+        self.0
+            .iter()
+            .position(|m| matches!(m, ModOrGroup::Group { group_name, .. } if group_name == name))
+    }
+
+    fn get_mut(&mut self, index: usize) -> &mut ModConfig {
+        match &mut self.0[index] {
+            ModOrGroup::Individual(mc) => mc,
+            ModOrGroup::Group { .. } => unreachable!(),
+        }
+    }
+
+    fn insert(&mut self, index: usize, entry: ModOrGroup) {
+        self.0.insert(index.min(self.0.len()), entry);
+    }
+
+    fn move_to(&mut self, from: usize, to: usize) {
+        let m = self.0.remove(from);
+        self.insert(to, m);
+    }
+
+    fn entries(&self, store: &ModStore) -> Vec<ModListEntry> {
+        self.0
+            .iter()
+            .map(|m| match m {
+                ModOrGroup::Individual(mc) => ModListEntry::Mod {
+                    url: mc.spec.url.clone(),
+                    name: store.get_mod_info(&mc.spec).map(|info| info.name),
+                    enabled: mc.enabled,
+                    required: mc.required,
+                    inherited_from: None,
+                },
+                ModOrGroup::Group {
+                    group_name,
+                    enabled,
+                    overrides,
+                } => ModListEntry::Group {
+                    group: group_name.clone(),
+                    enabled: *enabled,
+                    overrides: overrides.clone(),
+                },
+            })
+            .collect()
+    }
+
+    fn remove(&mut self, index: usize) {
+        self.0.remove(index);
+    }
+}
+
+async fn action_mods(dirs: Dirs, action: ActionMods, opts: &Options) -> Result<()> {
+    let mut state = init_state(dirs, opts)?;
+
+    let find = |mods: &ModList, name: &str, url: &str| {
+        mods.position(url)
+            .with_context(|| format!("mod `{url}` is not in {name}"))
+    };
+
+    let list = matches!(action, ActionMods::List { .. }) || opts.output == OutputFormat::Json;
+    let list_target = action.target().clone();
+    match action {
+        ActionMods::List { .. } => {}
+        ActionMods::Add {
+            target,
+            update,
+            mods,
+        } => {
+            let specs = mods
+                .into_iter()
+                .map(ModSpecification::new)
+                .collect::<Vec<_>>();
+            // check the target exists before resolving
+            ModList::get(&mut state.mod_data, &target)?;
+            let resolved = resolve_mods_with_provider_init(
+                &mut state,
+                &specs,
+                update,
+                &cancel_on_ctrl_c(),
+                |state, url, factory| init_provider(state, url, factory, opts),
+            )
+            .await?;
+
+            let primary_mods = specs.into_iter().collect::<HashSet<_>>();
+            let mod_data = &mut **state.mod_data;
+            let profile = target
+                .profile
+                .clone()
+                .unwrap_or_else(|| mod_data.active_profile.clone());
+            for (resolved_spec, info) in resolved {
+                let is_dep = !primary_mods.contains(&resolved_spec);
+                if target.group.is_none() {
+                    mod_data.add_resolved_mod(&profile, &resolved_spec, &info, is_dep);
+                    continue;
+                }
+                let (_, mods) = ModList::get(mod_data, &target)?;
+                let satisfied = is_dep
+                    && mods.0.iter_mut().any(|m| match m {
+                        ModOrGroup::Individual(mc) => {
+                            let satisfies = mc.spec.satisfies_dependency(&resolved_spec);
+                            mc.enabled |= satisfies;
+                            satisfies
+                        }
+                        ModOrGroup::Group { .. } => false,
+                    });
+                if !satisfied {
+                    mods.0.push(ModOrGroup::Individual(ModConfig {
+                        spec: info.spec.clone(),
+                        required: info.suggested_require,
+                        enabled: true,
+                    }));
+                }
+            }
+        }
+        ActionMods::Remove { target, url } => {
+            if let Some(profile) = inheriting_profile(&state.mod_data, &target, &url) {
+                state.mod_data.profile_mut(&profile)?.excluded.insert(url);
+            } else {
+                let (name, mut mods) = ModList::get(&mut state.mod_data, &target)?;
+                let index = find(&mods, &name, &url)?;
+                mods.remove(index);
+            }
+        }
+        ActionMods::Move { target, url, index } => {
+            let (name, mut mods) = ModList::get(&mut state.mod_data, &target)?;
+            let from = find(&mods, &name, &url)?;
+            mods.move_to(from, index.min(mods.len() - 1));
+        }
+        ActionMods::Set {
+            target,
+            url,
+            enabled,
+            required,
+        } => {
+            if let Some(profile) = inheriting_profile(&state.mod_data, &target, &url) {
+                if required.is_some() {
+                    bail!("mod `{url}` is inherited, change whether it is required in the profile it is inherited from");
+                }
+                let profile = state.mod_data.profile_mut(&profile)?;
+                profile.excluded.remove(&url);
+                if let Some(enabled) = enabled {
+                    profile.overrides.insert(url, enabled);
+                }
+            } else {
+                let (name, mut mods) = ModList::get(&mut state.mod_data, &target)?;
+                let index = find(&mods, &name, &url)?;
+                let mc = mods.get_mut(index);
+                if let Some(enabled) = enabled {
+                    mc.enabled = enabled;
+                }
+                if let Some(required) = required {
+                    mc.required = required;
+                }
+            }
+        }
+    }
+    state.mod_data.save()?;
+
+    if list {
+        let store = state.store.clone();
+        let (_, mods) = ModList::get(&mut state.mod_data, &list_target)?;
+        let mut entries = mods.entries(&store);
+        if list_target.group.is_none() {
+            let profile = list_target
+                .profile
+                .unwrap_or_else(|| state.mod_data.active_profile.clone());
+            entries.extend(
+                state
+                    .mod_data
+                    .walk_mods(&profile)
+                    .into_iter()
+                    .filter_map(|walked| {
+                        Some(ModListEntry::Mod {
+                            url: walked.config.spec.url.clone(),
+                            name: store
+                                .get_mod_info(&walked.config.spec)
+                                .map(|info| info.name),
+                            enabled: walked.enabled,
+                            required: walked.config.required,
+                            inherited_from: Some(walked.inherited_from?.to_string()),
+                        })
+                    }),
+            );
+        }
+        match opts.output {
+            OutputFormat::Text => {
+                for (i, entry) in entries.iter().enumerate() {
+                    if matches!(
+                        entry,
+                        ModListEntry::Mod {
+                            inherited_from: Some(_),
+                            ..
+                        }
+                    ) {
+                        println!("  - {entry}");
+                    } else {
+                        println!("{i:>3} {entry}");
+                    }
+                }
+            }
+            OutputFormat::Json => print_json(&entries)?,
+        }
+    }
+    Ok(())
+}
+
+/// Provider as printed by `providers list`.
+#[derive(Serialize)]
+struct ProviderOutput {
+    id: &'static str,
+    parameters: Vec<ProviderParameterOutput>,
+}
+
+#[derive(Serialize)]
+struct ProviderParameterOutput {
+    id: &'static str,
+    name: &'static str,
+    /// Whether a value is stored in the config or secret store.
+    stored: bool,
+    /// Whether a value is supplied by `--provider-param` or the environment.
+    overridden: bool,
+}
+
+async fn action_providers(dirs: Dirs, action: ActionProviders, opts: &Options) -> Result<()> {
+    let mut state = init_state(dirs, opts)?;
+
+    let get_factory = |id: &str| {
+        ModStore::get_provider_factories()
+            .find(|f| f.id == id)
+            .with_context(|| format!("no provider named `{id}`"))
+    };
+
+    let list = matches!(action, ActionProviders::List) || opts.output == OutputFormat::Json;
+    match action {
+        ActionProviders::List => {}
+        ActionProviders::Set { provider, params } => {
+            let factory = get_factory(&provider)?;
+            let params = params
+                .into_iter()
+                .map(|p| format!("{provider}.{p}").parse::<ProviderParam>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| anyhow!(e))?;
+
+            let previous = state.provider_parameters(factory.id);
+            let checked = match provider_parameters(&mut state, factory, opts, &params) {
+                Ok(params) => state.store.add_provider_checked(factory, &params).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = checked {
+                // don't keep parameters that failed the check
+                state.set_provider_parameters(factory, previous)?;
+                return Err(e.context(format!("failed to set up provider `{}`", factory.id)));
+            }
+        }
+        ActionProviders::Test { provider } => {
+            let factory = get_factory(&provider)?;
+            let params = merged_provider_parameters(&state, factory, opts);
+            if let Some(p) = factory
+                .parameters
+                .iter()
+                .find(|p| !params.contains_key(p.id))
+            {
+                bail!("missing parameter `{}.{}` ({})", factory.id, p.id, p.name);
+            }
+            state.store.add_provider_checked(factory, &params).await?;
+            if opts.output == OutputFormat::Text {
+                println!("provider `{}` OK", factory.id);
+            }
+        }
+        ActionProviders::Clear { provider } => {
+            let factory = get_factory(&provider)?;
+            state.set_provider_parameters(factory, Default::default())?;
+        }
+        ActionProviders::Store { kind } => {
+            state.set_secret_store(kind)?;
+        }
+    }
+
+    if list {
+        let providers = ModStore::get_provider_factories()
+            .map(|factory| ProviderOutput {
+                id: factory.id,
+                parameters: factory
+                    .parameters
+                    .iter()
+                    .map(|p| ProviderParameterOutput {
+                        id: p.id,
+                        name: p.name,
+                        stored: state.provider_parameters(factory.id).contains_key(p.id),
+                        overridden: opts
+                            .provider_parameters
+                            .get(factory.id)
+                            .is_some_and(|params| params.contains_key(p.id)),
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        match opts.output {
+            OutputFormat::Text => {
+                for provider in providers {
+                    println!("{}", provider.id);
+                    for p in provider.parameters {
+                        let status = match (p.stored, p.overridden) {
+                            (_, true) => "overridden",
+                            (true, false) => "stored",
+                            (false, false) => "not set",
+                        };
+                        println!("    {} ({}): {status}", p.id, p.name);
+                    }
+                }
+            }
+            OutputFormat::Json => print_json(&providers)?,
+        }
+    }
     Ok(())
 }
@@ -1,27 +1,42 @@
-use std::collections::BTreeSet;
-
 use anyhow::Result;
 
-use crate::providers::ModSpecification;
+use super::{Diagnostic, Lint, LintCtxt, LintFactory, LintId, LintLevel, LintMod, ModContents};
 
-use super::{Lint, LintCtxt};
+inventory::submit! {
+    LintFactory {
+        id: LintId::ARCHIVE_WITH_MULTIPLE_PAKS,
+        description: "Archive with multiple paks",
+        default_level: LintLevel::Warn,
+        default_allowed_paths: &[],
+        requires_game_pak: false,
+        new: || Box::<ArchiveMultiplePaksLint>::default(),
+    }
+}
 
 #[derive(Default)]
-pub struct ArchiveMultiplePaksLint;
+pub struct ArchiveMultiplePaksLint {
+    diagnostics: Vec<Diagnostic>,
+}
 
 impl Lint for ArchiveMultiplePaksLint {
-    type Output = BTreeSet<ModSpecification>;
+    fn visit_mod(&mut self, _lcx: &LintCtxt, lint_mod: &LintMod) -> Result<()> {
+        if let ModContents::Pak {
+            multiple_paks: true,
+            ..
+        } = lint_mod.contents
+        {
+            self.diagnostics.push(
+                Diagnostic::new(
+                    LintId::ARCHIVE_WITH_MULTIPLE_PAKS,
+                    "archive contains multiple paks, only the first one is used",
+                )
+                .with_mod(lint_mod.spec.clone()),
+            );
+        }
+        Ok(())
+    }
 
-    fn check_mods(&mut self, lcx: &LintCtxt) -> Result<Self::Output> {
-        let mut archive_multiple_paks_mods = BTreeSet::new();
-        lcx.for_each_mod(
-            |_, _, _| Ok(()),
-            None::<fn(ModSpecification)>,
-            None::<fn(ModSpecification)>,
-            Some(|mod_spec| {
-                archive_multiple_paks_mods.insert(mod_spec);
-            }),
-        )?;
-        Ok(archive_multiple_paks_mods)
+    fn finish(&mut self, _lcx: &LintCtxt) -> Result<Vec<Diagnostic>> {
+        Ok(std::mem::take(&mut self.diagnostics))
     }
 }
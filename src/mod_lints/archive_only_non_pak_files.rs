@@ -1,25 +1,36 @@
-use std::collections::BTreeSet;
+use super::{Diagnostic, Lint, LintCtxt, LintFactory, LintId, LintLevel, LintMod, ModContents};
 
-use crate::providers::ModSpecification;
-
-use super::{Lint, LintCtxt};
+inventory::submit! {
+    LintFactory {
+        id: LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES,
+        description: "Archive with only non-pak files",
+        default_level: LintLevel::Warn,
+        default_allowed_paths: &[],
+        requires_game_pak: false,
+        new: || Box::<ArchiveOnlyNonPakFilesLint>::default(),
+    }
+}
 
 #[derive(Default)]
-pub struct ArchiveOnlyNonPakFilesLint;
+pub struct ArchiveOnlyNonPakFilesLint {
+    diagnostics: Vec<Diagnostic>,
+}
 
 impl Lint for ArchiveOnlyNonPakFilesLint {
-    type Output = BTreeSet<ModSpecification>;
+    fn visit_mod(&mut self, _lcx: &LintCtxt, lint_mod: &LintMod) -> anyhow::Result<()> {
+        if let ModContents::OnlyNonPakFiles = lint_mod.contents {
+            self.diagnostics.push(
+                Diagnostic::new(
+                    LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES,
+                    "archive contains only non-pak files",
+                )
+                .with_mod(lint_mod.spec.clone()),
+            );
+        }
+        Ok(())
+    }
 
-    fn check_mods(&mut self, lcx: &LintCtxt) -> anyhow::Result<Self::Output> {
-        let mut archive_only_non_pak_files_mods = BTreeSet::new();
-        lcx.for_each_mod(
-            |_, _, _| Ok(()),
-            None::<fn(ModSpecification)>,
-            Some(|mod_spec| {
-                archive_only_non_pak_files_mods.insert(mod_spec);
-            }),
-            None::<fn(ModSpecification)>,
-        )?;
-        Ok(archive_only_non_pak_files_mods)
+    fn finish(&mut self, _lcx: &LintCtxt) -> anyhow::Result<Vec<Diagnostic>> {
+        Ok(std::mem::take(&mut self.diagnostics))
     }
 }
@@ -0,0 +1,258 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{bail, Context, Result};
+use indexmap::IndexSet;
+use rayon::prelude::*;
+use unreal_asset::exports::{Export, ExportBaseTrait, ExportNormalTrait};
+use unreal_asset::properties::struct_property::StructProperty;
+use unreal_asset::properties::{Property, PropertyDataTrait};
+use unreal_asset::Asset;
+
+use crate::integrate::merge::{same_imports, Diff};
+use crate::providers::ModSpecification;
+use crate::{parse_asset, AssetData};
+
+use super::{
+    uexp_path, Diagnostic, Lint, LintCtxt, LintFactory, LintId, LintLevel, LintMod, ModFile, ModPak,
+};
+
+inventory::submit! {
+    LintFactory {
+        id: LintId::ASSET_CONFLICTS,
+        description: "Conflicting assets compared property by property",
+        default_level: LintLevel::Allow,
+        default_allowed_paths: &["fsd/content/_interop"],
+        requires_game_pak: true,
+        new: || Box::<AssetConflictsLint>::default(),
+    }
+}
+
+/// Number of colliding properties listed in a diagnostic.
+const MAX_LISTED: usize = 5;
+
+/// For assets shipped by more than one mod, diffs each mod's copy against the game's and reports
+/// whether the mods change disjoint properties or collide.
+#[derive(Default)]
+pub struct AssetConflictsLint {
+    per_asset_modifiers: BTreeMap<String, IndexSet<ModSpecification>>,
+}
+
+impl Lint for AssetConflictsLint {
+    fn visit_file(
+        &mut self,
+        _lcx: &LintCtxt,
+        lint_mod: &LintMod,
+        _pak: &ModPak,
+        file: &ModFile,
+    ) -> Result<()> {
+        if file.normalized_path.ends_with(".uasset") {
+            self.per_asset_modifiers
+                .entry(file.normalized_path.clone())
+                .or_default()
+                .insert(lint_mod.spec.clone());
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, lcx: &LintCtxt) -> Result<Vec<Diagnostic>> {
+        let game_pak = lcx.game_pak()?;
+
+        let conflicts = std::mem::take(&mut self.per_asset_modifiers)
+            .into_iter()
+            .filter(|(_, modifiers)| modifiers.len() > 1)
+            .collect::<Vec<_>>();
+
+        let diagnostics = conflicts
+            .into_par_iter()
+            .map(|(path, modifiers)| -> Result<Option<Diagnostic>> {
+                let mut copies = vec![];
+                for mod_spec in &modifiers {
+                    let pak = lcx
+                        .get_mod(mod_spec)
+                        .and_then(LintMod::pak)
+                        .context("mod has no pak")?;
+                    let Some(copy) = pak.read_asset(&path)? else {
+                        return Ok(None);
+                    };
+                    copies.push(copy);
+                }
+                let base = match (game_pak.read(&path)?, game_pak.read(&uexp_path(&path))?) {
+                    (Some(uasset), Some(uexp)) => Some((uasset, uexp)),
+                    _ => None,
+                };
+
+                let message = match compare(base.as_ref(), &copies) {
+                    Ok(Comparison::Identical) => "mods ship identical copies".to_string(),
+                    Ok(Comparison::Disjoint) => format!(
+                        "{} mods change disjoint properties and may be mergeable",
+                        modifiers.len()
+                    ),
+                    Ok(Comparison::Colliding(keys)) => {
+                        let mut listed = keys
+                            .iter()
+                            .take(MAX_LISTED)
+                            .map(String::as_str)
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        if keys.len() > MAX_LISTED {
+                            listed.push_str(&format!(" and {} more", keys.len() - MAX_LISTED));
+                        }
+                        format!(
+                            "{} mods change the same properties: {listed}",
+                            modifiers.len()
+                        )
+                    }
+                    Err(e) => format!("could not compare copies: {e:#}"),
+                };
+
+                Ok(Some(
+                    Diagnostic::new(LintId::ASSET_CONFLICTS, message)
+                        .with_mods(modifiers)
+                        .with_path(path),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(diagnostics.into_iter().flatten().collect())
+    }
+}
+
+enum Comparison {
+    /// Every mod ships the same bytes.
+    Identical,
+    /// No property is changed differently by more than one mod.
+    Disjoint,
+    /// Properties changed differently by more than one mod.
+    Colliding(BTreeSet<String>),
+}
+
+/// Diff each copy against `base`, the game's version if there is one.
+fn compare(base: Option<&AssetData>, copies: &[AssetData]) -> Result<Comparison> {
+    if copies.windows(2).all(|w| w[0] == w[1]) {
+        return Ok(Comparison::Identical);
+    }
+
+    let base = base
+        .map(|(uasset, uexp)| parse_asset(uasset.as_slice(), uexp.as_slice()))
+        .transpose()
+        .context("failed to parse game asset")?;
+    let copies = copies
+        .iter()
+        .map(|(uasset, uexp)| parse_asset(uasset.as_slice(), uexp.as_slice()))
+        .collect::<Result<Vec<_>>>()?;
+    // Object references would otherwise compare equal while pointing at different packages.
+    if copies.windows(2).any(|w| !same_imports(&w[0], &w[1])) {
+        bail!("mods import different packages");
+    }
+
+    let base = base.as_ref().map(flatten).unwrap_or_default();
+    let copies = copies.iter().map(flatten).collect::<Vec<_>>();
+    Ok(classify(&base, &copies))
+}
+
+/// Whether the flattened copies of differing assets change any value differently.
+fn classify<V: PartialEq>(
+    base: &BTreeMap<String, V>,
+    copies: &[BTreeMap<String, V>],
+) -> Comparison {
+    let colliding = Diff::new(base, copies)
+        .colliding()
+        .map(str::to_string)
+        .collect::<BTreeSet<_>>();
+    if colliding.is_empty() {
+        Comparison::Disjoint
+    } else {
+        Comparison::Colliding(colliding)
+    }
+}
+
+/// Part of an asset which can be changed independently.
+#[derive(PartialEq)]
+enum Value<'a> {
+    /// An export's presence; its contents are compared through its properties and rows.
+    Export,
+    Property(&'a Property),
+    Row(&'a StructProperty),
+}
+
+/// Every export, property and data table row of `asset`, keyed by `Export`, `Export.Property`
+/// and `Export.Row` respectively.
+fn flatten<C: std::io::Read + std::io::Seek>(asset: &Asset<C>) -> BTreeMap<String, Value<'_>> {
+    let mut values = BTreeMap::new();
+    let mut seen_exports = BTreeMap::<String, usize>::new();
+    for export in &asset.asset_data.exports {
+        let name = export.get_base_export().object_name.get_owned_content();
+        // Exports with the same name are told apart by the order they appear in.
+        let count = seen_exports.entry(name.clone()).or_default();
+        let export_key = match *count {
+            0 => name,
+            n => format!("{name}#{n}"),
+        };
+        *count += 1;
+
+        if let Some(normal_export) = export.get_normal_export() {
+            for property in &normal_export.properties {
+                let mut key = format!("{export_key}.{}", property.get_name().get_owned_content());
+                if property.get_duplication_index() != 0 {
+                    key.push_str(&format!("[{}]", property.get_duplication_index()));
+                }
+                values.insert(key, Value::Property(property));
+            }
+        }
+        if let Export::DataTableExport(data_table) = export {
+            for row in &data_table.table.data {
+                let key = format!("{export_key}.{}", row.name.get_owned_content());
+                values.insert(key, Value::Row(row));
+            }
+        }
+        values.insert(export_key, Value::Export);
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_identical() {
+        // identical copies are not parsed
+        let copy = (b"uasset".to_vec(), b"uexp".to_vec());
+        assert!(matches!(
+            compare(None, &[copy.clone(), copy]),
+            Ok(Comparison::Identical)
+        ));
+
+        let other = (b"other".to_vec(), b"uexp".to_vec());
+        assert!(compare(None, &[other, (b"uasset".to_vec(), b"uexp".to_vec())]).is_err());
+    }
+
+    // There is no cooked data table among the test assets, so the flattened values of an edited
+    // table are stood in for by plain values.
+    #[test]
+    fn test_classify() {
+        let values = |values: &[(&str, i32)]| {
+            values
+                .iter()
+                .map(|(k, v)| (k.to_string(), *v))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let base = values(&[("Table", 0), ("Table.a", 1), ("Table.b", 1)]);
+
+        let copies = [
+            values(&[("Table", 0), ("Table.a", 2), ("Table.b", 1)]),
+            values(&[("Table", 0), ("Table.a", 1), ("Table.b", 2), ("Table.c", 1)]),
+        ];
+        assert!(matches!(classify(&base, &copies), Comparison::Disjoint));
+
+        let copies = [
+            values(&[("Table", 0), ("Table.a", 2), ("Table.b", 1)]),
+            values(&[("Table", 0), ("Table.a", 3)]),
+        ];
+        // only one mod removes `b`
+        let Comparison::Colliding(keys) = classify(&base, &copies) else {
+            panic!("expected colliding changes");
+        };
+        assert_eq!(keys, ["Table.a".to_string()].into());
+    }
+}
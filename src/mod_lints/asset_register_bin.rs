@@ -1,34 +1,49 @@
 use anyhow::Result;
-use std::collections::{BTreeMap, BTreeSet};
 
-use crate::providers::ModSpecification;
-
-use super::{Lint, LintCtxt};
+use super::{
+    Diagnostic, Fix, Lint, LintCtxt, LintFactory, LintId, LintLevel, LintMod, ModFile, ModPak,
+};
+
+inventory::submit! {
+    LintFactory {
+        id: LintId::ASSET_REGISTRY_BIN,
+        description: "Mods containing AssetRegistry.bin",
+        default_level: LintLevel::Warn,
+        default_allowed_paths: &[],
+        requires_game_pak: false,
+        new: || Box::<AssetRegisterBinLint>::default(),
+    }
+}
 
 #[derive(Default)]
-pub struct AssetRegisterBinLint;
+pub struct AssetRegisterBinLint {
+    diagnostics: Vec<Diagnostic>,
+}
 
 impl Lint for AssetRegisterBinLint {
-    type Output = BTreeMap<ModSpecification, BTreeSet<String>>;
-
-    fn check_mods(&mut self, lcx: &LintCtxt) -> Result<Self::Output> {
-        let mut asset_register_bin_mods = BTreeMap::new();
-
-        lcx.for_each_mod_file(|mod_spec, _, _, raw_path, normalized_path| {
-            if let Some(filename) = raw_path.file_name() {
-                if filename == "AssetRegistry.bin" {
-                    asset_register_bin_mods
-                        .entry(mod_spec.clone())
-                        .and_modify(|paths: &mut BTreeSet<String>| {
-                            paths.insert(normalized_path.clone());
-                        })
-                        .or_insert_with(|| [normalized_path.clone()].into());
-                }
-            }
-
-            Ok(())
-        })?;
+    fn visit_file(
+        &mut self,
+        _lcx: &LintCtxt,
+        lint_mod: &LintMod,
+        _pak: &ModPak,
+        file: &ModFile,
+    ) -> Result<()> {
+        if file
+            .path
+            .file_name()
+            .is_some_and(|f| f == "AssetRegistry.bin")
+        {
+            self.diagnostics.push(
+                Diagnostic::new(LintId::ASSET_REGISTRY_BIN, "includes `AssetRegistry.bin`")
+                    .with_mod(lint_mod.spec.clone())
+                    .with_path(&file.normalized_path)
+                    .with_fix(Fix::RemoveFile),
+            );
+        }
+        Ok(())
+    }
 
-        Ok(asset_register_bin_mods)
+    fn finish(&mut self, _lcx: &LintCtxt) -> Result<Vec<Diagnostic>> {
+        Ok(std::mem::take(&mut self.diagnostics))
     }
 }
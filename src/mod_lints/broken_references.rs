@@ -0,0 +1,348 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use rayon::prelude::*;
+use tracing::trace;
+use unreal_asset::exports::{Export, ExportNormalTrait};
+use unreal_asset::properties::Property;
+
+use crate::integrate::INTEGRATION_PAK;
+use crate::providers::ModSpecification;
+use crate::{game_path, normalize_path, parse_asset};
+
+use super::{Diagnostic, Lint, LintCtxt, LintFactory, LintId, LintLevel, LintMod, ModFile, ModPak};
+
+inventory::submit! {
+    LintFactory {
+        id: LintId::BROKEN_REFERENCES,
+        description: "Mods referencing missing assets or undeclared dependencies",
+        default_level: LintLevel::Allow,
+        default_allowed_paths: &[],
+        requires_game_pak: true,
+        new: || Box::<BrokenReferencesLint>::default(),
+    }
+}
+
+/// Parses every `.uasset` and checks that the packages it imports or references through soft
+/// object paths are provided by the game, the mod itself, one of its declared dependencies or the
+/// mod integration.
+#[derive(Default)]
+pub struct BrokenReferencesLint {
+    /// Normalized package paths (without extension) provided by each mod.
+    packages: BTreeMap<ModSpecification, HashSet<String>>,
+    assets: Vec<(ModSpecification, String)>,
+}
+
+impl Lint for BrokenReferencesLint {
+    fn visit_mod(&mut self, _lcx: &LintCtxt, lint_mod: &LintMod) -> Result<()> {
+        let packages = lint_mod
+            .pak()
+            .into_iter()
+            .flat_map(|pak| &pak.files)
+            .filter_map(|f| package_of(&f.normalized_path))
+            .map(str::to_string)
+            .collect();
+        self.packages.insert(lint_mod.spec.clone(), packages);
+        Ok(())
+    }
+
+    fn visit_file(
+        &mut self,
+        _lcx: &LintCtxt,
+        lint_mod: &LintMod,
+        _pak: &ModPak,
+        file: &ModFile,
+    ) -> Result<()> {
+        if file.normalized_path.ends_with(".uasset") {
+            self.assets
+                .push((lint_mod.spec.clone(), file.normalized_path.clone()));
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, lcx: &LintCtxt) -> Result<Vec<Diagnostic>> {
+        let game_pak = lcx.game_pak()?;
+        let integration_packages = integration_packages();
+
+        let references = std::mem::take(&mut self.assets)
+            .into_par_iter()
+            .map(|(mod_spec, path)| -> Result<_> {
+                let references = read_references(lcx, &mod_spec, &path)?;
+                Ok((mod_spec, path, references))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut diagnostics = vec![];
+        for (mod_spec, path, references) in references {
+            let dependencies = lcx
+                .mod_infos
+                .get(&mod_spec)
+                .map(|info| {
+                    info.suggested_dependencies
+                        .iter()
+                        .map(|d| d.url.as_str())
+                        .collect::<HashSet<_>>()
+                })
+                .unwrap_or_default();
+
+            for reference in references {
+                let Some(package) = normalize_package(&reference) else {
+                    continue;
+                };
+                if integration_packages.contains(&package)
+                    || game_pak.contains(&format!("{package}.uasset"))
+                    || game_pak.contains(&format!("{package}.umap"))
+                {
+                    continue;
+                }
+
+                let diagnostic = match self.check_reference(&mod_spec, &package, &dependencies) {
+                    None => continue,
+                    Some(BrokenReference::Dangling) => Diagnostic::new(
+                        LintId::BROKEN_REFERENCES,
+                        format!("references `{reference}`, which no mod or the game provides"),
+                    )
+                    .with_mod(mod_spec.clone())
+                    .with_path(&path),
+                    Some(BrokenReference::Undeclared(providers)) => Diagnostic::new(
+                        LintId::BROKEN_REFERENCES,
+                        format!(
+                            "references `{reference}` from another mod without declaring it as a dependency"
+                        ),
+                    )
+                    .with_mod(mod_spec.clone())
+                    .with_mods(providers.into_iter().cloned())
+                    .with_path(&path),
+                };
+                diagnostics.push(diagnostic);
+            }
+        }
+
+        Ok(diagnostics)
+    }
+}
+
+/// Why a reference to a package neither the game nor the integration provides is broken.
+#[derive(Debug, PartialEq)]
+enum BrokenReference<'a> {
+    /// No mod provides the package.
+    Dangling,
+    /// Only other mods provide the package and none of them is a declared dependency.
+    Undeclared(Vec<&'a ModSpecification>),
+}
+
+impl BrokenReferencesLint {
+    /// Check a reference of `mod_spec` to `package`, which the game and the integration do not
+    /// provide, against the packages of every mod and the URLs of `mod_spec`'s dependencies.
+    fn check_reference(
+        &self,
+        mod_spec: &ModSpecification,
+        package: &str,
+        dependencies: &HashSet<&str>,
+    ) -> Option<BrokenReference<'_>> {
+        let provided_by = |spec: &ModSpecification| {
+            self.packages
+                .get(spec)
+                .is_some_and(|packages| packages.contains(package))
+        };
+        if provided_by(mod_spec) {
+            return None;
+        }
+        let providers = self
+            .packages
+            .keys()
+            .filter(|spec| *spec != mod_spec && provided_by(*spec))
+            .collect::<Vec<_>>();
+        if providers.is_empty() {
+            Some(BrokenReference::Dangling)
+        } else if providers
+            .iter()
+            .any(|spec| dependencies.contains(spec.url.as_str()))
+        {
+            None
+        } else {
+            Some(BrokenReference::Undeclared(providers))
+        }
+    }
+}
+
+/// Normalized package path of an asset file, e.g. `fsd/content/foo` for `fsd/content/foo.uasset`.
+fn package_of(normalized_path: &str) -> Option<&str> {
+    normalized_path
+        .strip_suffix(".uasset")
+        .or_else(|| normalized_path.strip_suffix(".umap"))
+}
+
+/// Normalized package path of a package name such as `/Game/Foo`, or `None` for native and
+/// plugin packages, which are not shipped as assets.
+fn normalize_package(package: &str) -> Option<String> {
+    let package = package.split('.').next()?;
+    let path = if let Some(rest) = package.strip_prefix("/Game/") {
+        format!("fsd/content/{rest}")
+    } else if let Some(rest) = package.strip_prefix("/Engine/") {
+        format!("engine/content/{rest}")
+    } else {
+        return None;
+    };
+    Some(path.to_ascii_lowercase())
+}
+
+/// Packages contained in the mod integration's own pak.
+fn integration_packages() -> &'static HashSet<String> {
+    static PACKAGES: OnceLock<HashSet<String>> = OnceLock::new();
+    PACKAGES.get_or_init(|| {
+        let Ok(pak) = repak::PakReader::new_any(&mut Cursor::new(INTEGRATION_PAK)) else {
+            return HashSet::new();
+        };
+        let mount = Path::new(pak.mount_point());
+        pak.files()
+            .into_iter()
+            .filter_map(|f| {
+                let normalized = normalize_path(&game_path(mount, &f)?);
+                package_of(&normalized).map(str::to_string)
+            })
+            .collect()
+    })
+}
+
+/// Packages the asset at `path` imports or references through soft object paths. Assets which
+/// cannot be parsed are skipped.
+fn read_references(
+    lcx: &LintCtxt,
+    mod_spec: &ModSpecification,
+    path: &str,
+) -> Result<BTreeSet<String>> {
+    let Some(pak) = lcx.get_mod(mod_spec).and_then(LintMod::pak) else {
+        return Ok(BTreeSet::new());
+    };
+    let Some((uasset, uexp)) = pak.read_asset(path)? else {
+        return Ok(BTreeSet::new());
+    };
+    let asset = match parse_asset(uasset, uexp) {
+        Ok(asset) => asset,
+        Err(e) => {
+            trace!("skipping {path}, failed to parse: {e}");
+            return Ok(BTreeSet::new());
+        }
+    };
+
+    let mut references = BTreeSet::new();
+    for import in &asset.imports {
+        if import.outer_index.index == 0 && import.class_name.get_content(|n| n == "Package") {
+            references.insert(import.object_name.get_owned_content());
+        }
+    }
+    for export in &asset.asset_data.exports {
+        if let Some(normal_export) = export.get_normal_export() {
+            for property in &normal_export.properties {
+                soft_references(property, &mut references);
+            }
+        }
+        if let Export::DataTableExport(data_table) = export {
+            for row in &data_table.table.data {
+                for property in &row.value {
+                    soft_references(property, &mut references);
+                }
+            }
+        }
+    }
+    Ok(references)
+}
+
+fn soft_references(property: &Property, references: &mut BTreeSet<String>) {
+    match property {
+        Property::SoftObjectProperty(soft) => {
+            let asset_path = &soft.value.asset_path;
+            let package = match &asset_path.package_name {
+                Some(package_name) => package_name.get_owned_content(),
+                None => asset_path.asset_name.get_owned_content(),
+            };
+            if !package.is_empty() && package != "None" {
+                references.insert(package);
+            }
+        }
+        Property::StructProperty(p) => {
+            p.value.iter().for_each(|p| soft_references(p, references));
+        }
+        Property::ArrayProperty(p) => {
+            p.value.iter().for_each(|p| soft_references(p, references));
+        }
+        Property::SetProperty(p) => {
+            p.value
+                .value
+                .iter()
+                .for_each(|p| soft_references(p, references));
+        }
+        Property::MapProperty(p) => {
+            for (key, value) in &p.value {
+                soft_references(key, references);
+                soft_references(value, references);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(url: &str) -> ModSpecification {
+        ModSpecification::new(url.to_string())
+    }
+
+    #[test]
+    fn test_normalize_package() {
+        assert_eq!(
+            normalize_package("/Game/Foo/Bar.Bar").as_deref(),
+            Some("fsd/content/foo/bar")
+        );
+        assert_eq!(
+            normalize_package("/Engine/BasicShapes/Cube").as_deref(),
+            Some("engine/content/basicshapes/cube")
+        );
+        assert_eq!(normalize_package("/Script/FSD"), None);
+        assert_eq!(package_of("fsd/content/foo.umap"), Some("fsd/content/foo"));
+        assert_eq!(package_of("fsd/content/foo.uexp"), None);
+    }
+
+    #[test]
+    fn test_check_reference() {
+        let (a, b, c) = (spec("a"), spec("b"), spec("c"));
+        let lint = BrokenReferencesLint {
+            packages: [
+                (a.clone(), ["fsd/content/a".to_string()].into()),
+                (b.clone(), ["fsd/content/shared".to_string()].into()),
+                (c.clone(), ["fsd/content/shared".to_string()].into()),
+            ]
+            .into(),
+            assets: vec![],
+        };
+        let no_dependencies = HashSet::new();
+
+        assert_eq!(
+            lint.check_reference(&a, "fsd/content/a", &no_dependencies),
+            None
+        );
+        assert_eq!(
+            lint.check_reference(&a, "fsd/content/missing", &no_dependencies),
+            Some(BrokenReference::Dangling)
+        );
+        assert_eq!(
+            lint.check_reference(&a, "fsd/content/shared", &no_dependencies),
+            Some(BrokenReference::Undeclared(vec![&b, &c]))
+        );
+        assert_eq!(
+            lint.check_reference(&a, "fsd/content/shared", &["c"].into()),
+            None
+        );
+        // a mod providing the package itself needs no dependency
+        assert_eq!(
+            lint.check_reference(&b, "fsd/content/shared", &no_dependencies),
+            None
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{lint_factories, LintId};
+
+/// How a lint's findings are treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintLevel {
+    /// The lint is not run.
+    Allow,
+    /// Findings are reported.
+    Warn,
+    /// Findings are reported and, if enabled, block installing the profile.
+    Deny,
+}
+
+/// Lint levels and path allowlists. Lints without an entry use their defaults.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintConfig {
+    #[serde(default)]
+    pub levels: BTreeMap<LintId, LintLevel>,
+    /// Normalized (lowercase, `/` separated) path prefixes each lint ignores.
+    #[serde(default)]
+    pub allowed_paths: BTreeMap<LintId, Vec<String>>,
+}
+
+impl LintConfig {
+    /// Config which runs exactly `lints` at [`LintLevel::Warn`].
+    pub fn with_enabled(lints: &BTreeSet<LintId>) -> Self {
+        Self {
+            levels: lint_factories()
+                .into_iter()
+                .map(|f| {
+                    let level = if lints.contains(&f.id) {
+                        LintLevel::Warn
+                    } else {
+                        LintLevel::Allow
+                    };
+                    (f.id, level)
+                })
+                .collect(),
+            allowed_paths: Default::default(),
+        }
+    }
+
+    pub fn level(&self, lint: LintId) -> LintLevel {
+        self.levels
+            .get(&lint)
+            .copied()
+            .or_else(|| lint.factory().map(|f| f.default_level))
+            .unwrap_or(LintLevel::Allow)
+    }
+
+    pub fn enabled_lints(&self) -> BTreeSet<LintId> {
+        lint_factories()
+            .into_iter()
+            .map(|f| f.id)
+            .filter(|id| self.level(*id) != LintLevel::Allow)
+            .collect()
+    }
+
+    pub fn denied_lints(&self) -> BTreeSet<LintId> {
+        lint_factories()
+            .into_iter()
+            .map(|f| f.id)
+            .filter(|id| self.level(*id) == LintLevel::Deny)
+            .collect()
+    }
+
+    /// Whether `lint` should ignore the normalized `path`.
+    pub fn is_path_allowed(&self, lint: LintId, path: &str) -> bool {
+        match self.allowed_paths.get(&lint) {
+            Some(prefixes) => prefixes.iter().any(|p| path.starts_with(p.as_str())),
+            None => lint
+                .factory()
+                .is_some_and(|f| f.default_allowed_paths.iter().any(|p| path.starts_with(p))),
+        }
+    }
+
+    /// Apply `other` on top of `self`, replacing the level and allowlist of every lint it lists.
+    pub fn merge(&mut self, other: &LintConfig) {
+        self.levels.extend(&other.levels);
+        self.allowed_paths.extend(
+            other
+                .allowed_paths
+                .iter()
+                .map(|(id, paths)| (*id, paths.clone())),
+        );
+    }
+}
+
+/// Contents of `lints.json`: a config for all profiles plus per-profile overrides.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct LintConfigFile {
+    #[serde(flatten)]
+    pub default: LintConfig,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, LintConfig>,
+}
+
+impl LintConfigFile {
+    /// Read a lint config file, returning the default config if it does not exist.
+    pub fn read(path: &Path) -> Result<Self> {
+        match std::fs::read(path) {
+            Ok(buf) => serde_json::from_slice(&buf)
+                .with_context(|| format!("failed to parse lint config {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Default::default()),
+            Err(e) => {
+                Err(e).with_context(|| format!("failed to read lint config {}", path.display()))
+            }
+        }
+    }
+
+    /// The config for `profile`, its overrides applied on top of the default config.
+    pub fn for_profile(&self, profile: &str) -> LintConfig {
+        let mut config = self.default.clone();
+        if let Some(overrides) = self.profiles.get(profile) {
+            config.merge(overrides);
+        }
+        config
+    }
+}
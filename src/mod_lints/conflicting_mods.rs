@@ -5,41 +5,52 @@ use indexmap::IndexSet;
 
 use crate::providers::ModSpecification;
 
-use super::{Lint, LintCtxt};
+use super::{Diagnostic, Lint, LintCtxt, LintFactory, LintId, LintLevel, LintMod, ModFile, ModPak};
+
+inventory::submit! {
+    LintFactory {
+        id: LintId::CONFLICTING,
+        description: "Mods containing conflicting files",
+        default_level: LintLevel::Warn,
+        default_allowed_paths: &["fsd/content/_interop"],
+        requires_game_pak: false,
+        new: || Box::<ConflictingModsLint>::default(),
+    }
+}
 
 #[derive(Default)]
-pub struct ConflictingModsLint;
-
-const CONFLICTING_MODS_LINT_WHITELIST: [&str; 1] = ["fsd/content/_interop"];
+pub struct ConflictingModsLint {
+    per_path_modifiers: BTreeMap<String, IndexSet<ModSpecification>>,
+}
 
 impl Lint for ConflictingModsLint {
-    type Output = BTreeMap<String, IndexSet<ModSpecification>>;
-
-    fn check_mods(&mut self, lcx: &LintCtxt) -> Result<Self::Output> {
-        let mut per_path_modifiers = BTreeMap::new();
-
-        lcx.for_each_mod_file(|mod_spec, _, _, _, normalized_path| {
-            per_path_modifiers
-                .entry(normalized_path)
-                .and_modify(|modifiers: &mut IndexSet<ModSpecification>| {
-                    modifiers.insert(mod_spec.clone());
-                })
-                .or_insert_with(|| [mod_spec.clone()].into());
-            Ok(())
-        })?;
-
-        let conflicting_mods = per_path_modifiers
+    fn visit_file(
+        &mut self,
+        _lcx: &LintCtxt,
+        lint_mod: &LintMod,
+        _pak: &ModPak,
+        file: &ModFile,
+    ) -> Result<()> {
+        self.per_path_modifiers
+            .entry(file.normalized_path.clone())
+            .or_default()
+            .insert(lint_mod.spec.clone());
+        Ok(())
+    }
+
+    fn finish(&mut self, _lcx: &LintCtxt) -> Result<Vec<Diagnostic>> {
+        let conflicting_mods = std::mem::take(&mut self.per_path_modifiers)
             .into_iter()
-            .filter(|(p, _)| {
-                for whitelisted_path in CONFLICTING_MODS_LINT_WHITELIST {
-                    if p.starts_with(whitelisted_path) {
-                        return false;
-                    }
-                }
-                true
-            })
             .filter(|(_, modifiers)| modifiers.len() > 1)
-            .collect::<BTreeMap<String, IndexSet<ModSpecification>>>();
+            .map(|(path, modifiers)| {
+                Diagnostic::new(
+                    LintId::CONFLICTING,
+                    format!("{} mods modify the same asset", modifiers.len()),
+                )
+                .with_mods(modifiers)
+                .with_path(path)
+            })
+            .collect();
 
         Ok(conflicting_mods)
     }
@@ -1,29 +1,35 @@
-use std::collections::BTreeSet;
-
 use anyhow::Result;
 
-use crate::providers::ModSpecification;
+use super::{Diagnostic, Lint, LintCtxt, LintFactory, LintId, LintLevel, LintMod, ModContents};
 
-use super::{Lint, LintCtxt};
+inventory::submit! {
+    LintFactory {
+        id: LintId::EMPTY_ARCHIVE,
+        description: "Mods containing empty archives",
+        default_level: LintLevel::Warn,
+        default_allowed_paths: &[],
+        requires_game_pak: false,
+        new: || Box::<EmptyArchiveLint>::default(),
+    }
+}
 
 #[derive(Default)]
-pub struct EmptyArchiveLint;
+pub struct EmptyArchiveLint {
+    diagnostics: Vec<Diagnostic>,
+}
 
 impl Lint for EmptyArchiveLint {
-    type Output = BTreeSet<ModSpecification>;
-
-    fn check_mods(&mut self, lcx: &LintCtxt) -> Result<Self::Output> {
-        let mut empty_archive_mods = BTreeSet::new();
-
-        lcx.for_each_mod(
-            |_, _, _| Ok(()),
-            Some(|mod_spec| {
-                empty_archive_mods.insert(mod_spec);
-            }),
-            None::<fn(ModSpecification)>,
-            None::<fn(ModSpecification)>,
-        )?;
+    fn visit_mod(&mut self, _lcx: &LintCtxt, lint_mod: &LintMod) -> Result<()> {
+        if let ModContents::EmptyArchive = lint_mod.contents {
+            self.diagnostics.push(
+                Diagnostic::new(LintId::EMPTY_ARCHIVE, "archive is empty")
+                    .with_mod(lint_mod.spec.clone()),
+            );
+        }
+        Ok(())
+    }
 
-        Ok(empty_archive_mods)
+    fn finish(&mut self, _lcx: &LintCtxt) -> Result<Vec<Diagnostic>> {
+        Ok(std::mem::take(&mut self.diagnostics))
     }
 }
@@ -0,0 +1,80 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Cursor;
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+
+use crate::providers::ModSpecification;
+
+use super::{Fix, LintCtxt, LintMod, LintReport};
+
+/// A mod repacked to resolve the fixable diagnostics of a [`LintReport`].
+#[derive(Debug)]
+pub struct FixedMod {
+    pub spec: ModSpecification,
+    /// Normalized paths of the files which were left out. Files not mounted under the game root
+    /// cannot be repacked and are listed with their mount point, see
+    /// [`ModPak::unmounted`](super::ModPak::unmounted).
+    pub removed: BTreeSet<String>,
+    /// The cleaned mod as a V11 pak.
+    pub data: Vec<u8>,
+}
+
+/// Repack every mod of `lint_ctxt` with a fixable diagnostic in `report` as a V11 pak, leaving
+/// out the files diagnostics suggest removing and those not mounted under the game root. Mods
+/// without fixable diagnostics are not returned.
+pub fn fix_mods(report: &LintReport, lint_ctxt: &LintCtxt) -> Result<Vec<FixedMod>> {
+    let mut removals: BTreeMap<&ModSpecification, BTreeSet<String>> = BTreeMap::new();
+    for diagnostic in &report.diagnostics {
+        for mod_spec in &diagnostic.mods {
+            match (&diagnostic.fix, &diagnostic.path) {
+                (Some(Fix::RemoveFile), Some(path)) => {
+                    removals.entry(mod_spec).or_default().insert(path.clone());
+                }
+                (Some(Fix::Repack), _) => {
+                    removals.entry(mod_spec).or_default();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    lint_ctxt
+        .mods()
+        .par_iter()
+        .filter_map(|lint_mod| {
+            let removed = removals.get(&lint_mod.spec)?.clone();
+            Some((lint_mod, removed))
+        })
+        .map(|(lint_mod, mut removed)| -> Result<FixedMod> {
+            let data = repack(lint_mod, &mut removed)
+                .with_context(|| format!("failed to repack mod {}", lint_mod.spec.url))?;
+            Ok(FixedMod {
+                spec: lint_mod.spec.clone(),
+                removed,
+                data,
+            })
+        })
+        .collect()
+}
+
+fn repack(lint_mod: &LintMod, removed: &mut BTreeSet<String>) -> Result<Vec<u8>> {
+    let pak = lint_mod.pak().context("mod does not contain a pak")?;
+    let mut reader = pak.open()?;
+    let mut writer = repak::PakWriter::new(
+        Cursor::new(vec![]),
+        repak::Version::V11,
+        "../../../".to_string(),
+        None,
+    );
+    for file in &pak.files {
+        if removed.contains(&file.normalized_path) {
+            continue;
+        }
+        let data = pak.reader.get(&file.entry, &mut reader)?;
+        let path = file.path.to_string_lossy().replace('\\', "/");
+        writer.write_file(&path, &mut Cursor::new(data))?;
+    }
+    removed.extend(pak.unmounted.iter().cloned());
+    Ok(writer.write_index()?.into_inner())
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use repak::PakReader;
+use sha2::Digest;
+use tracing::trace;
+
+use crate::{game_path, normalize_path, open_file};
+
+/// The most recently loaded game pak, reused while the file on disk is unchanged so repeated lint
+/// runs do not re-read the index or re-hash assets.
+static CACHE: Mutex<Option<(CacheKey, Arc<GamePak>)>> = Mutex::new(None);
+
+#[derive(PartialEq, Eq)]
+struct CacheKey {
+    path: PathBuf,
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+/// Index of the game's main pak.
+pub struct GamePak {
+    path: PathBuf,
+    reader: PakReader,
+    /// Normalized path (lowercase, `/` separated, relative to the game root) to index entry.
+    entries: HashMap<String, String>,
+    /// SHA-256 of assets hashed so far, by normalized path.
+    hashes: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl GamePak {
+    /// Load the game pak at `path`, or return the cached index if the file has not changed.
+    pub(crate) fn load(path: &Path) -> Result<Arc<Self>> {
+        let metadata = std::fs::metadata(path)
+            .with_context(|| format!("Could not read metadata of {}", path.display()))?;
+        let key = CacheKey {
+            path: path.to_path_buf(),
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        };
+
+        let mut cache = CACHE.lock().unwrap();
+        if let Some((cached_key, game_pak)) = cache.as_ref()
+            && *cached_key == key
+        {
+            trace!("reusing cached game pak index");
+            return Ok(game_pak.clone());
+        }
+
+        let mut reader = BufReader::new(open_file(path)?);
+        let pak = PakReader::new_any(&mut reader)?;
+        let mount_point = PathBuf::from(pak.mount_point());
+        let entries = pak
+            .files()
+            .into_iter()
+            .filter_map(|entry| {
+                let normalized = normalize_path(&game_path(&mount_point, &entry)?);
+                Some((normalized, entry))
+            })
+            .collect();
+
+        let game_pak = Arc::new(Self {
+            path: path.to_path_buf(),
+            reader: pak,
+            entries,
+            hashes: Default::default(),
+        });
+        *cache = Some((key, game_pak.clone()));
+        Ok(game_pak)
+    }
+
+    pub fn contains(&self, normalized_path: &str) -> bool {
+        self.entries.contains_key(normalized_path)
+    }
+
+    /// Contents of the game's asset at `normalized_path`, if there is one.
+    pub fn read(&self, normalized_path: &str) -> Result<Option<Vec<u8>>> {
+        let Some(entry) = self.entries.get(normalized_path) else {
+            return Ok(None);
+        };
+        let mut reader = BufReader::new(open_file(&self.path)?);
+        Ok(Some(self.reader.get(entry, &mut reader)?))
+    }
+
+    /// SHA-256 of the game's asset at `normalized_path`, if there is one. Hashes are cached, so
+    /// each asset is only read once.
+    pub fn hash(&self, normalized_path: &str) -> Result<Option<Vec<u8>>> {
+        if let Some(hash) = self.hashes.lock().unwrap().get(normalized_path) {
+            return Ok(Some(hash.clone()));
+        }
+        let Some(entry) = self.entries.get(normalized_path) else {
+            return Ok(None);
+        };
+
+        let mut reader = BufReader::new(open_file(&self.path)?);
+        let mut hasher = sha2::Sha256::new();
+        self.reader.read_file(entry, &mut reader, &mut hasher)?;
+        let hash = hasher.finalize().to_vec();
+
+        self.hashes
+            .lock()
+            .unwrap()
+            .insert(normalized_path.to_string(), hash.clone());
+        Ok(Some(hash))
+    }
+}
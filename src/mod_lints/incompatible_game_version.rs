@@ -0,0 +1,60 @@
+use anyhow::{bail, Result};
+
+use crate::read_game_version;
+
+use super::{Diagnostic, Lint, LintCtxt, LintFactory, LintId, LintLevel};
+
+inventory::submit! {
+    LintFactory {
+        id: LintId::INCOMPATIBLE_GAME_VERSION,
+        description: "Mods not marked compatible with the game version",
+        default_level: LintLevel::Warn,
+        default_allowed_paths: &[],
+        requires_game_pak: true,
+        new: || Box::new(IncompatibleGameVersionLint),
+    }
+}
+
+#[derive(Default)]
+pub struct IncompatibleGameVersionLint;
+
+impl Lint for IncompatibleGameVersionLint {
+    fn finish(&mut self, lcx: &LintCtxt) -> Result<Vec<Diagnostic>> {
+        let Some(game_pak_path) = &lcx.fsd_pak_path else {
+            bail!("IncompatibleGameVersionLint requires specifying a valid game pak path");
+        };
+
+        let game_version = read_game_version(game_pak_path)?;
+
+        let mut diagnostics = vec![];
+
+        for lint_mod in lcx.mods() {
+            let Some(tags) = lcx
+                .mod_infos
+                .get(&lint_mod.spec)
+                .and_then(|info| info.modio_tags.as_ref())
+            else {
+                continue;
+            };
+
+            if !tags.supports_game_version(&game_version) {
+                diagnostics.push(
+                    Diagnostic::new(
+                        LintId::INCOMPATIBLE_GAME_VERSION,
+                        format!(
+                            "not marked compatible with game version {game_version}, supports {}",
+                            tags.versions
+                                .iter()
+                                .map(String::as_str)
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    )
+                    .with_mod(lint_mod.spec.clone()),
+                );
+            }
+        }
+
+        Ok(diagnostics)
+    }
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+
+use crate::integrate::integration_critical_effect;
+
+use super::{Diagnostic, Lint, LintCtxt, LintFactory, LintId, LintLevel, LintMod, ModFile, ModPak};
+
+inventory::submit! {
+    LintFactory {
+        id: LintId::INTEGRATION_CRITICAL_ASSETS,
+        description: "Mods containing assets the mod integration patches or provides",
+        default_level: LintLevel::Warn,
+        default_allowed_paths: &[],
+        requires_game_pak: false,
+        new: || Box::<IntegrationCriticalAssetsLint>::default(),
+    }
+}
+
+/// Reports mods shipping `BP_PlayerControllerBase`, the other assets patched when integrating, or
+/// files under `_AssemblyStorm/ModIntegration`.
+#[derive(Default)]
+pub struct IntegrationCriticalAssetsLint {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Lint for IntegrationCriticalAssetsLint {
+    fn visit_file(
+        &mut self,
+        _lcx: &LintCtxt,
+        lint_mod: &LintMod,
+        _pak: &ModPak,
+        file: &ModFile,
+    ) -> Result<()> {
+        if let Some(effect) = integration_critical_effect(&file.normalized_path) {
+            self.diagnostics.push(
+                Diagnostic::new(LintId::INTEGRATION_CRITICAL_ASSETS, effect)
+                    .with_mod(lint_mod.spec.clone())
+                    .with_path(&file.normalized_path),
+            );
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, _lcx: &LintCtxt) -> Result<Vec<Diagnostic>> {
+        Ok(std::mem::take(&mut self.diagnostics))
+    }
+}
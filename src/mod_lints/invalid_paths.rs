@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use indexmap::IndexSet;
+
+use crate::providers::ModSpecification;
+
+use super::{Diagnostic, Lint, LintCtxt, LintFactory, LintId, LintLevel, LintMod, ModFile, ModPak};
+
+inventory::submit! {
+    LintFactory {
+        id: LintId::INVALID_PATHS,
+        description: "Mods containing paths which differ only in case or cannot be integrated",
+        default_level: LintLevel::Warn,
+        default_allowed_paths: &[],
+        requires_game_pak: false,
+        new: || Box::<InvalidPathsLint>::default(),
+    }
+}
+
+/// Reports files mounted outside the game root, paths with backslashes or non-ASCII characters,
+/// and paths which differ only in case, within a mod or across mods.
+#[derive(Default)]
+pub struct InvalidPathsLint {
+    diagnostics: Vec<Diagnostic>,
+    /// Normalized path to the `/` separated paths with their original case, and the mods which
+    /// contain each.
+    per_path_variants: BTreeMap<String, BTreeMap<String, IndexSet<ModSpecification>>>,
+}
+
+impl Lint for InvalidPathsLint {
+    fn visit_mod(&mut self, _lcx: &LintCtxt, lint_mod: &LintMod) -> Result<()> {
+        for path in lint_mod.pak().into_iter().flat_map(|pak| &pak.unmounted) {
+            self.diagnostics.push(
+                Diagnostic::new(
+                    LintId::INVALID_PATHS,
+                    "not mounted under the game root (`../../../`), integrating the mod fails",
+                )
+                .with_mod(lint_mod.spec.clone())
+                .with_path(path),
+            );
+        }
+        Ok(())
+    }
+
+    fn visit_file(
+        &mut self,
+        _lcx: &LintCtxt,
+        lint_mod: &LintMod,
+        _pak: &ModPak,
+        file: &ModFile,
+    ) -> Result<()> {
+        let path = file.path.to_string_lossy();
+        if path.contains('\\') {
+            self.diagnostics.push(
+                Diagnostic::new(LintId::INVALID_PATHS, "path contains backslashes")
+                    .with_mod(lint_mod.spec.clone())
+                    .with_path(&file.normalized_path),
+            );
+        }
+        if !path.is_ascii() {
+            self.diagnostics.push(
+                Diagnostic::new(
+                    LintId::INVALID_PATHS,
+                    "path contains non-ASCII characters, which the game may fail to load",
+                )
+                .with_mod(lint_mod.spec.clone())
+                .with_path(&file.normalized_path),
+            );
+        }
+
+        self.per_path_variants
+            .entry(file.normalized_path.clone())
+            .or_default()
+            .entry(path.replace('\\', "/"))
+            .or_default()
+            .insert(lint_mod.spec.clone());
+        Ok(())
+    }
+
+    fn finish(&mut self, _lcx: &LintCtxt) -> Result<Vec<Diagnostic>> {
+        let mut diagnostics = std::mem::take(&mut self.diagnostics);
+
+        for (normalized_path, variants) in std::mem::take(&mut self.per_path_variants) {
+            if variants.len() < 2 {
+                continue;
+            }
+            let listed = variants
+                .keys()
+                .map(|v| format!("`{v}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let mods = variants.into_values().flatten().collect::<IndexSet<_>>();
+            let message = if mods.len() == 1 {
+                format!("contains paths which differ only in case, only the first is installed: {listed}")
+            } else {
+                format!("mods contain paths which differ only in case, only the first is installed: {listed}")
+            };
+            diagnostics.push(
+                Diagnostic::new(LintId::INVALID_PATHS, message)
+                    .with_mods(mods)
+                    .with_path(normalized_path),
+            );
+        }
+
+        Ok(diagnostics)
+    }
+}
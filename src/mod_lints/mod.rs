@@ -1,207 +1,574 @@
 mod archive_multiple_paks;
 mod archive_only_non_pak_files;
+mod asset_conflicts;
 mod asset_register_bin;
+mod broken_references;
+mod config;
 mod conflicting_mods;
 mod empty_archive;
+mod fix;
+mod game_pak;
+mod incompatible_game_version;
+mod integration_critical_assets;
+mod invalid_paths;
 mod non_asset_files;
 mod outdated_pak_version;
+mod report;
 mod shader_files;
 mod split_asset_pairs;
 mod unmodified_game_assets;
+mod unparsable_assets;
 
-use std::collections::{BTreeMap, BTreeSet};
-use std::io::BufReader;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{BufReader, Cursor, Read, Seek};
 use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use indexmap::IndexSet;
+use rayon::prelude::*;
 use repak::PakReader;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tokio_util::sync::CancellationToken;
 use tracing::trace;
 
-use crate::mod_lints::conflicting_mods::ConflictingModsLint;
-use crate::providers::{ModSpecification, ReadSeek};
-use crate::{lint_get_all_files_from_data, open_file, GetAllFilesFromDataError, PakOrNotPak};
-
-use self::archive_multiple_paks::ArchiveMultiplePaksLint;
-use self::archive_only_non_pak_files::ArchiveOnlyNonPakFilesLint;
-use self::asset_register_bin::AssetRegisterBinLint;
-use self::empty_archive::EmptyArchiveLint;
-use self::non_asset_files::NonAssetFilesLint;
-use self::outdated_pak_version::OutdatedPakVersionLint;
-use self::shader_files::ShaderFilesLint;
-pub use self::split_asset_pairs::SplitAssetPair;
-use self::split_asset_pairs::SplitAssetPairsLint;
-use self::unmodified_game_assets::UnmodifiedGameAssetsLint;
+use crate::error::Cancelled;
+use crate::providers::{ModInfo, ModSpecification, ReadSeek};
+use crate::{game_path, normalize_path, open_file, AssetData};
+
+pub use self::config::{LintConfig, LintConfigFile, LintLevel};
+pub use self::fix::{fix_mods, FixedMod};
+pub use self::game_pak::GamePak;
+pub use self::report::ReportFormat;
 
 pub struct LintCtxt {
-    pub(crate) mods: IndexSet<(ModSpecification, PathBuf)>,
+    pub(crate) mods: Vec<LintMod>,
     pub(crate) fsd_pak_path: Option<PathBuf>,
+    /// Resolved mod metadata, only available for mods which have been resolved by a provider.
+    pub(crate) mod_infos: HashMap<ModSpecification, ModInfo>,
+    pub(crate) config: LintConfig,
+    game_pak: OnceLock<Arc<GamePak>>,
+    cancel: CancellationToken,
 }
 
 impl LintCtxt {
+    /// Load and index every mod, in parallel.
     pub fn init(
         mods: IndexSet<(ModSpecification, PathBuf)>,
         fsd_pak_path: Option<PathBuf>,
+        mod_infos: HashMap<ModSpecification, ModInfo>,
+        config: LintConfig,
+        cancel: CancellationToken,
     ) -> Result<Self> {
         trace!("LintCtxt::init");
-        Ok(Self { mods, fsd_pak_path })
-    }
-
-    pub fn for_each_mod<F, EmptyArchiveHandler, OnlyNonPakFilesHandler, MultiplePakFilesHandler>(
-        &self,
-        mut f: F,
-        mut empty_archive_handler: Option<EmptyArchiveHandler>,
-        mut only_non_pak_files_handler: Option<OnlyNonPakFilesHandler>,
-        mut multiple_pak_files_handler: Option<MultiplePakFilesHandler>,
-    ) -> Result<()>
-    where
-        F: FnMut(ModSpecification, &mut Box<dyn ReadSeek>, &PakReader) -> Result<()>,
-        EmptyArchiveHandler: FnMut(ModSpecification),
-        OnlyNonPakFilesHandler: FnMut(ModSpecification),
-        MultiplePakFilesHandler: FnMut(ModSpecification),
-    {
-        for (mod_spec, mod_pak_path) in &self.mods {
-            let maybe_archive_reader = Box::new(BufReader::new(open_file(mod_pak_path)?));
-            let bufs = match lint_get_all_files_from_data(maybe_archive_reader) {
-                Ok(bufs) => bufs,
-                Err(e) => match e {
-                    GetAllFilesFromDataError::EmptyArchive => {
-                        if let Some(ref mut handler) = empty_archive_handler {
-                            handler(mod_spec.clone());
-                        }
-                        continue;
+        let mods = mods
+            .into_iter()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|(spec, path)| {
+                if cancel.is_cancelled() {
+                    return Err(Cancelled.into());
+                }
+                LintMod::load(spec.clone(), path)
+                    .with_context(|| format!("failed to load mod {}", spec.url))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self::new(mods, fsd_pak_path, mod_infos, config, cancel))
+    }
+
+    fn new(
+        mods: Vec<LintMod>,
+        fsd_pak_path: Option<PathBuf>,
+        mod_infos: HashMap<ModSpecification, ModInfo>,
+        config: LintConfig,
+        cancel: CancellationToken,
+    ) -> Self {
+        Self {
+            mods,
+            fsd_pak_path,
+            mod_infos,
+            config,
+            game_pak: OnceLock::new(),
+            cancel,
+        }
+    }
+
+    pub fn mods(&self) -> &[LintMod] {
+        &self.mods
+    }
+
+    pub fn get_mod(&self, mod_spec: &ModSpecification) -> Option<&LintMod> {
+        self.mods.iter().find(|m| &m.spec == mod_spec)
+    }
+
+    /// Whether `lint` should ignore the normalized `path` according to the config's allowlist.
+    pub fn is_path_allowed(&self, lint: LintId, path: &str) -> bool {
+        self.config.is_path_allowed(lint, path)
+    }
+
+    /// The game pak index, loaded once per context on first use. `GamePak::load` reuses the
+    /// index of the last loaded game pak while the file is unchanged, which is what later lint runs
+    /// share.
+    pub fn game_pak(&self) -> Result<&GamePak> {
+        if let Some(game_pak) = self.game_pak.get() {
+            return Ok(game_pak);
+        }
+        let Some(path) = &self.fsd_pak_path else {
+            bail!("lint requires specifying a valid game pak path");
+        };
+        let game_pak = GamePak::load(path)?;
+        Ok(self.game_pak.get_or_init(|| game_pak))
+    }
+
+    /// Fail with [`Cancelled`] once the token the context was created with has been cancelled.
+    pub fn check_cancelled(&self) -> Result<()> {
+        if self.cancel.is_cancelled() {
+            return Err(Cancelled.into());
+        }
+        Ok(())
+    }
+
+    /// Run every registered lint not at [`LintLevel::Allow`] in the context's config. The loaded
+    /// mods can then be repacked with [`fix_mods`] without reading them again.
+    pub fn lint(&self) -> Result<LintReport> {
+        let (lints, lint_report) = instantiate(&self.config);
+        run(lints, lint_report, self)
+    }
+
+    /// Visit every mod and file once, dispatching to all `lints`.
+    fn visit(&self, lints: &mut [(LintId, Box<dyn Lint>)]) -> Result<()> {
+        for lint_mod in &self.mods {
+            self.check_cancelled()?;
+            for (_, lint) in lints.iter_mut() {
+                lint.visit_mod(self, lint_mod)?;
+            }
+            let Some(pak) = lint_mod.pak() else {
+                continue;
+            };
+            for file in &pak.files {
+                self.check_cancelled()?;
+                for (id, lint) in lints.iter_mut() {
+                    if !self.is_path_allowed(*id, &file.normalized_path) {
+                        lint.visit_file(self, lint_mod, pak, file)?;
                     }
-                    GetAllFilesFromDataError::OnlyNonPakFiles => {
-                        if let Some(ref mut handler) = only_non_pak_files_handler {
-                            handler(mod_spec.clone());
-                        }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A mod as loaded by [`LintCtxt::init`].
+pub struct LintMod {
+    pub spec: ModSpecification,
+    /// Path of the mod's file, or its URL for mods linted from memory.
+    pub path: PathBuf,
+    pub contents: ModContents,
+}
+
+pub enum ModContents {
+    EmptyArchive,
+    OnlyNonPakFiles,
+    Pak {
+        pak: ModPak,
+        /// The mod is an archive with more than one pak, only the first of which is used.
+        multiple_paks: bool,
+    },
+}
+
+impl LintMod {
+    fn load(spec: ModSpecification, path: PathBuf) -> Result<Self> {
+        let reader = BufReader::new(open_file(&path)?);
+        let contents = ModContents::load(reader, PakSource::File(path.clone()))?;
+        Ok(Self {
+            spec,
+            path,
+            contents,
+        })
+    }
+
+    fn load_from_memory(spec: ModSpecification, data: Arc<[u8]>) -> Result<Self> {
+        let contents = ModContents::load(Cursor::new(data.clone()), PakSource::Memory(data))?;
+        Ok(Self {
+            path: PathBuf::from(&spec.url),
+            spec,
+            contents,
+        })
+    }
+
+    pub fn pak(&self) -> Option<&ModPak> {
+        match &self.contents {
+            ModContents::Pak { pak, .. } => Some(pak),
+            _ => None,
+        }
+    }
+}
+
+impl ModContents {
+    /// Read a zip archive or plain pak. `source` is where the pak is read from if `reader` is not
+    /// an archive.
+    fn load(mut reader: impl Read + Seek, source: PakSource) -> Result<Self> {
+        Ok(if let Ok(mut archive) = zip::ZipArchive::new(&mut reader) {
+            if archive.is_empty() {
+                ModContents::EmptyArchive
+            } else {
+                let mut first_pak = None;
+                let mut multiple_paks = false;
+                for i in 0..archive.len() {
+                    let mut file = archive.by_index(i)?;
+                    let is_pak = file.is_file()
+                        && file
+                            .enclosed_name()
+                            .is_some_and(|p| p.extension().is_some_and(|e| e == "pak"));
+                    if !is_pak {
                         continue;
                     }
-                    GetAllFilesFromDataError::Other(e) => return Err(e),
-                },
-            };
-
-            let mut individual_pak_readers = bufs
-                .into_iter()
-                .filter_map(|(_, pak_or_non_pak)| match pak_or_non_pak {
-                    PakOrNotPak::Pak(individual_pak_reader) => Some(individual_pak_reader),
-                    PakOrNotPak::NotPak(_) => None,
-                })
-                .collect::<Vec<_>>();
-
-            if individual_pak_readers.len() > 1 {
-                if let Some(ref mut handler) = multiple_pak_files_handler {
-                    handler(mod_spec.clone());
+                    if first_pak.is_some() {
+                        multiple_paks = true;
+                        break;
+                    }
+                    let mut buf = vec![];
+                    file.read_to_end(&mut buf)?;
+                    first_pak = Some(buf);
+                }
+                match first_pak {
+                    Some(buf) => ModContents::Pak {
+                        pak: ModPak::new(PakSource::Memory(buf.into()))?,
+                        multiple_paks,
+                    },
+                    None => ModContents::OnlyNonPakFiles,
                 }
             }
+        } else {
+            ModContents::Pak {
+                pak: ModPak::new(source)?,
+                multiple_paks: false,
+            }
+        })
+    }
+}
 
-            let mut first_pak_read_seek = individual_pak_readers.remove(0);
-            let pak_reader = repak::PakReader::new_any(&mut first_pak_read_seek)?;
-            f(mod_spec.clone(), &mut first_pak_read_seek, &pak_reader)?
+enum PakSource {
+    File(PathBuf),
+    /// Pak extracted from a zip archive.
+    Memory(Arc<[u8]>),
+}
+
+impl PakSource {
+    fn open(&self) -> Result<Box<dyn ReadSeek>> {
+        Ok(match self {
+            PakSource::File(path) => Box::new(BufReader::new(open_file(path)?)),
+            PakSource::Memory(buf) => Box::new(Cursor::new(buf.clone())),
+        })
+    }
+}
+
+/// A mod's pak with its parsed index.
+pub struct ModPak {
+    source: PakSource,
+    pub reader: PakReader,
+    /// Files in index order.
+    pub files: Vec<ModFile>,
+    /// Files which are not mounted under the game root, as their mount point and name joined.
+    /// They are left out of `files`.
+    pub unmounted: Vec<String>,
+}
+
+pub struct ModFile {
+    /// Name of the file in the pak index.
+    pub entry: String,
+    /// Path relative to the game root, e.g. `FSD/Content/...`.
+    pub path: PathBuf,
+    /// `path` lowercased and `/` separated.
+    pub normalized_path: String,
+}
+
+impl ModPak {
+    fn new(source: PakSource) -> Result<Self> {
+        let reader = PakReader::new_any(&mut source.open()?)?;
+        let mount = PathBuf::from(reader.mount_point());
+        let mut files = vec![];
+        let mut unmounted = vec![];
+        for entry in reader.files() {
+            let Some(path) = game_path(&mount, &entry) else {
+                unmounted.push(mount.join(&entry).to_string_lossy().to_string());
+                continue;
+            };
+            let normalized_path = normalize_path(&path);
+            files.push(ModFile {
+                entry,
+                path,
+                normalized_path,
+            });
         }
+        Ok(Self {
+            source,
+            reader,
+            files,
+            unmounted,
+        })
+    }
 
-        Ok(())
+    pub fn version(&self) -> repak::Version {
+        self.reader.version()
     }
 
-    pub fn for_each_mod_file<F>(&self, mut f: F) -> Result<()>
-    where
-        F: FnMut(
-            ModSpecification,
-            &mut Box<dyn ReadSeek>,
-            &PakReader,
-            PathBuf,
-            String,
-        ) -> Result<()>,
-    {
-        self.for_each_mod(
-            |mod_spec, pak_read_seek, pak_reader| {
-                let mount = PathBuf::from(pak_reader.mount_point());
-                for p in pak_reader.files() {
-                    let path = mount.join(&p);
-                    let path_buf = path
-                        .strip_prefix("../../../")
-                        .context("prefix does not match")?;
-                    let normalized_path = &path_buf.to_string_lossy().replace('\\', "/");
-                    let normalized_path = normalized_path.to_ascii_lowercase();
-                    f(
-                        mod_spec.clone(),
-                        pak_read_seek,
-                        pak_reader,
-                        path_buf.to_path_buf(),
-                        normalized_path,
-                    )?
-                }
+    /// Open a new reader over the pak's data, e.g. to read files from multiple threads.
+    pub fn open(&self) -> Result<Box<dyn ReadSeek>> {
+        self.source.open()
+    }
+
+    pub fn read(&self, file: &ModFile) -> Result<Vec<u8>> {
+        Ok(self.reader.get(&file.entry, &mut self.open()?)?)
+    }
+
+    /// Find a file by normalized path.
+    pub fn file(&self, normalized_path: &str) -> Option<&ModFile> {
+        self.files
+            .iter()
+            .find(|f| f.normalized_path == normalized_path)
+    }
 
-                Ok(())
-            },
-            None::<fn(ModSpecification)>,
-            None::<fn(ModSpecification)>,
-            None::<fn(ModSpecification)>,
-        )
+    /// Read the `.uasset` or `.umap` at `normalized_path` along with its `.uexp`. Returns `None`
+    /// if either is missing; such incomplete pairs are reported by
+    /// [`LintId::SPLIT_ASSET_PAIRS`], so other lints skip them.
+    pub(crate) fn read_asset(&self, normalized_path: &str) -> Result<Option<AssetData>> {
+        let (Some(uasset), Some(uexp)) = (
+            self.file(normalized_path),
+            self.file(&uexp_path(normalized_path)),
+        ) else {
+            return Ok(None);
+        };
+        Ok(Some((self.read(uasset)?, self.read(uexp)?)))
     }
 }
 
+/// Path of the `.uexp` belonging to the `.uasset` or `.umap` at `path`.
+pub(crate) fn uexp_path(path: &str) -> String {
+    let stem = path.rsplit_once('.').map_or(path, |(stem, _)| stem);
+    format!("{stem}.uexp")
+}
+
+/// A lint visits every mod and file once through [`LintCtxt`], then reports what it found.
 pub trait Lint {
-    type Output;
+    /// Called for each mod in order, before its files are visited.
+    fn visit_mod(&mut self, _lcx: &LintCtxt, _lint_mod: &LintMod) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called for each file in a mod's pak which is not on the lint's path allowlist.
+    fn visit_file(
+        &mut self,
+        _lcx: &LintCtxt,
+        _lint_mod: &LintMod,
+        _pak: &ModPak,
+        _file: &ModFile,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called once every mod has been visited.
+    fn finish(&mut self, lcx: &LintCtxt) -> Result<Vec<Diagnostic>>;
+}
 
-    fn check_mods(&mut self, lcx: &LintCtxt) -> Result<Self::Output>;
+/// Registers a lint. Submit one with `inventory::submit!` to make a lint available to
+/// [`run_lints_with_config`], the lint config, the GUI and the CLI.
+pub struct LintFactory {
+    pub id: LintId,
+    /// Short human readable summary of what the lint looks for.
+    pub description: &'static str,
+    /// Level used when the lint config does not specify one. Slow lints should default to
+    /// [`LintLevel::Allow`].
+    pub default_level: LintLevel,
+    /// Path prefixes ignored when the lint config does not specify an allowlist.
+    pub default_allowed_paths: &'static [&'static str],
+    /// Whether the lint needs the game pak path to run.
+    pub requires_game_pak: bool,
+    pub new: fn() -> Box<dyn Lint>,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+impl std::fmt::Debug for LintFactory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LintFactory")
+            .field("id", &self.id)
+            .field("default_level", &self.default_level)
+            .finish()
+    }
+}
+
+inventory::collect!(LintFactory);
+
+/// All registered lints, ordered by id.
+pub fn lint_factories() -> Vec<&'static LintFactory> {
+    let mut factories = inventory::iter::<LintFactory>().collect::<Vec<_>>();
+    factories.sort_by_key(|f| f.id);
+    factories
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct LintId {
     name: &'static str,
 }
 
 impl LintId {
+    pub const fn new(name: &'static str) -> Self {
+        Self { name }
+    }
+
     pub fn to_name_lower(&self) -> String {
         self.name.to_ascii_lowercase()
     }
 
-    pub const CONFLICTING: Self = LintId {
-        name: "conflicting",
-    };
-    pub const ASSET_REGISTRY_BIN: Self = LintId {
-        name: "asset_registry_bin",
-    };
-    pub const SHADER_FILES: Self = LintId {
-        name: "shader_files",
-    };
-    pub const OUTDATED_PAK_VERSION: Self = LintId {
-        name: "outdated_pak_version",
-    };
-    pub const EMPTY_ARCHIVE: Self = LintId {
-        name: "empty_archive",
-    };
-    pub const ARCHIVE_WITH_ONLY_NON_PAK_FILES: Self = LintId {
-        name: "archive_only_non_pak_files",
-    };
-    pub const ARCHIVE_WITH_MULTIPLE_PAKS: Self = LintId {
-        name: "archive_with_multiple_paks",
-    };
-    pub const NON_ASSET_FILES: Self = LintId {
-        name: "non_asset_files",
-    };
-    pub const SPLIT_ASSET_PAIRS: Self = LintId {
-        name: "split_asset_pairs",
-    };
-    pub const UNMODIFIED_GAME_ASSETS: Self = LintId {
-        name: "unmodified_game_assets",
-    };
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Look up a registered lint by name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        inventory::iter::<LintFactory>()
+            .find(|f| f.id.name == name)
+            .map(|f| f.id)
+    }
+
+    pub fn factory(&self) -> Option<&'static LintFactory> {
+        inventory::iter::<LintFactory>().find(|f| f.id == *self)
+    }
+
+    pub const CONFLICTING: Self = Self::new("conflicting");
+    pub const ASSET_REGISTRY_BIN: Self = Self::new("asset_registry_bin");
+    pub const SHADER_FILES: Self = Self::new("shader_files");
+    pub const OUTDATED_PAK_VERSION: Self = Self::new("outdated_pak_version");
+    pub const EMPTY_ARCHIVE: Self = Self::new("empty_archive");
+    pub const ARCHIVE_WITH_ONLY_NON_PAK_FILES: Self = Self::new("archive_only_non_pak_files");
+    pub const ARCHIVE_WITH_MULTIPLE_PAKS: Self = Self::new("archive_with_multiple_paks");
+    pub const NON_ASSET_FILES: Self = Self::new("non_asset_files");
+    pub const SPLIT_ASSET_PAIRS: Self = Self::new("split_asset_pairs");
+    pub const UNMODIFIED_GAME_ASSETS: Self = Self::new("unmodified_game_assets");
+    pub const INCOMPATIBLE_GAME_VERSION: Self = Self::new("incompatible_game_version");
+    pub const ASSET_CONFLICTS: Self = Self::new("asset_conflicts");
+    pub const BROKEN_REFERENCES: Self = Self::new("broken_references");
+    pub const UNPARSABLE_ASSETS: Self = Self::new("unparsable_assets");
+    pub const INTEGRATION_CRITICAL_ASSETS: Self = Self::new("integration_critical_assets");
+    pub const INVALID_PATHS: Self = Self::new("invalid_paths");
+}
+
+impl std::fmt::Display for LintId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+impl Serialize for LintId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name)
+    }
+}
+
+impl<'de> Deserialize<'de> for LintId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        LintId::from_name(&name)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown lint `{name}`")))
+    }
+}
+
+/// A way to resolve a [`Diagnostic`] by modifying the offending mod.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Fix {
+    /// Remove the file at the diagnostic's path from the mod.
+    RemoveFile,
+    /// Repack the mod as a current version pak.
+    Repack,
+}
+
+/// A single problem found by a lint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub lint: LintId,
+    /// Filled in from the lint config when the report is assembled.
+    pub level: LintLevel,
+    /// Mods involved, usually one. Serialized as their URLs.
+    #[serde(serialize_with = "serialize_mod_urls")]
+    pub mods: Vec<ModSpecification>,
+    /// Normalized path of the offending file, if the problem is specific to one.
+    pub path: Option<String>,
+    pub message: String,
+    pub fix: Option<Fix>,
 }
 
-#[derive(Default, Debug)]
+impl Diagnostic {
+    pub fn new(lint: LintId, message: impl Into<String>) -> Self {
+        Self {
+            lint,
+            level: LintLevel::Warn,
+            mods: vec![],
+            path: None,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    pub fn with_mod(mut self, mod_spec: ModSpecification) -> Self {
+        self.mods.push(mod_spec);
+        self
+    }
+
+    pub fn with_mods(mut self, mods: impl IntoIterator<Item = ModSpecification>) -> Self {
+        self.mods.extend(mods);
+        self
+    }
+
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+}
+
+fn serialize_mod_urls<S: Serializer>(
+    mods: &[ModSpecification],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(mods.iter().map(|m| &m.url))
+}
+
+/// Diagnostics of every lint that was run.
+#[derive(Default, Debug, Serialize)]
 pub struct LintReport {
-    pub conflicting_mods: Option<BTreeMap<String, IndexSet<ModSpecification>>>,
-    pub asset_register_bin_mods: Option<BTreeMap<ModSpecification, BTreeSet<String>>>,
-    pub shader_file_mods: Option<BTreeMap<ModSpecification, BTreeSet<String>>>,
-    pub outdated_pak_version_mods: Option<BTreeMap<ModSpecification, repak::Version>>,
-    pub empty_archive_mods: Option<BTreeSet<ModSpecification>>,
-    pub archive_with_only_non_pak_files_mods: Option<BTreeSet<ModSpecification>>,
-    pub archive_with_multiple_paks_mods: Option<BTreeSet<ModSpecification>>,
-    pub non_asset_file_mods: Option<BTreeMap<ModSpecification, BTreeSet<String>>>,
-    pub split_asset_pairs_mods:
-        Option<BTreeMap<ModSpecification, BTreeMap<String, SplitAssetPair>>>,
-    pub unmodified_game_assets_mods: Option<BTreeMap<ModSpecification, BTreeSet<String>>>,
+    /// Level of each lint that was run.
+    pub levels: BTreeMap<LintId, LintLevel>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl LintReport {
+    /// Whether no lint found anything.
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// Diagnostics reported by `lint`.
+    pub fn for_lint(&self, lint: LintId) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(move |d| d.lint == lint)
+    }
+
+    /// Lints which found something.
+    pub fn fired(&self) -> BTreeSet<LintId> {
+        self.diagnostics.iter().map(|d| d.lint).collect()
+    }
+
+    /// Lints at [`LintLevel::Deny`] which found something.
+    pub fn denied(&self) -> BTreeSet<LintId> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.level == LintLevel::Deny)
+            .map(|d| d.lint)
+            .collect()
+    }
 }
 
 pub fn run_lints(
@@ -209,54 +576,151 @@ pub fn run_lints(
     mods: IndexSet<(ModSpecification, PathBuf)>,
     fsd_pak_path: Option<PathBuf>,
 ) -> Result<LintReport> {
-    let lint_ctxt = LintCtxt::init(mods, fsd_pak_path)?;
-    let mut lint_report = LintReport::default();
+    run_lints_with_mod_infos(enabled_lints, mods, fsd_pak_path, HashMap::new())
+}
 
-    for lint_id in enabled_lints {
-        match *lint_id {
-            LintId::CONFLICTING => {
-                let res = ConflictingModsLint.check_mods(&lint_ctxt)?;
-                lint_report.conflicting_mods = Some(res);
-            }
-            LintId::ASSET_REGISTRY_BIN => {
-                let res = AssetRegisterBinLint.check_mods(&lint_ctxt)?;
-                lint_report.asset_register_bin_mods = Some(res);
-            }
-            LintId::SHADER_FILES => {
-                let res = ShaderFilesLint.check_mods(&lint_ctxt)?;
-                lint_report.shader_file_mods = Some(res);
-            }
-            LintId::OUTDATED_PAK_VERSION => {
-                let res = OutdatedPakVersionLint.check_mods(&lint_ctxt)?;
-                lint_report.outdated_pak_version_mods = Some(res);
-            }
-            LintId::EMPTY_ARCHIVE => {
-                let res = EmptyArchiveLint.check_mods(&lint_ctxt)?;
-                lint_report.empty_archive_mods = Some(res);
-            }
-            LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES => {
-                let res = ArchiveOnlyNonPakFilesLint.check_mods(&lint_ctxt)?;
-                lint_report.archive_with_only_non_pak_files_mods = Some(res);
-            }
-            LintId::ARCHIVE_WITH_MULTIPLE_PAKS => {
-                let res = ArchiveMultiplePaksLint.check_mods(&lint_ctxt)?;
-                lint_report.archive_with_multiple_paks_mods = Some(res);
-            }
-            LintId::NON_ASSET_FILES => {
-                let res = NonAssetFilesLint.check_mods(&lint_ctxt)?;
-                lint_report.non_asset_file_mods = Some(res);
-            }
-            LintId::SPLIT_ASSET_PAIRS => {
-                let res = SplitAssetPairsLint.check_mods(&lint_ctxt)?;
-                lint_report.split_asset_pairs_mods = Some(res);
-            }
-            LintId::UNMODIFIED_GAME_ASSETS => {
-                let res = UnmodifiedGameAssetsLint.check_mods(&lint_ctxt)?;
-                lint_report.unmodified_game_assets_mods = Some(res);
-            }
-            _ => unimplemented!(),
+/// Same as [`run_lints`] but also makes resolved mod metadata available to lints which need it,
+/// such as [`LintId::INCOMPATIBLE_GAME_VERSION`].
+pub fn run_lints_with_mod_infos(
+    enabled_lints: &BTreeSet<LintId>,
+    mods: IndexSet<(ModSpecification, PathBuf)>,
+    fsd_pak_path: Option<PathBuf>,
+    mod_infos: HashMap<ModSpecification, ModInfo>,
+) -> Result<LintReport> {
+    run_lints_with_config(
+        &LintConfig::with_enabled(enabled_lints),
+        mods,
+        fsd_pak_path,
+        mod_infos,
+        &CancellationToken::new(),
+    )
+}
+
+/// Run every registered lint not at [`LintLevel::Allow`] in `config`, honoring its path
+/// allowlists. Returns [`Cancelled`] if `cancel` is cancelled before all mods have been checked.
+pub fn run_lints_with_config(
+    config: &LintConfig,
+    mods: IndexSet<(ModSpecification, PathBuf)>,
+    fsd_pak_path: Option<PathBuf>,
+    mod_infos: HashMap<ModSpecification, ModInfo>,
+    cancel: &CancellationToken,
+) -> Result<LintReport> {
+    let (lints, lint_report) = instantiate(config);
+    if lints.is_empty() {
+        return Ok(lint_report);
+    }
+
+    let lint_ctxt = LintCtxt::init(
+        mods,
+        fsd_pak_path,
+        mod_infos,
+        config.clone(),
+        cancel.clone(),
+    )?;
+    run(lints, lint_report, &lint_ctxt)
+}
+
+/// Lint a single mod, a zip archive or pak, read from `reader` rather than the mod store, e.g. to
+/// check a mod before it is published.
+pub fn run_lints_on_reader(
+    config: &LintConfig,
+    mod_spec: ModSpecification,
+    mut reader: impl Read,
+    fsd_pak_path: Option<PathBuf>,
+) -> Result<LintReport> {
+    let (lints, lint_report) = instantiate(config);
+    if lints.is_empty() {
+        return Ok(lint_report);
+    }
+
+    let mut data = vec![];
+    reader
+        .read_to_end(&mut data)
+        .with_context(|| format!("failed to read mod {}", mod_spec.url))?;
+    let lint_mod = LintMod::load_from_memory(mod_spec.clone(), data.into())
+        .with_context(|| format!("failed to load mod {}", mod_spec.url))?;
+    let lint_ctxt = LintCtxt::new(
+        vec![lint_mod],
+        fsd_pak_path,
+        HashMap::new(),
+        config.clone(),
+        CancellationToken::new(),
+    );
+    run(lints, lint_report, &lint_ctxt)
+}
+
+/// Instantiate the lints which are not at [`LintLevel::Allow`] in `config`, along with an empty
+/// report recording their levels.
+fn instantiate(config: &LintConfig) -> (Vec<(LintId, Box<dyn Lint>)>, LintReport) {
+    let mut lint_report = LintReport::default();
+    let mut lints = vec![];
+    for factory in lint_factories() {
+        let level = config.level(factory.id);
+        if level != LintLevel::Allow {
+            lint_report.levels.insert(factory.id, level);
+            lints.push((factory.id, (factory.new)()));
         }
     }
+    (lints, lint_report)
+}
+
+fn run(
+    mut lints: Vec<(LintId, Box<dyn Lint>)>,
+    mut lint_report: LintReport,
+    lint_ctxt: &LintCtxt,
+) -> Result<LintReport> {
+    lint_ctxt.visit(&mut lints)?;
+
+    for (id, mut lint) in lints {
+        lint_ctxt.check_cancelled()?;
+        trace!("finishing lint {id}");
+        let level = lint_report.levels[&id];
+        let diagnostics = lint.finish(lint_ctxt)?;
+        lint_report
+            .diagnostics
+            .extend(diagnostics.into_iter().map(|d| Diagnostic { level, ..d }));
+    }
 
     Ok(lint_report)
 }
+
+/// Run only the lints at [`LintLevel::Deny`] in `config` and fail if any of them found something.
+pub fn check_denied_lints(
+    config: &LintConfig,
+    mods: IndexSet<(ModSpecification, PathBuf)>,
+    fsd_pak_path: Option<PathBuf>,
+    mod_infos: HashMap<ModSpecification, ModInfo>,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    let denied = config.denied_lints();
+    if denied.is_empty() {
+        return Ok(());
+    }
+    let config = LintConfig {
+        levels: lint_factories()
+            .into_iter()
+            .map(|f| {
+                let level = if denied.contains(&f.id) {
+                    LintLevel::Deny
+                } else {
+                    LintLevel::Allow
+                };
+                (f.id, level)
+            })
+            .collect(),
+        allowed_paths: config.allowed_paths.clone(),
+    };
+    let report = run_lints_with_config(&config, mods, fsd_pak_path, mod_infos, cancel)?;
+    let denied = report.denied();
+    if !denied.is_empty() {
+        bail!(
+            "refusing to install, deny-level lints found problems: {}",
+            denied
+                .iter()
+                .map(LintId::name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    Ok(())
+}
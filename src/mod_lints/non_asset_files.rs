@@ -1,13 +1,24 @@
-use std::collections::{BTreeMap, BTreeSet};
-
 use anyhow::Result;
 
-use crate::providers::ModSpecification;
-
-use super::{Lint, LintCtxt};
+use super::{
+    Diagnostic, Fix, Lint, LintCtxt, LintFactory, LintId, LintLevel, LintMod, ModFile, ModPak,
+};
+
+inventory::submit! {
+    LintFactory {
+        id: LintId::NON_ASSET_FILES,
+        description: "Mods containing non-asset files",
+        default_level: LintLevel::Warn,
+        default_allowed_paths: &[],
+        requires_game_pak: false,
+        new: || Box::<NonAssetFilesLint>::default(),
+    }
+}
 
 #[derive(Default)]
-pub struct NonAssetFilesLint;
+pub struct NonAssetFilesLint {
+    diagnostics: Vec<Diagnostic>,
+}
 
 const ENDS_WITH_WHITE_LIST: [&str; 7] = [
     ".uexp",
@@ -20,26 +31,28 @@ const ENDS_WITH_WHITE_LIST: [&str; 7] = [
 ];
 
 impl Lint for NonAssetFilesLint {
-    type Output = BTreeMap<ModSpecification, BTreeSet<String>>;
-
-    fn check_mods(&mut self, lcx: &LintCtxt) -> Result<Self::Output> {
-        let mut non_asset_files = BTreeMap::new();
-
-        lcx.for_each_mod_file(|mod_spec, _, _, _, normalized_path| {
-            let is_unreal_asset = ENDS_WITH_WHITE_LIST
-                .iter()
-                .any(|end| normalized_path.ends_with(end));
-            if !is_unreal_asset {
-                non_asset_files
-                    .entry(mod_spec)
-                    .and_modify(|files: &mut BTreeSet<String>| {
-                        files.insert(normalized_path.clone());
-                    })
-                    .or_insert_with(|| [normalized_path].into());
-            }
-            Ok(())
-        })?;
+    fn visit_file(
+        &mut self,
+        _lcx: &LintCtxt,
+        lint_mod: &LintMod,
+        _pak: &ModPak,
+        file: &ModFile,
+    ) -> Result<()> {
+        let is_unreal_asset = ENDS_WITH_WHITE_LIST
+            .iter()
+            .any(|end| file.normalized_path.ends_with(end));
+        if !is_unreal_asset {
+            self.diagnostics.push(
+                Diagnostic::new(LintId::NON_ASSET_FILES, "includes a non-asset file")
+                    .with_mod(lint_mod.spec.clone())
+                    .with_path(&file.normalized_path)
+                    .with_fix(Fix::RemoveFile),
+            );
+        }
+        Ok(())
+    }
 
-        Ok(non_asset_files)
+    fn finish(&mut self, _lcx: &LintCtxt) -> Result<Vec<Diagnostic>> {
+        Ok(std::mem::take(&mut self.diagnostics))
     }
 }
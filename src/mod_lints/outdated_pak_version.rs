@@ -1,32 +1,45 @@
-use std::collections::BTreeMap;
-
-use crate::providers::ModSpecification;
-
-use super::{Lint, LintCtxt};
+use super::{Diagnostic, Fix, Lint, LintCtxt, LintFactory, LintId, LintLevel, LintMod};
 
 use anyhow::Result;
 
+inventory::submit! {
+    LintFactory {
+        id: LintId::OUTDATED_PAK_VERSION,
+        description: "Mods containing outdated pak version",
+        default_level: LintLevel::Warn,
+        default_allowed_paths: &[],
+        requires_game_pak: false,
+        new: || Box::<OutdatedPakVersionLint>::default(),
+    }
+}
+
 #[derive(Default)]
-pub struct OutdatedPakVersionLint;
+pub struct OutdatedPakVersionLint {
+    diagnostics: Vec<Diagnostic>,
+}
 
 impl Lint for OutdatedPakVersionLint {
-    type Output = BTreeMap<ModSpecification, repak::Version>;
-
-    fn check_mods(&mut self, lcx: &LintCtxt) -> Result<Self::Output> {
-        let mut outdated_pak_version_mods = BTreeMap::new();
-
-        lcx.for_each_mod(
-            |mod_spec, _, pak_reader| {
-                if pak_reader.version() < repak::Version::V11 {
-                    outdated_pak_version_mods.insert(mod_spec.clone(), pak_reader.version());
-                }
-                Ok(())
-            },
-            None::<fn(ModSpecification)>,
-            None::<fn(ModSpecification)>,
-            None::<fn(ModSpecification)>,
-        )?;
+    fn visit_mod(&mut self, _lcx: &LintCtxt, lint_mod: &LintMod) -> Result<()> {
+        if let Some(pak) = lint_mod.pak()
+            && pak.version() < repak::Version::V11
+        {
+            self.diagnostics.push(
+                Diagnostic::new(
+                    LintId::OUTDATED_PAK_VERSION,
+                    format!(
+                        "pak version {:?} is older than {:?}",
+                        pak.version(),
+                        repak::Version::V11
+                    ),
+                )
+                .with_mod(lint_mod.spec.clone())
+                .with_fix(Fix::Repack),
+            );
+        }
+        Ok(())
+    }
 
-        Ok(outdated_pak_version_mods)
+    fn finish(&mut self, _lcx: &LintCtxt) -> Result<Vec<Diagnostic>> {
+        Ok(std::mem::take(&mut self.diagnostics))
     }
 }
@@ -0,0 +1,288 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use super::{Diagnostic, LintId, LintLevel, LintReport};
+
+/// Heading of diagnostics which are not about any particular mod.
+const NO_MOD: &str = "(no mod)";
+
+/// Human readable formats a [`LintReport`] can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    /// A single HTML file with inline styles.
+    Html,
+}
+
+impl ReportFormat {
+    /// HTML for paths ending in `.html` or `.htm`, Markdown otherwise.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("html") || e.eq_ignore_ascii_case("htm") => {
+                Self::Html
+            }
+            _ => Self::Markdown,
+        }
+    }
+}
+
+impl LintReport {
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+
+    /// Render as Markdown, with a summary table followed by the diagnostics grouped by lint and
+    /// by mod.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let by_lint = self.by_lint();
+        let by_mod = self.by_mod();
+
+        writeln!(out, "# Lint report\n").unwrap();
+        writeln!(out, "{}\n", self.summary(&by_mod)).unwrap();
+        if self.is_empty() {
+            return out;
+        }
+
+        writeln!(out, "| Lint | Level | Problems |").unwrap();
+        writeln!(out, "| --- | --- | ---: |").unwrap();
+        for (lint, diagnostics) in &by_lint {
+            writeln!(
+                out,
+                "| `{lint}` | {} | {} |",
+                level_name(self.level(*lint)),
+                diagnostics.len()
+            )
+            .unwrap();
+        }
+
+        writeln!(out, "\n## By lint").unwrap();
+        for (lint, diagnostics) in &by_lint {
+            writeln!(
+                out,
+                "\n### {} (`{lint}`, {}) — {}\n",
+                description(*lint),
+                level_name(self.level(*lint)),
+                diagnostics.len()
+            )
+            .unwrap();
+            for diagnostic in diagnostics {
+                let mods = diagnostic
+                    .mods
+                    .iter()
+                    .map(|m| format!("`{}`", m.url))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(out, "- ").unwrap();
+                if !mods.is_empty() {
+                    write!(out, "{mods}: ").unwrap();
+                }
+                writeln!(out, "{}", markdown_line(diagnostic)).unwrap();
+            }
+        }
+
+        writeln!(out, "\n## By mod").unwrap();
+        for (url, diagnostics) in &by_mod {
+            writeln!(out, "\n### `{url}` — {}\n", diagnostics.len()).unwrap();
+            for diagnostic in diagnostics {
+                writeln!(
+                    out,
+                    "- `{}`: {}",
+                    diagnostic.lint,
+                    markdown_line(diagnostic)
+                )
+                .unwrap();
+            }
+        }
+
+        out
+    }
+
+    /// Render as a self-contained HTML page with the same sections as [`Self::to_markdown`].
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        let by_lint = self.by_lint();
+        let by_mod = self.by_mod();
+
+        out.push_str(concat!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n",
+            "<title>Lint report</title>\n<style>\n",
+            "body { font-family: sans-serif; margin: 2em; }\n",
+            "table { border-collapse: collapse; }\n",
+            "th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }\n",
+            "code { background: #f0f0f0; padding: 0 0.2em; }\n",
+            ".deny { color: #c00; }\n.warn { color: #b70; }\n",
+            "</style>\n</head>\n<body>\n<h1>Lint report</h1>\n",
+        ));
+        writeln!(out, "<p>{}</p>", escape(&self.summary(&by_mod))).unwrap();
+
+        if !self.is_empty() {
+            out.push_str("<table>\n<tr><th>Lint</th><th>Level</th><th>Problems</th></tr>\n");
+            for (lint, diagnostics) in &by_lint {
+                let level = level_name(self.level(*lint));
+                writeln!(
+                    out,
+                    "<tr><td><code>{lint}</code></td><td class=\"{level}\">{level}</td><td>{}</td></tr>",
+                    diagnostics.len()
+                )
+                .unwrap();
+            }
+            out.push_str("</table>\n");
+
+            out.push_str("<h2>By lint</h2>\n");
+            for (lint, diagnostics) in &by_lint {
+                let level = level_name(self.level(*lint));
+                writeln!(
+                    out,
+                    "<h3 class=\"{level}\">{} (<code>{lint}</code>, {level}) — {}</h3>\n<ul>",
+                    escape(description(*lint)),
+                    diagnostics.len()
+                )
+                .unwrap();
+                for diagnostic in diagnostics {
+                    out.push_str("<li>");
+                    let mods = diagnostic
+                        .mods
+                        .iter()
+                        .map(|m| format!("<code>{}</code>", escape(&m.url)))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    if !mods.is_empty() {
+                        write!(out, "{mods}: ").unwrap();
+                    }
+                    writeln!(out, "{}</li>", html_line(diagnostic)).unwrap();
+                }
+                out.push_str("</ul>\n");
+            }
+
+            out.push_str("<h2>By mod</h2>\n");
+            for (url, diagnostics) in &by_mod {
+                writeln!(
+                    out,
+                    "<h3><code>{}</code> — {}</h3>\n<ul>",
+                    escape(url),
+                    diagnostics.len()
+                )
+                .unwrap();
+                for diagnostic in diagnostics {
+                    writeln!(
+                        out,
+                        "<li class=\"{}\"><code>{}</code>: {}</li>",
+                        level_name(diagnostic.level),
+                        diagnostic.lint,
+                        html_line(diagnostic)
+                    )
+                    .unwrap();
+                }
+                out.push_str("</ul>\n");
+            }
+        }
+
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    fn level(&self, lint: LintId) -> LintLevel {
+        self.levels.get(&lint).copied().unwrap_or(LintLevel::Warn)
+    }
+
+    fn by_lint(&self) -> BTreeMap<LintId, Vec<&Diagnostic>> {
+        let mut by_lint = BTreeMap::<_, Vec<_>>::new();
+        for diagnostic in &self.diagnostics {
+            by_lint.entry(diagnostic.lint).or_default().push(diagnostic);
+        }
+        by_lint
+    }
+
+    /// Diagnostics by the URL of each mod involved; a diagnostic about several mods is listed under
+    /// each of them.
+    fn by_mod(&self) -> BTreeMap<&str, Vec<&Diagnostic>> {
+        let mut by_mod = BTreeMap::<_, Vec<_>>::new();
+        for diagnostic in &self.diagnostics {
+            if diagnostic.mods.is_empty() {
+                by_mod.entry(NO_MOD).or_default().push(diagnostic);
+            }
+            for m in &diagnostic.mods {
+                by_mod.entry(m.url.as_str()).or_default().push(diagnostic);
+            }
+        }
+        by_mod
+    }
+
+    fn summary(&self, by_mod: &BTreeMap<&str, Vec<&Diagnostic>>) -> String {
+        if self.is_empty() {
+            return "No problems found.".to_string();
+        }
+        let mods = by_mod.keys().filter(|url| **url != NO_MOD).count();
+        let denied = self.denied();
+        let mut summary = format!(
+            "{} problem(s) found by {} lint(s) in {mods} mod(s).",
+            self.diagnostics.len(),
+            self.fired().len()
+        );
+        if !denied.is_empty() {
+            write!(
+                summary,
+                " Deny-level lints found problems: {}.",
+                denied
+                    .iter()
+                    .map(LintId::name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+            .unwrap();
+        }
+        summary
+    }
+}
+
+fn description(lint: LintId) -> &'static str {
+    lint.factory().map_or(lint.name(), |f| f.description)
+}
+
+fn level_name(level: LintLevel) -> &'static str {
+    match level {
+        LintLevel::Allow => "allow",
+        LintLevel::Warn => "warn",
+        LintLevel::Deny => "deny",
+    }
+}
+
+/// The path and message of a diagnostic.
+fn markdown_line(diagnostic: &Diagnostic) -> String {
+    match &diagnostic.path {
+        Some(path) => format!("`{path}` {}", diagnostic.message),
+        None => diagnostic.message.clone(),
+    }
+}
+
+/// The path and message of a diagnostic.
+fn html_line(diagnostic: &Diagnostic) -> String {
+    match &diagnostic.path {
+        Some(path) => format!(
+            "<code>{}</code> {}",
+            escape(path),
+            escape(&diagnostic.message)
+        ),
+        None => escape(&diagnostic.message),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
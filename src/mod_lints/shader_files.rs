@@ -1,32 +1,48 @@
-use std::collections::{BTreeMap, BTreeSet};
-
 use anyhow::Result;
 
-use crate::providers::ModSpecification;
-
-use super::{Lint, LintCtxt};
+use super::{
+    Diagnostic, Fix, Lint, LintCtxt, LintFactory, LintId, LintLevel, LintMod, ModFile, ModPak,
+};
+
+inventory::submit! {
+    LintFactory {
+        id: LintId::SHADER_FILES,
+        description: "Mods containing shader files",
+        default_level: LintLevel::Warn,
+        default_allowed_paths: &[],
+        requires_game_pak: false,
+        new: || Box::<ShaderFilesLint>::default(),
+    }
+}
 
 #[derive(Default)]
-pub struct ShaderFilesLint;
+pub struct ShaderFilesLint {
+    diagnostics: Vec<Diagnostic>,
+}
 
 impl Lint for ShaderFilesLint {
-    type Output = BTreeMap<ModSpecification, BTreeSet<String>>;
-
-    fn check_mods(&mut self, lcx: &LintCtxt) -> Result<Self::Output> {
-        let mut shader_file_mods = BTreeMap::new();
-
-        lcx.for_each_mod_file(|mod_spec, _, _, raw_path, normalized_path| {
-            if raw_path.extension().and_then(std::ffi::OsStr::to_str) == Some("ushaderbytecode") {
-                shader_file_mods
-                    .entry(mod_spec)
-                    .and_modify(|paths: &mut BTreeSet<String>| {
-                        paths.insert(normalized_path.clone());
-                    })
-                    .or_insert_with(|| [normalized_path].into());
-            }
-            Ok(())
-        })?;
+    fn visit_file(
+        &mut self,
+        _lcx: &LintCtxt,
+        lint_mod: &LintMod,
+        _pak: &ModPak,
+        file: &ModFile,
+    ) -> Result<()> {
+        if file.path.extension().and_then(std::ffi::OsStr::to_str) == Some("ushaderbytecode") {
+            self.diagnostics.push(
+                Diagnostic::new(
+                    LintId::SHADER_FILES,
+                    "includes shader bytecode, which is not loaded and may crash the game",
+                )
+                .with_mod(lint_mod.spec.clone())
+                .with_path(&file.normalized_path)
+                .with_fix(Fix::RemoveFile),
+            );
+        }
+        Ok(())
+    }
 
-        Ok(shader_file_mods)
+    fn finish(&mut self, _lcx: &LintCtxt) -> Result<Vec<Diagnostic>> {
+        Ok(std::mem::take(&mut self.diagnostics))
     }
 }
@@ -4,97 +4,73 @@ use tracing::trace;
 
 use crate::providers::ModSpecification;
 
-use super::{Lint, LintCtxt};
+use super::{Diagnostic, Lint, LintCtxt, LintFactory, LintId, LintLevel, LintMod, ModFile, ModPak};
 
-#[derive(Default)]
-pub struct SplitAssetPairsLint;
+inventory::submit! {
+    LintFactory {
+        id: LintId::SPLIT_ASSET_PAIRS,
+        description: "Mods containing split {uexp, uasset} pairs",
+        default_level: LintLevel::Warn,
+        default_allowed_paths: &[],
+        requires_game_pak: false,
+        new: || Box::<SplitAssetPairsLint>::default(),
+    }
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub enum SplitAssetPair {
-    MissingUexp,
-    MissingUasset,
+#[derive(Default)]
+pub struct SplitAssetPairsLint {
+    per_mod_path_without_final_ext_to_exts_map:
+        BTreeMap<ModSpecification, BTreeMap<String, BTreeSet<String>>>,
 }
 
 impl Lint for SplitAssetPairsLint {
-    type Output = BTreeMap<ModSpecification, BTreeMap<String, SplitAssetPair>>;
+    fn visit_file(
+        &mut self,
+        _lcx: &LintCtxt,
+        lint_mod: &LintMod,
+        _pak: &ModPak,
+        file: &ModFile,
+    ) -> anyhow::Result<()> {
+        let mut iter = file.normalized_path.rsplit('.').take(2);
+        let Some(final_ext) = iter.next() else {
+            return Ok(());
+        };
+        let Some(path_without_final_ext) = iter.next() else {
+            return Ok(());
+        };
 
-    fn check_mods(&mut self, lcx: &LintCtxt) -> anyhow::Result<Self::Output> {
-        let mut per_mod_path_without_final_ext_to_exts_map = BTreeMap::new();
+        self.per_mod_path_without_final_ext_to_exts_map
+            .entry(lint_mod.spec.clone())
+            .or_default()
+            .entry(path_without_final_ext.to_string())
+            .or_default()
+            .insert(final_ext.to_string());
 
-        lcx.for_each_mod_file(|mod_spec, _, _, _, normalized_path| {
-            let mut iter = normalized_path.rsplit('.').take(2);
-            let Some(final_ext) = iter.next() else {
-                return Ok(());
-            };
-            let Some(path_without_final_ext) = iter.next() else {
-                return Ok(());
-            };
-
-            per_mod_path_without_final_ext_to_exts_map
-                .entry(mod_spec)
-                .and_modify(|map: &mut BTreeMap<String, BTreeSet<String>>| {
-                    map.entry(path_without_final_ext.to_string())
-                        .and_modify(|exts: &mut BTreeSet<String>| {
-                            exts.insert(final_ext.to_string());
-                        })
-                        .or_insert_with(|| [final_ext.to_string()].into());
-                })
-                .or_insert_with(|| {
-                    [(
-                        path_without_final_ext.to_string(),
-                        [final_ext.to_string()].into(),
-                    )]
-                    .into()
-                });
-
-            Ok(())
-        })?;
+        Ok(())
+    }
 
-        let mut split_asset_pairs_mods = BTreeMap::new();
+    fn finish(&mut self, _lcx: &LintCtxt) -> anyhow::Result<Vec<Diagnostic>> {
+        let mut diagnostics = vec![];
 
-        for (mod_spec, map) in per_mod_path_without_final_ext_to_exts_map {
+        for (mod_spec, map) in std::mem::take(&mut self.per_mod_path_without_final_ext_to_exts_map)
+        {
             for (path_without_final_ext, final_exts) in map {
-                split_asset_pairs_mods
-                    .entry(mod_spec.clone())
-                    .and_modify(|map: &mut BTreeMap<String, SplitAssetPair>| {
-                        match (final_exts.contains("uexp"), final_exts.contains("uasset")) {
-                            (true, false) => {
-                                map.insert(
-                                    format!("{path_without_final_ext}.uexp"),
-                                    SplitAssetPair::MissingUasset,
-                                );
-                            }
-                            (false, true) => {
-                                map.insert(
-                                    format!("{path_without_final_ext}.uasset"),
-                                    SplitAssetPair::MissingUexp,
-                                );
-                            }
-                            _ => {}
-                        }
-                    })
-                    .or_insert_with(|| {
-                        match (final_exts.contains("uexp"), final_exts.contains("uasset")) {
-                            (true, false) => [(
-                                format!("{path_without_final_ext}.uexp"),
-                                SplitAssetPair::MissingUasset,
-                            )]
-                            .into(),
-                            (false, true) => [(
-                                format!("{path_without_final_ext}.uasset"),
-                                SplitAssetPair::MissingUexp,
-                            )]
-                            .into(),
-                            _ => BTreeMap::default(),
-                        }
-                    });
+                let (path, message) =
+                    match (final_exts.contains("uexp"), final_exts.contains("uasset")) {
+                        (true, false) => ("uexp", "missing matching `.uasset`"),
+                        (false, true) => ("uasset", "missing matching `.uexp`"),
+                        _ => continue,
+                    };
+                diagnostics.push(
+                    Diagnostic::new(LintId::SPLIT_ASSET_PAIRS, message)
+                        .with_mod(mod_spec.clone())
+                        .with_path(format!("{path_without_final_ext}.{path}")),
+                );
             }
         }
 
-        split_asset_pairs_mods.retain(|_, map| !map.is_empty());
-
-        trace!("split_asset_pairs_mods:\n{:#?}", split_asset_pairs_mods);
+        trace!("split_asset_pairs diagnostics:\n{:#?}", diagnostics);
 
-        Ok(split_asset_pairs_mods)
+        Ok(diagnostics)
     }
 }
@@ -0,0 +1,133 @@
+use anyhow::Result;
+use rayon::prelude::*;
+
+use crate::parse_asset;
+use crate::providers::ModSpecification;
+
+use super::{Diagnostic, Lint, LintCtxt, LintFactory, LintId, LintLevel, LintMod, ModFile, ModPak};
+
+inventory::submit! {
+    LintFactory {
+        id: LintId::UNPARSABLE_ASSETS,
+        description: "Mods containing assets which do not parse for the game's engine version",
+        default_level: LintLevel::Warn,
+        default_allowed_paths: &[],
+        requires_game_pak: false,
+        new: || Box::<UnparsableAssetsLint>::default(),
+    }
+}
+
+/// Magic number at the start of every package.
+const PACKAGE_FILE_TAG: u32 = 0x9E2A83C1;
+/// Legacy file version written by UE 4.25 to 4.27; UE5 writes -8 and below.
+const LEGACY_FILE_VERSION: i32 = -7;
+/// `VER_UE4_AUTOMATIC_VERSION` of UE 4.27.
+const OBJECT_VERSION: i32 = 522;
+
+/// Parses every `.uasset`/`.umap` and `.uexp` pair as the game would, reporting assets which fail
+/// to parse or whose package summary shows they were cooked for another engine version. Cooked
+/// game content is normally unversioned, which is only mentioned when such an asset fails to
+/// parse.
+#[derive(Default)]
+pub struct UnparsableAssetsLint {
+    assets: Vec<(ModSpecification, String)>,
+}
+
+impl Lint for UnparsableAssetsLint {
+    fn visit_file(
+        &mut self,
+        _lcx: &LintCtxt,
+        lint_mod: &LintMod,
+        _pak: &ModPak,
+        file: &ModFile,
+    ) -> Result<()> {
+        let path = &file.normalized_path;
+        if path.ends_with(".uasset") || path.ends_with(".umap") {
+            self.assets.push((lint_mod.spec.clone(), path.clone()));
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, lcx: &LintCtxt) -> Result<Vec<Diagnostic>> {
+        let diagnostics = std::mem::take(&mut self.assets)
+            .into_par_iter()
+            .map(|(mod_spec, path)| -> Result<Option<Diagnostic>> {
+                let Some(pak) = lcx.get_mod(&mod_spec).and_then(LintMod::pak) else {
+                    return Ok(None);
+                };
+                let Some((uasset, uexp)) = pak.read_asset(&path)? else {
+                    return Ok(None);
+                };
+
+                Ok(check_asset(uasset, uexp).map(|message| {
+                    Diagnostic::new(LintId::UNPARSABLE_ASSETS, message)
+                        .with_mod(mod_spec)
+                        .with_path(path)
+                }))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(diagnostics.into_iter().flatten().collect())
+    }
+}
+
+/// Describes the first problem found with the asset, if any.
+fn check_asset(uasset: Vec<u8>, uexp: Vec<u8>) -> Option<String> {
+    let unversioned = match check_summary(&uasset) {
+        Ok(unversioned) => unversioned,
+        Err(message) => return Some(message),
+    };
+    parse_asset(uasset, uexp).err().map(|e| {
+        if unversioned {
+            format!("failed to parse as an unversioned Unreal Engine 4.27 package: {e}")
+        } else {
+            format!("failed to parse: {e}")
+        }
+    })
+}
+
+/// Check the versions in the package file summary, which `unreal_asset` overrides with the game's
+/// engine version for unversioned assets instead of rejecting them. Returns whether the package is
+/// unversioned, in which case only parsing it can tell whether it matches the game.
+fn check_summary(uasset: &[u8]) -> Result<bool, String> {
+    let mut ints = uasset
+        .chunks_exact(4)
+        .map(|c| i32::from_le_bytes(c.try_into().unwrap()));
+    let mut next = || {
+        ints.next()
+            .ok_or_else(|| "package summary is truncated".to_string())
+    };
+
+    let tag = next()? as u32;
+    if tag == PACKAGE_FILE_TAG.swap_bytes() {
+        return Err("package is big-endian, it was cooked for another platform".to_string());
+    }
+    if tag != PACKAGE_FILE_TAG {
+        return Err("not a package, the file does not start with the package tag".to_string());
+    }
+
+    let legacy_file_version = next()?;
+    if legacy_file_version < LEGACY_FILE_VERSION {
+        return Err(format!(
+            "cooked for Unreal Engine 5 (legacy file version {legacy_file_version}), the game uses 4.27"
+        ));
+    }
+    if legacy_file_version != LEGACY_FILE_VERSION {
+        return Err(format!(
+            "cooked for an older engine version (legacy file version {legacy_file_version}), the game uses 4.27"
+        ));
+    }
+
+    let _legacy_ue3_version = next()?;
+    let object_version = next()?;
+    let licensee_version = next()?;
+    if object_version == 0 && licensee_version == 0 {
+        return Ok(true);
+    }
+    if object_version != OBJECT_VERSION {
+        return Err(format!(
+            "package version {object_version} does not match the game's ({OBJECT_VERSION})"
+        ));
+    }
+    Ok(false)
+}
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::Sender;
 use tracing::info;
 
+use super::scheduler::{RequestScheduler, SchedulerMiddleware};
 use super::{
     BlobCache, BlobRef, FetchProgress, ModInfo, ModProvider, ModProviderCache, ModResolution,
     ModResponse, ModSpecification, ProviderCache,
@@ -46,7 +47,7 @@ impl ModProviderCache for HttpProviderCache {
 
 #[derive(Debug)]
 pub struct HttpProvider {
-    client: reqwest::Client,
+    client: reqwest_middleware::ClientWithMiddleware,
 }
 
 impl HttpProvider {
@@ -55,7 +56,9 @@ impl HttpProvider {
     }
     pub fn new() -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: reqwest_middleware::ClientBuilder::new(reqwest::Client::new())
+                .with::<SchedulerMiddleware>(Default::default())
+                .build(),
         }
     }
 }
@@ -123,39 +126,47 @@ impl ModProvider for HttpProvider {
                 path
             } else {
                 info!("downloading mod {url}...");
-                let response = self.client.get(url).send().await?.error_for_status()?;
-                let size = response.content_length(); // TODO will be incorrect if compressed
-                if let Some(mime) = response
-                    .headers()
-                    .get(reqwest::header::HeaderName::from_static("content-type"))
-                {
-                    let content_type = &mime.to_str()?;
-                    if !["application/zip", "application/octet-stream"].contains(content_type) {
-                        bail!("unexpected content-type: {content_type}");
-                    }
-                }
 
                 use futures::stream::TryStreamExt;
                 use tokio::io::AsyncWriteExt;
 
-                let mut cursor = std::io::Cursor::new(vec![]);
-                let mut stream = response.bytes_stream();
-                while let Some(bytes) = stream.try_next().await? {
-                    cursor.write_all(&bytes).await?;
-                    if let Some(size) = size {
-                        if let Some(tx) = &tx {
-                            tx.send(FetchProgress::Progress {
-                                resolution: res.clone(),
-                                progress: cursor.get_ref().len() as u64,
-                                size,
-                            })
-                            .await
-                            .unwrap();
+                let data = RequestScheduler::global()
+                    .hold_permit(async {
+                        let response = self.client.get(url).send().await?.error_for_status()?;
+                        let size = response.content_length(); // TODO will be incorrect if compressed
+                        if let Some(mime) = response
+                            .headers()
+                            .get(reqwest::header::HeaderName::from_static("content-type"))
+                        {
+                            let content_type = &mime.to_str()?;
+                            if !["application/zip", "application/octet-stream"]
+                                .contains(content_type)
+                            {
+                                bail!("unexpected content-type: {content_type}");
+                            }
                         }
-                    }
-                }
 
-                let blob = blob_cache.write(&cursor.into_inner())?;
+                        let mut cursor = std::io::Cursor::new(vec![]);
+                        let mut stream = response.bytes_stream();
+                        while let Some(bytes) = stream.try_next().await? {
+                            cursor.write_all(&bytes).await?;
+                            if let Some(size) = size {
+                                if let Some(tx) = &tx {
+                                    tx.send(FetchProgress::Progress {
+                                        resolution: res.clone(),
+                                        progress: cursor.get_ref().len() as u64,
+                                        size,
+                                    })
+                                    .await
+                                    .unwrap();
+                                }
+                            }
+                        }
+                        Ok(cursor.into_inner())
+                    })
+                    .await?;
+
+                let blob = blob_cache.write(&data)?;
                 let path = blob_cache.get_path(&blob).unwrap();
                 cache
                     .write()
@@ -1,17 +1,22 @@
 pub mod file;
 pub mod http;
 pub mod modio;
+pub mod scheduler;
 
-use crate::error::IntegrationError;
+use crate::error::{Cancelled, IntegrationError};
+use crate::providers::scheduler::RequestScheduler;
 use crate::state::config::ConfigWrapper;
+use crate::state::migration::{self, VersionedFile};
 use crate::write_file;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::Sender;
+use tokio_util::sync::CancellationToken;
 use tracing::info;
 
 use std::collections::{BTreeSet, HashMap, HashSet};
+use std::future::Future;
 use std::io::{Read, Seek};
 use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
@@ -59,16 +64,33 @@ impl DerefMut for VersionAnnotatedCache {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum MaybeVersionedCache {
-    Versioned(VersionAnnotatedCache),
-    Legacy(Cache!["0.0.0"]),
-}
+impl VersionedFile for VersionAnnotatedCache {
+    const NAME: &'static str = "cache.json";
+    const VERSIONS: &'static [&'static str] = &["0.0.0"];
+
+    fn deserialize_version(version: Option<&str>, buf: &[u8]) -> Result<Self> {
+        // HACK: versions are deserialized directly rather than through the tagged enum to
+        // workaround a serde issue relating to flattening with tags involving numeric keys in
+        // hashmaps, see <https://github.com/serde-rs/serde/issues/1183>.
+        Ok(match version {
+            None => VersionAnnotatedCache::V0_0_0(Cache_v0_0_0 {
+                cache: serde_json::from_slice(buf)?,
+            }),
+            Some("0.0.0") => VersionAnnotatedCache::V0_0_0(serde_json::from_slice(buf)?),
+            Some(version) => bail!("unsupported cache version {version}"),
+        })
+    }
 
-impl Default for MaybeVersionedCache {
-    fn default() -> Self {
-        MaybeVersionedCache::Versioned(Default::default())
+    fn version(&self) -> &'static str {
+        match self {
+            VersionAnnotatedCache::V0_0_0(_) => "0.0.0",
+        }
+    }
+
+    fn migrate(self) -> Self {
+        match self {
+            VersionAnnotatedCache::V0_0_0(_) => self,
+        }
     }
 }
 
@@ -97,7 +119,7 @@ impl ModStore {
 
         let cache = read_cache_metadata_or_default(&cache_metadata_path)?;
         let cache = ConfigWrapper::new(&cache_metadata_path, cache);
-        cache.save().unwrap();
+        cache.save()?;
 
         Ok(Self {
             providers: RwLock::new(providers),
@@ -158,6 +180,7 @@ impl ModStore {
         &self,
         mods: &[ModSpecification],
         update: bool,
+        cancel: &CancellationToken,
     ) -> Result<HashMap<ModSpecification, ModInfo>> {
         use futures::stream::{self, StreamExt, TryStreamExt};
 
@@ -168,14 +191,17 @@ impl ModStore {
         let mut precise_mod_specs = HashSet::new();
 
         while !to_resolve.is_empty() {
-            for (u, m) in stream::iter(
-                to_resolve
-                    .iter()
-                    .map(|u| self.resolve_mod(u.to_owned(), update)),
+            for (u, m) in cancellable(
+                cancel,
+                stream::iter(
+                    to_resolve
+                        .iter()
+                        .map(|u| self.resolve_mod(u.to_owned(), update)),
+                )
+                .boxed()
+                .buffer_unordered(RequestScheduler::global().max_concurrent_requests())
+                .try_collect::<Vec<_>>(),
             )
-            .boxed()
-            .buffer_unordered(5)
-            .try_collect::<Vec<_>>()
             .await?
             {
                 precise_mod_specs.insert(m.spec.clone());
@@ -219,16 +245,20 @@ impl ModStore {
         mods: &[&ModResolution],
         update: bool,
         tx: Option<Sender<FetchProgress>>,
+        cancel: &CancellationToken,
     ) -> Result<Vec<PathBuf>> {
         use futures::stream::{self, StreamExt, TryStreamExt};
 
-        stream::iter(
-            mods.iter()
-                .map(|res| self.fetch_mod(res, update, tx.clone())),
+        cancellable(
+            cancel,
+            stream::iter(
+                mods.iter()
+                    .map(|res| self.fetch_mod(res, update, tx.clone())),
+            )
+            .boxed() // without this the future becomes !Send https://github.com/rust-lang/rust/issues/104382
+            .buffer_unordered(RequestScheduler::global().max_concurrent_requests())
+            .try_collect::<Vec<_>>(),
         )
-        .boxed() // without this the future becomes !Send https://github.com/rust-lang/rust/issues/104382
-        .buffer_unordered(5)
-        .try_collect::<Vec<_>>()
         .await
     }
 
@@ -237,16 +267,20 @@ impl ModStore {
         mods: &[&ModResolution],
         update: bool,
         tx: Option<Sender<FetchProgress>>,
+        cancel: &CancellationToken,
     ) -> Result<Vec<PathBuf>> {
         use futures::stream::{self, StreamExt, TryStreamExt};
 
-        stream::iter(
-            mods.iter()
-                .map(|res| self.fetch_mod(res, update, tx.clone())),
+        cancellable(
+            cancel,
+            stream::iter(
+                mods.iter()
+                    .map(|res| self.fetch_mod(res, update, tx.clone())),
+            )
+            .boxed() // without this the future becomes !Send https://github.com/rust-lang/rust/issues/104382
+            .buffered(RequestScheduler::global().max_concurrent_requests())
+            .try_collect::<Vec<_>>(),
         )
-        .boxed() // without this the future becomes !Send https://github.com/rust-lang/rust/issues/104382
-        .buffered(5)
-        .try_collect::<Vec<_>>()
         .await
     }
 
@@ -267,15 +301,23 @@ impl ModStore {
             .await
     }
 
-    pub async fn update_cache(&self) -> Result<()> {
+    pub async fn update_cache(&self, cancel: &CancellationToken) -> Result<()> {
         let providers = self.providers.read().unwrap().clone();
         for (name, provider) in providers.iter() {
             info!("updating cache for {name} provider");
-            provider.update_cache(self.cache.clone()).await?;
+            cancellable(cancel, provider.update_cache(self.cache.clone())).await?;
         }
         Ok(())
     }
 
+    /// Store `data` in the blob cache, returning the path of the blob.
+    pub fn add_blob(&self, data: &[u8]) -> Result<PathBuf> {
+        let blob = self.blob_cache.write(data)?;
+        self.blob_cache
+            .get_path(&blob)
+            .context("blob missing after writing it")
+    }
+
     pub fn get_mod_info(&self, spec: &ModSpecification) -> Option<ModInfo> {
         self.get_provider(&spec.url)
             .ok()?
@@ -290,55 +332,29 @@ impl ModStore {
 
     pub fn get_version_name(&self, spec: &ModSpecification) -> Option<String> {
         self.get_provider(&spec.url)
-            .unwrap()
+            .ok()?
             .get_version_name(spec, self.cache.clone())
     }
 }
 
-fn read_cache_metadata_or_default(cache_metadata_path: &PathBuf) -> Result<VersionAnnotatedCache> {
-    let cache: MaybeVersionedCache = match std::fs::read(cache_metadata_path) {
-        Ok(buf) => {
-            let mut dyn_value = serde_json::from_slice::<serde_json::Value>(&buf)
-                .context("failed to deserialize cache metadata into dynamic json value")?;
-            let obj_map = dyn_value
-                .as_object_mut()
-                .context("failed to deserialize cache metadata into object map")?;
-            let version = obj_map.remove("version");
-            if let Some(v) = version && let serde_json::Value::String(vs) = v
-            {
-                match vs.as_str() {
-                    "0.0.0" => {
-                        // HACK: workaround a serde issue relating to flattening with tags
-                        // involving numeric keys in hashmaps, see
-                        // <https://github.com/serde-rs/serde/issues/1183>.
-                        match serde_json::from_slice::<Cache!["0.0.0"]>(&buf) {
-                            Ok(c) => MaybeVersionedCache::Versioned(VersionAnnotatedCache::V0_0_0(c)),
-                            Err(e) => Err(e).context("failed to deserialize cache as v0.0.0")?
-                        }
-                    }
-                    _ => unimplemented!(),
-                }
-            } else {
-                // HACK: workaround a serde issue relating to flattening with tags involving
-                // numeric keys in hashmaps, see <https://github.com/serde-rs/serde/issues/1183>.
-                match serde_json::from_slice::<HashMap<String, Box<dyn ModProviderCache>>>(&buf) {
-                    Ok(c) => MaybeVersionedCache::Legacy(Cache_v0_0_0 { cache: c }),
-                    Err(e) => Err(e).context("failed to deserialize legacy cache")?
-                }
-            }
-        }
-        Err(e) if e.kind() == std::io::ErrorKind::NotFound => MaybeVersionedCache::default(),
-        Err(e) => Err(e).context("failed to read `cache.json`")?,
-    };
-
-    let cache: VersionAnnotatedCache = match cache {
-        MaybeVersionedCache::Versioned(v) => match v {
-            VersionAnnotatedCache::V0_0_0(v) => VersionAnnotatedCache::V0_0_0(v),
-        },
-        MaybeVersionedCache::Legacy(legacy) => VersionAnnotatedCache::V0_0_0(legacy),
-    };
+/// Drive `fut` to completion unless `cancel` is triggered first, in which case the future is
+/// dropped (aborting any requests still in flight) and [`Cancelled`] is returned.
+async fn cancellable<T, F>(cancel: &CancellationToken, fut: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    tokio::select! {
+        res = fut => res,
+        _ = cancel.cancelled() => Err(Cancelled.into()),
+    }
+}
 
-    Ok(cache)
+fn read_cache_metadata_or_default(cache_metadata_path: &PathBuf) -> Result<VersionAnnotatedCache> {
+    match std::fs::read(cache_metadata_path) {
+        Ok(buf) => migration::load(cache_metadata_path, &buf),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Default::default()),
+        Err(e) => Err(e).context("failed to read `cache.json`"),
+    }
 }
 
 pub trait ReadSeek: Read + Seek + Send {}
@@ -365,14 +381,14 @@ impl FetchProgress {
 }
 
 /// Whether a mod can be resolved by clients or not
-#[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd, Hash)]
+#[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd, Hash, serde::Serialize)]
 pub enum ResolvableStatus {
     Unresolvable(String),
     Resolvable,
 }
 
 /// Returned from ModStore
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct ModInfo {
     pub provider: &'static str,
     pub name: String,
@@ -386,7 +402,7 @@ pub struct ModInfo {
 }
 
 /// Tags from mod.io.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct ModioTags {
     pub qol: bool,
     pub gameplay: bool,
@@ -398,13 +414,25 @@ pub struct ModioTags {
     pub approval_status: ApprovalStatus,
 }
 
-#[derive(Debug, Copy, Clone)]
+impl ModioTags {
+    /// Whether the version tags (e.g. `1.38`) include the given game version (e.g.
+    /// `1.38.91207.0`). Mods without any version tags are assumed to be compatible.
+    pub fn supports_game_version(&self, game_version: &str) -> bool {
+        self.versions.is_empty()
+            || self.versions.iter().any(|tag| {
+                let mut game_parts = game_version.split('.');
+                tag.split('.').all(|t| game_parts.next() == Some(t))
+            })
+    }
+}
+
+#[derive(Debug, Copy, Clone, serde::Serialize)]
 pub enum RequiredStatus {
     RequiredByAll,
     Optional,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, serde::Serialize)]
 pub enum ApprovalStatus {
     Verified,
     Approved,
@@ -437,7 +465,7 @@ impl ModSpecification {
 }
 
 /// Points to a specific version of a specific mod
-#[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd, Hash)]
+#[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd, Hash, serde::Serialize)]
 pub struct ModResolution {
     pub url: String,
     pub status: ResolvableStatus,
@@ -513,6 +541,8 @@ pub struct ProviderParameter<'a> {
     pub name: &'a str,
     pub description: &'a str,
     pub link: Option<&'a str>,
+    /// Secret values are kept in the secret store instead of `config.json`.
+    pub secret: bool,
 }
 
 #[typetag::serde(tag = "type")]
@@ -586,3 +616,49 @@ impl BlobCache {
 }
 
 inventory::collect!(ProviderFactory);
+
+#[cfg(test)]
+mod cache_tests {
+    use super::*;
+    use crate::state::migration::MigrationError;
+
+    const PROVIDER_CACHES: &str = r#"{
+        "modio": {"ModioCache": {"mod_id_map": {}, "modfile_blobs": {"123": "abc"}, "dependencies": {}, "mods": {}, "last_update_time": null}},
+        "http": {"HttpProviderCache": {"url_blobs": {"https://example.org/mod.pak": "def"}}}
+    }"#;
+
+    fn read(json: &str) -> (tempfile::TempDir, Result<VersionAnnotatedCache>) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        std::fs::write(&path, json).unwrap();
+        let cache = read_cache_metadata_or_default(&path);
+        (dir, cache)
+    }
+
+    #[test]
+    fn test_read_legacy_cache() {
+        let (dir, cache) = read(PROVIDER_CACHES);
+        let cache = cache.unwrap();
+        assert!(matches!(cache, VersionAnnotatedCache::V0_0_0(_)));
+        assert!(cache.cache["modio"].as_any().is::<modio::ModioCache>());
+        assert!(cache.cache["http"].as_any().is::<http::HttpProviderCache>());
+        assert!(dir.path().join("cache.json.legacy.bak").exists());
+    }
+
+    #[test]
+    fn test_read_cache_v0_0_0() {
+        let (dir, cache) = read(&format!(r#"{{"version": "0.0.0", "cache": {PROVIDER_CACHES}}}"#));
+        let cache = cache.unwrap();
+        assert_eq!(cache.cache.len(), 2);
+        assert!(!dir.path().join("cache.json.0.0.0.bak").exists());
+    }
+
+    #[test]
+    fn test_read_newer_cache() {
+        let (_dir, cache) = read(r#"{"version": "99.0.0", "cache": {}}"#);
+        assert!(matches!(
+            cache.unwrap_err().downcast::<MigrationError>(),
+            Ok(MigrationError::NewerVersion { .. })
+        ));
+    }
+}
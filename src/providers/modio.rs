@@ -11,14 +11,12 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use mockall::{predicate::*};
 
 use anyhow::{bail, Context, Result};
-use reqwest::{Request, Response};
-use reqwest_middleware::{Middleware, Next};
 use serde::{Deserialize, Serialize};
-use task_local_extensions::Extensions;
 use tokio::sync::mpsc::Sender;
 use tracing::{info, warn};
 use drg_modio::DrgModio;
 
+use super::scheduler::RequestScheduler;
 use super::{
     BlobCache, BlobRef, FetchProgress, ModInfo, ModProvider,
     ModProviderCache, ModResolution, ModResponse, ModSpecification, ProviderCache,
@@ -43,6 +41,7 @@ inventory::submit! {
                 name: "OAuth Token",
                 description: "mod.io OAuth token",
                 link: Some("https://mod.io/me/access"),
+                secret: true,
             },
         ]
     }
@@ -161,42 +160,6 @@ impl ModioFile {
     }
 }
 
-#[derive(Default)]
-struct LoggingMiddleware {
-    requests: std::sync::Arc<std::sync::atomic::AtomicUsize>,
-}
-
-#[async_trait::async_trait]
-impl Middleware for LoggingMiddleware {
-    async fn handle(
-        &self,
-        req: Request,
-        extensions: &mut Extensions,
-        next: Next<'_>,
-    ) -> reqwest_middleware::Result<Response> {
-        loop {
-            info!(
-                "request started {} {:?}",
-                self.requests
-                    .fetch_add(1, std::sync::atomic::Ordering::Relaxed),
-                req.url().path()
-            );
-            let res = next.clone().run(req.try_clone().unwrap(), extensions).await;
-            if let Ok(res) = &res {
-                if let Some(retry) = res.headers().get("retry-after") {
-                    info!("retrying after: {}...", retry.to_str().unwrap());
-                    tokio::time::sleep(tokio::time::Duration::from_secs(
-                        retry.to_str().unwrap().parse::<u64>().unwrap(),
-                    ))
-                    .await;
-                    continue;
-                }
-            }
-            return res;
-        }
-    }
-}
-
 #[async_trait::async_trait]
 impl<M: DrgModio + Send + Sync> ModProvider for ModioProvider<M> {
     async fn resolve_mod(
@@ -464,22 +427,25 @@ impl<M: DrgModio + Send + Sync> ModProvider for ModioProvider<M> {
                     use futures::stream::TryStreamExt;
                     use tokio::io::AsyncWriteExt;
 
-                    let mut cursor = std::io::Cursor::new(vec![]);
-                    let mut stream = Box::pin(self.modio.download(download).stream());
-                    while let Some(bytes) = stream.try_next().await? {
-                        cursor.write_all(&bytes).await?;
-                        if let Some(tx) = &tx {
-                            tx.send(FetchProgress::Progress {
-                                resolution: res.clone(),
-                                progress: cursor.get_ref().len() as u64,
-                                size,
-                            })
-                            .await
-                            .unwrap();
+                    let data = RequestScheduler::global().hold_permit(async {
+                        let mut cursor = std::io::Cursor::new(vec![]);
+                        let mut stream = Box::pin(self.modio.download(download).stream());
+                        while let Some(bytes) = stream.try_next().await? {
+                            cursor.write_all(&bytes).await?;
+                            if let Some(tx) = &tx {
+                                tx.send(FetchProgress::Progress {
+                                    resolution: res.clone(),
+                                    progress: cursor.get_ref().len() as u64,
+                                    size,
+                                })
+                                .await
+                                .unwrap();
+                            }
                         }
-                    }
+                        Ok(cursor.into_inner())
+                    }).await?;
 
-                    let blob = blob_cache.write(&cursor.into_inner())?;
+                    let blob = blob_cache.write(&data)?;
                     let path = blob_cache.get_path(&blob).unwrap();
 
                     cache
@@ -572,7 +538,7 @@ impl<M: DrgModio + Send + Sync> ModProvider for ModioProvider<M> {
                     .map(|u| resolve_mod(self, cache.clone(), u.to_owned())),
             )
             .boxed()
-            .buffer_unordered(5)
+            .buffer_unordered(RequestScheduler::global().max_concurrent_requests())
             .try_collect::<Vec<_>>()
             .await?
             {
@@ -2,7 +2,8 @@ use mockall::automock;
 use std::collections::{HashMap, HashSet};
 use mockall::predicate::str;
 use anyhow::Context;
-use crate::providers::modio::{LoggingMiddleware, MODIO_DRG_ID, ModioMod, ModioModResponse};
+use crate::providers::modio::{MODIO_DRG_ID, ModioMod, ModioModResponse};
+use crate::providers::scheduler::SchedulerMiddleware;
 
 #[cfg_attr(test, automock)]
 #[async_trait::async_trait]
@@ -31,7 +32,7 @@ pub trait DrgModio: Sync + Send {
 impl DrgModio for modio::Modio {
     fn with_parameters(parameters: &HashMap<String, String>) -> anyhow::Result<Self> {
         let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new())
-            .with::<LoggingMiddleware>(Default::default())
+            .with::<SchedulerMiddleware>(Default::default())
             .build();
         let modio = modio::Modio::new(
             modio::Credentials::with_token(
@@ -6,7 +6,8 @@ use modio::files::File;
 use modio::mods::Mod;
 use reqwest_middleware::ClientWithMiddleware;
 use crate::providers::modio::drg_modio::DrgModio;
-use crate::providers::modio::{LoggingMiddleware, ModioMod, ModioModResponse};
+use crate::providers::modio::{ModioMod, ModioModResponse};
+use crate::providers::scheduler::SchedulerMiddleware;
 
 pub struct SwissDevModio {
     client: ClientWithMiddleware,
@@ -25,7 +26,7 @@ const API_URL: &str = "https://mods.swiss.dev/api/v1";
 impl DrgModio for SwissDevModio {
     fn with_parameters(parameters: &HashMap<String, String>) -> anyhow::Result<Self> where Self: Sized {
         let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new())
-            .with::<LoggingMiddleware>(Default::default())
+            .with::<SchedulerMiddleware>(Default::default())
             .build();
         let modio = SwissDevModio::new(client);
 
@@ -37,3 +37,24 @@ pub fn process_modio_tags(set: &HashSet<String>) -> ModioTags {
         approval_status,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_supports_game_version() {
+        let tags = process_modio_tags(&HashSet::from([
+            "QoL".to_string(),
+            "1.37".to_string(),
+            "1.38".to_string(),
+        ]));
+        assert!(tags.supports_game_version("1.38.91207.0"));
+        assert!(tags.supports_game_version("1.37.89215.0"));
+        assert!(!tags.supports_game_version("1.39.93412.0"));
+        assert!(!tags.supports_game_version("1.380.0.0"));
+
+        let untagged = process_modio_tags(&HashSet::from(["QoL".to_string()]));
+        assert!(untagged.supports_game_version("1.39.93412.0"));
+    }
+}
@@ -0,0 +1,302 @@
+//! Request scheduler shared by all HTTP based providers.
+//!
+//! Every client built with [`SchedulerMiddleware`] shares a single global concurrency limit and
+//! per-host token buckets so that resolving or fetching many mods at once does not trip mod.io's
+//! rate limits. Requests that are rate limited anyway are retried a bounded number of times.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use reqwest::{Request, Response, StatusCode};
+use reqwest_middleware::{Middleware, Next};
+use serde::{Deserialize, Serialize};
+use task_local_extensions::Extensions;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::{debug, info, warn};
+
+lazy_static::lazy_static! {
+    static ref SCHEDULER: Arc<RequestScheduler> = Arc::new(RequestScheduler::new(Default::default()));
+}
+
+tokio::task_local! {
+    /// Set while a task holds a permit through [`RequestScheduler::hold_permit`].
+    static PERMIT_HELD: ();
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    /// Maximum number of requests in flight at once across all providers.
+    pub max_concurrent_requests: usize,
+    /// Sustained number of requests per second allowed to a single host. Zero disables rate
+    /// limiting.
+    pub requests_per_second: f64,
+    /// Number of requests that can be made to a single host in a burst before being throttled.
+    pub burst: u32,
+    /// Maximum number of times a rate limited request is retried before giving up.
+    pub max_retries: u32,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_requests: 5,
+            requests_per_second: 5.0,
+            burst: 10,
+            max_retries: 5,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct RequestMetrics {
+    requests: AtomicUsize,
+    retried: AtomicUsize,
+    throttled: AtomicUsize,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RequestMetricsSnapshot {
+    /// Total requests sent, including retries.
+    pub requests: usize,
+    /// Requests retried after the server responded with a rate limit.
+    pub retried: usize,
+    /// Requests delayed locally by the per-host rate limit.
+    pub throttled: usize,
+}
+
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(burst: u32) -> Self {
+        Self {
+            tokens: burst as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Take a token, returning how long the caller has to wait before it may use it.
+    fn take(&mut self, rate: f64, burst: u32) -> Duration {
+        if rate <= 0.0 {
+            return Duration::ZERO;
+        }
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now;
+        self.tokens = (self.tokens + elapsed * rate).min(burst.max(1) as f64) - 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / rate)
+        }
+    }
+}
+
+pub struct RequestScheduler {
+    config: RwLock<SchedulerConfig>,
+    semaphore: RwLock<Arc<Semaphore>>,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+    metrics: RequestMetrics,
+}
+
+impl RequestScheduler {
+    pub fn new(config: SchedulerConfig) -> Self {
+        Self {
+            semaphore: RwLock::new(Arc::new(Semaphore::new(
+                config.max_concurrent_requests.max(1),
+            ))),
+            config: RwLock::new(config),
+            buckets: Default::default(),
+            metrics: Default::default(),
+        }
+    }
+
+    /// Scheduler shared by all providers.
+    pub fn global() -> Arc<Self> {
+        SCHEDULER.clone()
+    }
+
+    /// Apply a new configuration. Requests already in flight are unaffected.
+    pub fn configure(&self, config: &SchedulerConfig) {
+        *self.semaphore.write().unwrap() =
+            Arc::new(Semaphore::new(config.max_concurrent_requests.max(1)));
+        self.buckets.lock().unwrap().clear();
+        *self.config.write().unwrap() = config.clone();
+    }
+
+    pub fn max_concurrent_requests(&self) -> usize {
+        self.config.read().unwrap().max_concurrent_requests.max(1)
+    }
+
+    pub fn metrics(&self) -> RequestMetricsSnapshot {
+        RequestMetricsSnapshot {
+            requests: self.metrics.requests.load(Ordering::Relaxed),
+            retried: self.metrics.retried.load(Ordering::Relaxed),
+            throttled: self.metrics.throttled.load(Ordering::Relaxed),
+        }
+    }
+
+    async fn throttle(&self, host: &str) {
+        let delay = {
+            let config = self.config.read().unwrap();
+            self.buckets
+                .lock()
+                .unwrap()
+                .entry(host.to_owned())
+                .or_insert_with(|| TokenBucket::new(config.burst))
+                .take(config.requests_per_second, config.burst)
+        };
+        if !delay.is_zero() {
+            self.metrics.throttled.fetch_add(1, Ordering::Relaxed);
+            debug!("throttling request to {host} for {delay:?}");
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Run `f` holding a concurrency permit until it completes, for downloads whose response body
+    /// is streamed long after the middleware has returned. Requests made by `f` do not take a
+    /// permit of their own.
+    pub async fn hold_permit<T>(
+        &self,
+        f: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        let _permit = self.acquire().await?;
+        PERMIT_HELD.scope((), f).await
+    }
+
+    async fn acquire(&self) -> reqwest_middleware::Result<OwnedSemaphorePermit> {
+        let semaphore = self.semaphore.read().unwrap().clone();
+        semaphore
+            .acquire_owned()
+            .await
+            .map_err(|e| reqwest_middleware::Error::Middleware(e.into()))
+    }
+}
+
+/// Middleware routing requests through the global [`RequestScheduler`].
+///
+/// The concurrency permit of a request is released once its response headers arrive, bodies
+/// which are streamed afterwards need [`RequestScheduler::hold_permit`].
+///
+/// Cancellation needs no support here: [`ModStore`](super::ModStore) drops the futures of
+/// cancelled operations, which also aborts any request waiting on the scheduler.
+#[derive(Clone)]
+pub struct SchedulerMiddleware {
+    scheduler: Arc<RequestScheduler>,
+}
+
+impl Default for SchedulerMiddleware {
+    fn default() -> Self {
+        Self {
+            scheduler: RequestScheduler::global(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for SchedulerMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let host = req.url().host_str().unwrap_or_default().to_owned();
+        let path = req.url().path().to_owned();
+        // requests with streaming bodies cannot be cloned, so they are only sent once
+        let max_retries = match req.try_clone() {
+            Some(_) => self.scheduler.config.read().unwrap().max_retries,
+            None => 0,
+        };
+        let permit_held = PERMIT_HELD.try_with(|_| ()).is_ok();
+        let mut req = Some(req);
+
+        let mut attempt = 0;
+        loop {
+            self.scheduler.throttle(&host).await;
+            let permit = if permit_held {
+                None
+            } else {
+                Some(self.scheduler.acquire().await?)
+            };
+
+            let request = self
+                .scheduler
+                .metrics
+                .requests
+                .fetch_add(1, Ordering::Relaxed);
+            info!("request started {request} {path:?}");
+
+            // the last attempt sends the original request
+            let attempt_req = if attempt < max_retries {
+                req.as_ref().and_then(Request::try_clone)
+            } else {
+                req.take()
+            }
+            .expect("request is left for every attempt");
+            let res = next.clone().run(attempt_req, extensions).await;
+            drop(permit);
+
+            if let Ok(res) = &res
+                && let Some(delay) = rate_limited(res, attempt)
+            {
+                if attempt >= max_retries {
+                    warn!("giving up on {path:?} after {attempt} retries");
+                    return Err(reqwest_middleware::Error::Middleware(anyhow!(
+                        "rate limited by {host}, gave up after {attempt} retries"
+                    )));
+                }
+                attempt += 1;
+                self.scheduler
+                    .metrics
+                    .retried
+                    .fetch_add(1, Ordering::Relaxed);
+                info!("retrying after: {delay:?}...");
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+            return res;
+        }
+    }
+}
+
+/// Returns how long to wait before retrying if the response indicates the request was rate
+/// limited.
+fn rate_limited(res: &Response, attempt: u32) -> Option<Duration> {
+    let retry_after = res.headers().get(reqwest::header::RETRY_AFTER);
+    if retry_after.is_none() && res.status() != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    Some(
+        retry_after
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or_else(|| Duration::from_secs(1 << attempt.min(6))),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let mut bucket = TokenBucket::new(2);
+        assert_eq!(bucket.take(1.0, 2), Duration::ZERO);
+        assert_eq!(bucket.take(1.0, 2), Duration::ZERO);
+        assert!(bucket.take(1.0, 2) > Duration::from_millis(900));
+
+        let mut unlimited = TokenBucket::new(0);
+        for _ in 0..100 {
+            assert_eq!(unlimited.take(0.0, 0), Duration::ZERO);
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::providers::{
+    ApprovalStatus, ModInfo, ModSpecification, ModStore, ModioTags, RequiredStatus,
+};
+
+use super::ModData_v0_2_0 as ModData;
+
+/// Why a mod was left out of the client-only subset of a profile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Error)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum ClientOnlyExclusion {
+    #[error("marked as required in the profile")]
+    Required,
+    #[error("marked as required by all players on mod.io")]
+    RequiredByAll,
+    #[error("not verified or approved on mod.io")]
+    NotApproved,
+    #[error("tagged as gameplay or framework")]
+    Gameplay,
+    #[error("not tagged as QoL, visual or audio")]
+    NotClientSide,
+    #[error("no mod.io metadata available")]
+    Unknown,
+    #[error("depends on `{url}` which is not client-only safe")]
+    Dependency { url: String },
+}
+
+/// The mods of a profile which only affect the local player and can therefore be used in public
+/// lobbies without other players having them installed.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ClientOnlySubset {
+    /// Client-only safe mods in load order.
+    pub mods: Vec<ModSpecification>,
+    /// Enabled mods which were left out and why.
+    pub excluded: Vec<(ModSpecification, ClientOnlyExclusion)>,
+}
+
+/// Check the mod.io tags of a mod. Only optional, verified or approved mods tagged as QoL,
+/// visual or audio and not as gameplay or framework are client-only safe.
+pub fn client_only_exclusion(tags: Option<&ModioTags>) -> Option<ClientOnlyExclusion> {
+    let Some(tags) = tags else {
+        return Some(ClientOnlyExclusion::Unknown);
+    };
+    if matches!(tags.required_status, RequiredStatus::RequiredByAll) {
+        Some(ClientOnlyExclusion::RequiredByAll)
+    } else if matches!(tags.approval_status, ApprovalStatus::Sandbox) {
+        Some(ClientOnlyExclusion::NotApproved)
+    } else if tags.gameplay || tags.framework {
+        Some(ClientOnlyExclusion::Gameplay)
+    } else if !(tags.qol || tags.visual || tags.audio) {
+        Some(ClientOnlyExclusion::NotClientSide)
+    } else {
+        None
+    }
+}
+
+impl ModData {
+    /// Derive the client-only subset of the enabled mods of a profile. Mod info is looked up
+    /// with `info`, so metadata must already be resolved for mods to be considered. Mods whose
+    /// dependencies are not client-only safe themselves are excluded as well, since integrating
+    /// them would pull the dependencies back in.
+    pub fn client_only_subset<F>(&self, profile: &str, info: F) -> ClientOnlySubset
+    where
+        F: Fn(&ModSpecification) -> Option<ModInfo>,
+    {
+        let mut checked = HashMap::new();
+        let mut subset = ClientOnlySubset::default();
+        self.for_each_enabled_mod(profile, |mc| {
+            let exclusion = if mc.required {
+                Some(ClientOnlyExclusion::Required)
+            } else {
+                check_mod(&mc.spec, &info, &mut checked, &mut vec![]).0
+            };
+            match exclusion {
+                Some(exclusion) => subset.excluded.push((mc.spec.clone(), exclusion)),
+                None => subset.mods.push(mc.spec.clone()),
+            }
+        });
+        subset
+    }
+
+    /// [`ModData::client_only_subset`] using the mod info cached in `store`.
+    pub fn client_only_subset_cached(&self, profile: &str, store: &ModStore) -> ClientOnlySubset {
+        self.client_only_subset(profile, |spec| store.get_mod_info(spec))
+    }
+}
+
+/// Check a mod and its dependencies. Also returns the depth in `visiting` of the shallowest mod
+/// still being checked which the result relies on, since mods in a dependency cycle are assumed
+/// safe until the cycle is resolved as a whole.
+fn check_mod<F>(
+    spec: &ModSpecification,
+    info: &F,
+    checked: &mut HashMap<String, Option<ClientOnlyExclusion>>,
+    visiting: &mut Vec<String>,
+) -> (Option<ClientOnlyExclusion>, usize)
+where
+    F: Fn(&ModSpecification) -> Option<ModInfo>,
+{
+    if let Some(exclusion) = checked.get(&spec.url) {
+        return (exclusion.clone(), usize::MAX);
+    }
+    if let Some(depth) = visiting.iter().position(|url| *url == spec.url) {
+        return (None, depth);
+    }
+    let depth = visiting.len();
+    visiting.push(spec.url.clone());
+
+    let mod_info = info(spec);
+    let mut exclusion =
+        client_only_exclusion(mod_info.as_ref().and_then(|i| i.modio_tags.as_ref()));
+    let mut low = usize::MAX;
+    if exclusion.is_none()
+        && let Some(mod_info) = &mod_info
+    {
+        for dep in &mod_info.suggested_dependencies {
+            let (dep_exclusion, dep_low) = check_mod(dep, info, checked, visiting);
+            low = low.min(dep_low);
+            if dep_exclusion.is_some() {
+                exclusion = Some(ClientOnlyExclusion::Dependency {
+                    url: dep.url.clone(),
+                });
+                break;
+            }
+        }
+    }
+    visiting.pop();
+
+    // Exclusions are final, but a mod found safe only while a mod further up is still being
+    // checked may yet depend on an excluded mod through it, so it is not remembered.
+    if exclusion.is_some() || low >= depth {
+        checked.insert(spec.url.clone(), exclusion.clone());
+        low = usize::MAX;
+    }
+    (exclusion, low)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::super::{ModConfig, ModOrGroup, ModProfile};
+    use super::*;
+    use crate::providers::{ModResolution, ResolvableStatus};
+
+    fn tags(qol: bool, gameplay: bool, approval_status: ApprovalStatus) -> ModioTags {
+        ModioTags {
+            qol,
+            gameplay,
+            audio: false,
+            visual: false,
+            framework: false,
+            versions: BTreeSet::new(),
+            required_status: RequiredStatus::Optional,
+            approval_status,
+        }
+    }
+
+    #[test]
+    fn test_client_only_exclusion() {
+        assert_eq!(
+            client_only_exclusion(Some(&tags(true, false, ApprovalStatus::Verified))),
+            None
+        );
+        assert_eq!(
+            client_only_exclusion(Some(&tags(true, false, ApprovalStatus::Approved))),
+            None
+        );
+        assert_eq!(
+            client_only_exclusion(Some(&tags(true, false, ApprovalStatus::Sandbox))),
+            Some(ClientOnlyExclusion::NotApproved)
+        );
+        assert_eq!(
+            client_only_exclusion(Some(&tags(true, true, ApprovalStatus::Verified))),
+            Some(ClientOnlyExclusion::Gameplay)
+        );
+        assert_eq!(
+            client_only_exclusion(Some(&tags(false, false, ApprovalStatus::Verified))),
+            Some(ClientOnlyExclusion::NotClientSide)
+        );
+        assert_eq!(
+            client_only_exclusion(Some(&ModioTags {
+                required_status: RequiredStatus::RequiredByAll,
+                ..tags(true, false, ApprovalStatus::Verified)
+            })),
+            Some(ClientOnlyExclusion::RequiredByAll)
+        );
+        assert_eq!(
+            client_only_exclusion(None),
+            Some(ClientOnlyExclusion::Unknown)
+        );
+    }
+
+    #[test]
+    fn test_client_only_subset_without_info() {
+        let mod_config = |url: &str, required: bool, enabled: bool| {
+            ModOrGroup::Individual(ModConfig {
+                spec: ModSpecification::new(url.to_string()),
+                required,
+                enabled,
+            })
+        };
+        let mut mod_data = ModData::default();
+        mod_data.profiles.insert(
+            "default".to_string(),
+            ModProfile {
+                mods: vec![
+                    mod_config("a", true, true),
+                    mod_config("b", false, true),
+                    mod_config("c", false, false),
+                ],
+                ..Default::default()
+            },
+        );
+
+        let subset = mod_data.client_only_subset("default", |_| None);
+        assert!(subset.mods.is_empty());
+        assert_eq!(
+            subset.excluded,
+            [
+                (
+                    ModSpecification::new("a".to_string()),
+                    ClientOnlyExclusion::Required
+                ),
+                (
+                    ModSpecification::new("b".to_string()),
+                    ClientOnlyExclusion::Unknown
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_client_only_subset_dependency_cycle() {
+        let mod_info = |url: &str, safe: bool, dependencies: &[&str]| ModInfo {
+            provider: "test",
+            name: url.to_string(),
+            spec: ModSpecification::new(url.to_string()),
+            versions: vec![],
+            resolution: ModResolution {
+                url: url.to_string(),
+                status: ResolvableStatus::Resolvable,
+            },
+            suggested_require: false,
+            suggested_dependencies: dependencies
+                .iter()
+                .map(|d| ModSpecification::new(d.to_string()))
+                .collect(),
+            modio_tags: Some(tags(true, !safe, ApprovalStatus::Verified)),
+            modio_id: None,
+        };
+        // `a` depends on `b` and the unsafe `c`, `b` depends on `a`
+        let infos = [
+            mod_info("a", true, &["b", "c"]),
+            mod_info("b", true, &["a"]),
+            mod_info("c", false, &[]),
+        ];
+        let mut mod_data = ModData::default();
+        mod_data.profiles.insert(
+            "default".to_string(),
+            ModProfile {
+                mods: ["a", "b"]
+                    .map(|url| {
+                        ModOrGroup::Individual(ModConfig {
+                            spec: ModSpecification::new(url.to_string()),
+                            required: false,
+                            enabled: true,
+                        })
+                    })
+                    .into(),
+                ..Default::default()
+            },
+        );
+
+        let subset = mod_data.client_only_subset("default", |spec| {
+            infos.iter().find(|i| i.spec.url == spec.url).cloned()
+        });
+        assert!(subset.mods.is_empty());
+        assert_eq!(
+            subset.excluded,
+            [
+                (
+                    ModSpecification::new("a".to_string()),
+                    ClientOnlyExclusion::Dependency {
+                        url: "c".to_string()
+                    }
+                ),
+                (
+                    ModSpecification::new("b".to_string()),
+                    ClientOnlyExclusion::Dependency {
+                        url: "a".to_string()
+                    }
+                ),
+            ]
+        );
+    }
+}
@@ -4,6 +4,10 @@ use std::path::{Path, PathBuf};
 use anyhow::{Context, Result};
 
 use serde::{de::DeserializeOwned, Serialize};
+use tracing::error;
+
+/// Number of previous versions kept by [`ConfigWrapper::with_backups`].
+const BACKUP_COUNT: usize = 3;
 
 pub trait ConfigTrait: std::fmt::Debug + Default + Serialize + DeserializeOwned {}
 impl<T> ConfigTrait for T where T: std::fmt::Debug + Default + Serialize + DeserializeOwned {}
@@ -12,6 +16,7 @@ impl<T> ConfigTrait for T where T: std::fmt::Debug + Default + Serialize + Deser
 #[derive(Debug)]
 pub struct ConfigWrapper<C: ConfigTrait> {
     path: Option<PathBuf>,
+    backups: bool,
     config: C,
 }
 
@@ -20,13 +25,28 @@ impl<C: ConfigTrait> ConfigWrapper<C> {
     pub fn new<P: AsRef<Path>>(path: P, config: C) -> Self {
         Self {
             config,
+            backups: false,
+            path: Some(path.as_ref().to_path_buf()),
+        }
+    }
+
+    /// Like [`ConfigWrapper::new`] but each save first moves the previous file into a set of
+    /// rolling backups, see [`backups`].
+    pub fn with_backups<P: AsRef<Path>>(path: P, config: C) -> Self {
+        Self {
+            config,
+            backups: true,
             path: Some(path.as_ref().to_path_buf()),
         }
     }
 
     /// Create wrapper that lives only in memory and has no file backing
     pub fn memory(config: C) -> Self {
-        Self { config, path: None }
+        Self {
+            config,
+            backups: false,
+            path: None,
+        }
     }
 
     /// Try our best to ensure that the config written is complete to protect against partial
@@ -38,9 +58,14 @@ impl<C: ConfigTrait> ConfigWrapper<C> {
     /// See <https://stackoverflow.com/questions/70362352/atomic-file-create-write>.
     pub fn save(&self) -> Result<()> {
         if let Some(final_path) = &self.path {
+            let data = serde_json::to_vec_pretty(&self.config)?;
+            if self.backups {
+                rotate_backups(final_path, &data)
+                    .with_context(|| format!("failed to back up {}", final_path.display()))?;
+            }
             let mut temp_file = tempfile::NamedTempFile::new_in(final_path.parent().unwrap())?;
             temp_file
-                .write_all(&serde_json::to_vec_pretty(&self.config)?)
+                .write_all(&data)
                 .context("failed to write to tempfile")?;
             temp_file
                 .persist(final_path)
@@ -65,6 +90,80 @@ impl<C: ConfigTrait> std::ops::DerefMut for ConfigWrapper<C> {
 
 impl<C: ConfigTrait> Drop for ConfigWrapper<C> {
     fn drop(&mut self) {
-        self.save().unwrap();
+        if let Err(e) = self.save() {
+            error!("failed to save {:?}: {e:#}", self.path);
+        }
+    }
+}
+
+fn backup_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".bak{n}"));
+    path.with_file_name(name)
+}
+
+/// Existing backups of `path`, newest first.
+pub fn backups(path: &Path) -> Vec<PathBuf> {
+    (1..=BACKUP_COUNT)
+        .map(|n| backup_path(path, n))
+        .filter(|p| p.exists())
+        .collect()
+}
+
+/// Shift existing backups back by one and move the current contents of `path` into the newest
+/// slot. Skipped if `path` already contains `data` or is not valid JSON, so that repeated saves or
+/// a corrupt file do not push out good backups.
+fn rotate_backups(path: &Path, data: &[u8]) -> Result<()> {
+    let current = match std::fs::read(path) {
+        Ok(current) => current,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    if current == data || serde_json::from_slice::<serde_json::Value>(&current).is_err() {
+        return Ok(());
+    }
+    for n in (1..BACKUP_COUNT).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            std::fs::rename(from, backup_path(path, n + 1))?;
+        }
+    }
+    std::fs::write(backup_path(path, 1), current)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rolling_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        let mut config = ConfigWrapper::with_backups(&path, 0u32);
+
+        for i in 1..=5 {
+            *config = i;
+            config.save().unwrap();
+            // saving unchanged data does not rotate
+            config.save().unwrap();
+        }
+
+        let backups = backups(&path);
+        assert_eq!(backups.len(), BACKUP_COUNT);
+        let read = |p: &Path| serde_json::from_slice::<u32>(&std::fs::read(p).unwrap()).unwrap();
+        assert_eq!(read(&path), 5);
+        assert_eq!(
+            backups.iter().map(|p| read(p)).collect::<Vec<_>>(),
+            [4, 3, 2]
+        );
+
+        std::fs::write(&path, "{ corrupt").unwrap();
+        *config = 6;
+        config.save().unwrap();
+        assert_eq!(
+            backups.iter().map(|p| read(p)).collect::<Vec<_>>(),
+            [4, 3, 2]
+        );
     }
 }
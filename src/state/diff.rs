@@ -0,0 +1,457 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::providers::{ModSpecification, ModStore};
+
+use super::{ModData_v0_2_0 as ModData, ModOrGroup};
+
+/// A profile flattened to its mods in load order, including inherited mods, as compared by
+/// [`ProfileDiff`].
+#[derive(Debug, Clone, Default)]
+pub struct ProfileMods {
+    pub mods: Vec<ProfileMod>,
+    /// Groups included in the profile and whether each is enabled.
+    pub groups: BTreeMap<String, bool>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProfileMod {
+    pub spec: ModSpecification,
+    /// The mod's own enabled state with the profile's overrides applied, regardless of whether
+    /// its group is enabled.
+    pub enabled: bool,
+    /// Innermost group the mod is included through, if any.
+    pub group: Option<String>,
+}
+
+impl ProfileMods {
+    /// Parse a profile code as copied from the GUI, one mod URL per line. Every mod is enabled.
+    pub fn from_code(code: &str) -> Self {
+        Self {
+            mods: code
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(|url| ProfileMod {
+                    spec: ModSpecification::new(url.to_string()),
+                    enabled: true,
+                    group: None,
+                })
+                .collect(),
+            groups: BTreeMap::new(),
+        }
+    }
+}
+
+impl ModData {
+    pub fn profile_mods(&self, profile: &str) -> Result<ProfileMods> {
+        let mods = self.walk_mods(profile);
+        let profile = self
+            .profiles
+            .get(profile)
+            .with_context(|| format!("profile `{profile}` does not exist"))?;
+        Ok(ProfileMods {
+            mods: mods
+                .into_iter()
+                .map(|walked| ProfileMod {
+                    spec: walked.config.spec.clone(),
+                    enabled: walked.enabled,
+                    group: walked.groups.last().map(|group| group.to_string()),
+                })
+                .collect(),
+            groups: profile
+                .mods
+                .iter()
+                .filter_map(|m| match m {
+                    ModOrGroup::Group {
+                        group_name,
+                        enabled,
+                        ..
+                    } => Some((group_name.clone(), *enabled)),
+                    ModOrGroup::Individual(_) => None,
+                })
+                .collect(),
+        })
+    }
+
+    /// Diff two profiles, see [`ProfileDiff::new`].
+    pub fn diff_profiles(
+        &self,
+        old: &str,
+        new: &str,
+        store: Option<&ModStore>,
+    ) -> Result<ProfileDiff> {
+        Ok(ProfileDiff::new(
+            &self.profile_mods(old)?,
+            &self.profile_mods(new)?,
+            store,
+        ))
+    }
+}
+
+/// Differences between two profiles. Mods are identified by URL, or paired with a mod whose URL
+/// they satisfy as a dependency, such as another version of the same mod.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ProfileDiff {
+    pub added: Vec<ModSpecification>,
+    pub removed: Vec<ModSpecification>,
+    pub enabled_changed: Vec<EnabledChange>,
+    /// Mods whose position relative to the other mods changed.
+    pub moved: Vec<MovedMod>,
+    /// Mods included through a different group, or moved into or out of a group.
+    pub group_changed: Vec<GroupChange>,
+    pub version_changed: Vec<VersionChange>,
+    pub groups_added: Vec<String>,
+    pub groups_removed: Vec<String>,
+    pub groups_enabled_changed: Vec<GroupEnabledChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EnabledChange {
+    pub spec: ModSpecification,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MovedMod {
+    pub spec: ModSpecification,
+    pub old_index: usize,
+    pub new_index: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GroupChange {
+    pub spec: ModSpecification,
+    pub old_group: Option<String>,
+    pub new_group: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VersionChange {
+    pub old_spec: ModSpecification,
+    pub new_spec: ModSpecification,
+    /// Resolved version names, if known to the mod store.
+    pub old_version: Option<String>,
+    pub new_version: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GroupEnabledChange {
+    pub name: String,
+    pub enabled: bool,
+}
+
+impl ProfileDiff {
+    /// Diff `old` against `new`. Version names are looked up in `store` if given.
+    pub fn new(old: &ProfileMods, new: &ProfileMods, store: Option<&ModStore>) -> Self {
+        let mut diff = Self::default();
+        let pairs = pair_mods(&old.mods, &new.mods);
+
+        let mut old_paired = vec![false; old.mods.len()];
+        let mut new_paired = vec![false; new.mods.len()];
+        for &(o, n) in &pairs {
+            old_paired[o] = true;
+            new_paired[n] = true;
+        }
+        diff.removed = old
+            .mods
+            .iter()
+            .zip(&old_paired)
+            .filter(|(_, paired)| !**paired)
+            .map(|(m, _)| m.spec.clone())
+            .collect();
+        diff.added = new
+            .mods
+            .iter()
+            .zip(&new_paired)
+            .filter(|(_, paired)| !**paired)
+            .map(|(m, _)| m.spec.clone())
+            .collect();
+
+        let version_name = |spec: &ModSpecification| store.and_then(|s| s.get_version_name(spec));
+        for &(o, n) in &pairs {
+            let (old_mod, new_mod) = (&old.mods[o], &new.mods[n]);
+            if old_mod.enabled != new_mod.enabled {
+                diff.enabled_changed.push(EnabledChange {
+                    spec: new_mod.spec.clone(),
+                    enabled: new_mod.enabled,
+                });
+            }
+            if old_mod.group != new_mod.group {
+                diff.group_changed.push(GroupChange {
+                    spec: new_mod.spec.clone(),
+                    old_group: old_mod.group.clone(),
+                    new_group: new_mod.group.clone(),
+                });
+            }
+            if old_mod.spec != new_mod.spec {
+                diff.version_changed.push(VersionChange {
+                    old_spec: old_mod.spec.clone(),
+                    new_spec: new_mod.spec.clone(),
+                    old_version: version_name(&old_mod.spec),
+                    new_version: version_name(&new_mod.spec),
+                });
+            }
+        }
+
+        // pairs are in old order, so the mods which keep their relative order are the longest
+        // increasing subsequence of new indices
+        let kept = longest_increasing_subsequence(&pairs.iter().map(|p| p.1).collect::<Vec<_>>());
+        for (i, &(o, n)) in pairs.iter().enumerate() {
+            if !kept.contains(&i) {
+                diff.moved.push(MovedMod {
+                    spec: new.mods[n].spec.clone(),
+                    old_index: o,
+                    new_index: n,
+                });
+            }
+        }
+        diff.moved.sort_by_key(|m| m.new_index);
+
+        for (name, enabled) in &old.groups {
+            match new.groups.get(name) {
+                None => diff.groups_removed.push(name.clone()),
+                Some(new_enabled) if new_enabled != enabled => {
+                    diff.groups_enabled_changed.push(GroupEnabledChange {
+                        name: name.clone(),
+                        enabled: *new_enabled,
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        diff.groups_added = new
+            .groups
+            .keys()
+            .filter(|name| !old.groups.contains_key(*name))
+            .cloned()
+            .collect();
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// One line per difference, marked `+` for additions, `-` for removals and `~` for changes.
+    pub fn lines(&self) -> Vec<(char, String)> {
+        let mut lines = vec![];
+        lines.extend(self.added.iter().map(|s| ('+', s.url.clone())));
+        lines.extend(self.removed.iter().map(|s| ('-', s.url.clone())));
+        lines.extend(self.enabled_changed.iter().map(|c| {
+            let state = if c.enabled { "enabled" } else { "disabled" };
+            ('~', format!("{}: {state}", c.spec.url))
+        }));
+        lines.extend(self.moved.iter().map(|m| {
+            (
+                '~',
+                format!(
+                    "{}: moved from position {} to {}",
+                    m.spec.url,
+                    m.old_index + 1,
+                    m.new_index + 1
+                ),
+            )
+        }));
+        lines.extend(self.group_changed.iter().map(|c| {
+            let group = |g: &Option<String>| g.as_deref().unwrap_or("(none)").to_string();
+            (
+                '~',
+                format!(
+                    "{}: group {} → {}",
+                    c.spec.url,
+                    group(&c.old_group),
+                    group(&c.new_group)
+                ),
+            )
+        }));
+        lines.extend(self.version_changed.iter().map(|c| {
+            let version = |v: &Option<String>| v.as_deref().unwrap_or("?").to_string();
+            (
+                '~',
+                format!(
+                    "{}: version {} → {}",
+                    c.new_spec.url,
+                    version(&c.old_version),
+                    version(&c.new_version)
+                ),
+            )
+        }));
+        lines.extend(
+            self.groups_added
+                .iter()
+                .map(|g| ('+', format!("group {g}"))),
+        );
+        lines.extend(
+            self.groups_removed
+                .iter()
+                .map(|g| ('-', format!("group {g}"))),
+        );
+        lines.extend(self.groups_enabled_changed.iter().map(|c| {
+            let state = if c.enabled { "enabled" } else { "disabled" };
+            ('~', format!("group {}: {state}", c.name))
+        }));
+        lines
+    }
+}
+
+/// Indices of mods in `old` and `new` which are the same mod, in `old` order. Mods with the same
+/// URL are paired first, then mods whose URLs satisfy each other as dependencies.
+fn pair_mods(old: &[ProfileMod], new: &[ProfileMod]) -> Vec<(usize, usize)> {
+    let mut new_of_old: Vec<Option<usize>> = vec![None; old.len()];
+    let mut new_paired = vec![false; new.len()];
+    let matchers: [fn(&ModSpecification, &ModSpecification) -> bool; 2] =
+        [|a, b| a == b, |a, b| a.satisfies_dependency(b)];
+    for matches in matchers {
+        for (o, old_mod) in old.iter().enumerate() {
+            if new_of_old[o].is_some() {
+                continue;
+            }
+            if let Some(n) =
+                (0..new.len()).find(|&n| !new_paired[n] && matches(&old_mod.spec, &new[n].spec))
+            {
+                new_of_old[o] = Some(n);
+                new_paired[n] = true;
+            }
+        }
+    }
+    new_of_old
+        .into_iter()
+        .enumerate()
+        .filter_map(|(o, n)| Some((o, n?)))
+        .collect()
+}
+
+/// Positions in `values` of one of its longest strictly increasing subsequences.
+fn longest_increasing_subsequence(values: &[usize]) -> Vec<usize> {
+    // length of the longest subsequence ending at each position, and the position before it
+    let mut lengths = vec![1; values.len()];
+    let mut previous = vec![None; values.len()];
+    for i in 0..values.len() {
+        for j in 0..i {
+            if values[j] < values[i] && lengths[j] + 1 > lengths[i] {
+                lengths[i] = lengths[j] + 1;
+                previous[i] = Some(j);
+            }
+        }
+    }
+    let mut subsequence = vec![];
+    let mut next = (0..values.len()).max_by_key(|&i| lengths[i]);
+    while let Some(i) = next {
+        subsequence.push(i);
+        next = previous[i];
+    }
+    subsequence.reverse();
+    subsequence
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(mods: &[(&str, bool, Option<&str>)]) -> ProfileMods {
+        ProfileMods {
+            mods: mods
+                .iter()
+                .map(|(url, enabled, group)| ProfileMod {
+                    spec: ModSpecification::new(url.to_string()),
+                    enabled: *enabled,
+                    group: group.map(str::to_string),
+                })
+                .collect(),
+            groups: mods
+                .iter()
+                .filter_map(|(_, _, group)| Some((group?.to_string(), true)))
+                .collect(),
+        }
+    }
+
+    fn specs(urls: &[&str]) -> Vec<ModSpecification> {
+        urls.iter()
+            .map(|url| ModSpecification::new(url.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_identical_profiles() {
+        let a = profile(&[("a", true, None), ("b", false, Some("g"))]);
+        assert!(ProfileDiff::new(&a, &a, None).is_empty());
+    }
+
+    #[test]
+    fn test_added_removed_and_enabled() {
+        let old = profile(&[("a", true, None), ("b", true, None), ("c", true, None)]);
+        let new = profile(&[("a", false, None), ("c", true, None), ("d", true, None)]);
+        let diff = ProfileDiff::new(&old, &new, None);
+        assert_eq!(diff.added, specs(&["d"]));
+        assert_eq!(diff.removed, specs(&["b"]));
+        assert_eq!(
+            diff.enabled_changed,
+            [EnabledChange {
+                spec: ModSpecification::new("a".to_string()),
+                enabled: false
+            }]
+        );
+        assert!(diff.moved.is_empty());
+    }
+
+    #[test]
+    fn test_moved() {
+        let old = profile(&[("a", true, None), ("b", true, None), ("c", true, None)]);
+        let new = profile(&[("c", true, None), ("a", true, None), ("b", true, None)]);
+        let diff = ProfileDiff::new(&old, &new, None);
+        assert_eq!(
+            diff.moved,
+            [MovedMod {
+                spec: ModSpecification::new("c".to_string()),
+                old_index: 2,
+                new_index: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_groups_and_versions() {
+        let old = profile(&[
+            ("https://mod.io/g/drg/m/x#1", true, None),
+            ("y", true, Some("g")),
+        ]);
+        let new = profile(&[
+            ("https://mod.io/g/drg/m/x#1/11", true, None),
+            ("y", true, Some("h")),
+        ]);
+        let diff = ProfileDiff::new(&old, &new, None);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(
+            diff.version_changed,
+            [VersionChange {
+                old_spec: ModSpecification::new("https://mod.io/g/drg/m/x#1".to_string()),
+                new_spec: ModSpecification::new("https://mod.io/g/drg/m/x#1/11".to_string()),
+                old_version: None,
+                new_version: None,
+            }]
+        );
+        assert_eq!(diff.groups_added, ["h"]);
+        assert_eq!(diff.groups_removed, ["g"]);
+        assert_eq!(
+            diff.group_changed,
+            [GroupChange {
+                spec: ModSpecification::new("y".to_string()),
+                old_group: Some("g".to_string()),
+                new_group: Some("h".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_profile_code() {
+        let code = ProfileMods::from_code("a\n\n  b  \n");
+        assert_eq!(
+            code.mods.iter().map(|m| m.spec.clone()).collect::<Vec<_>>(),
+            specs(&["a", "b"])
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Loading of versioned JSON files, migrating older formats to the current version.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use thiserror::Error;
+use tracing::info;
+
+/// A file format with a top level `version` field, represented by an enum with one variant per
+/// supported version.
+pub trait VersionedFile: Sized {
+    /// File name used in error messages.
+    const NAME: &'static str;
+    /// Every version that can be read, oldest first. The last one is the current version.
+    const VERSIONS: &'static [&'static str];
+
+    /// Deserialize `buf` stored as `version`, where `None` is the legacy unversioned format.
+    fn deserialize_version(version: Option<&str>, buf: &[u8]) -> Result<Self>;
+
+    /// Version of the held data.
+    fn version(&self) -> &'static str;
+
+    /// Migrate to the next version. Never called on the current version.
+    fn migrate(self) -> Self;
+}
+
+#[derive(Error, Debug)]
+pub enum MigrationError {
+    #[error("`{name}` was written by a newer version of drg-mod-integration (format version {version}, newest supported is {latest}), please update")]
+    NewerVersion {
+        name: &'static str,
+        version: String,
+        latest: &'static str,
+    },
+    #[error("`{name}` has unknown format version {version}")]
+    UnknownVersion { name: &'static str, version: String },
+}
+
+/// Returns the `version` field of `buf`, or `None` if it is in the legacy unversioned format.
+pub fn detect_version(buf: &[u8]) -> Result<Option<String>> {
+    let value = serde_json::from_slice::<serde_json::Value>(buf)
+        .context("failed to deserialize into dynamic json value")?;
+    let obj_map = value
+        .as_object()
+        .context("failed to deserialize into object map")?;
+    Ok(match obj_map.get("version") {
+        Some(serde_json::Value::String(version)) => Some(version.clone()),
+        _ => None,
+    })
+}
+
+/// Deserialize `buf` read from `path` and migrate it to the current version. Before migrating, the
+/// original contents are copied next to `path` as `<file name>.<version>.bak`.
+pub fn load<T: VersionedFile>(path: &Path, buf: &[u8]) -> Result<T> {
+    let latest = *T::VERSIONS.last().expect("at least one version");
+
+    let version = detect_version(buf).with_context(|| format!("failed to read `{}`", T::NAME))?;
+    if let Some(version) = &version
+        && !T::VERSIONS.contains(&version.as_str())
+    {
+        let newer = match (
+            semver::Version::parse(version),
+            semver::Version::parse(latest),
+        ) {
+            (Ok(version), Ok(latest)) => version > latest,
+            _ => false,
+        };
+        return Err(if newer {
+            MigrationError::NewerVersion {
+                name: T::NAME,
+                version: version.clone(),
+                latest,
+            }
+        } else {
+            MigrationError::UnknownVersion {
+                name: T::NAME,
+                version: version.clone(),
+            }
+        }
+        .into());
+    }
+
+    let version_name = version.as_deref().unwrap_or("legacy");
+    let mut data = T::deserialize_version(version.as_deref(), buf)
+        .with_context(|| format!("failed to deserialize `{}` as {version_name}", T::NAME))?;
+
+    if version.as_deref() != Some(latest) {
+        let backup = backup_path(path, version_name);
+        std::fs::write(&backup, buf)
+            .with_context(|| format!("failed to back up `{}` to {}", T::NAME, backup.display()))?;
+        info!("backed up `{}` to {}", T::NAME, backup.display());
+
+        while data.version() != latest {
+            let from = data.version();
+            data = data.migrate();
+            if data.version() == from {
+                bail!("`{}` migration from {from} did not advance", T::NAME);
+            }
+            info!("migrated `{}` from {from} to {}", T::NAME, data.version());
+        }
+    }
+
+    Ok(data)
+}
+
+/// Backups [`load`] made of `path` before migrating it, oldest version first.
+pub fn backups<T: VersionedFile>(path: &Path) -> Vec<PathBuf> {
+    std::iter::once("legacy")
+        .chain(T::VERSIONS.iter().copied())
+        .map(|version| backup_path(path, version))
+        .filter(|p| p.exists())
+        .collect()
+}
+
+fn backup_path(path: &Path, version: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{version}.bak"));
+    path.with_file_name(name)
+}
@@ -1,22 +1,35 @@
+pub mod client_only;
 pub mod config;
+pub mod diff;
+pub mod migration;
+pub mod secrets;
 
 use std::{
-    collections::{BTreeMap, HashMap},
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     ops::{Deref, DerefMut},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
 
 use crate::{
     gui::GuiTheme,
-    providers::{ModSpecification, ModStore},
+    mod_lints::{LintConfig, LintConfigFile},
+    providers::{
+        scheduler::{RequestScheduler, SchedulerConfig},
+        ModInfo, ModSpecification, ModStore, ProviderFactory,
+    },
     DRGInstallation, Dirs,
 };
 
 use self::config::ConfigWrapper;
+use self::migration::VersionedFile;
+use self::secrets::{SecretStore, SecretStoreKind, Secrets, SecretsUnavailable};
 
 /// Mod configuration, holds ModSpecification as well as other metadata
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
@@ -34,29 +47,210 @@ fn default_true() -> bool {
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ModGroup {
-    pub mods: Vec<ModConfig>,
+    /// Like a profile, a group can contain other groups. Groups containing only mods are read
+    /// unchanged since mods deserialize as [`ModOrGroup::Individual`].
+    pub mods: Vec<ModOrGroup>,
 }
 
 #[obake::versioned]
 #[obake(version("0.0.0"))]
 #[obake(version("0.1.0"))]
+#[obake(version("0.2.0"))]
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ModProfile {
     #[obake(cfg("0.0.0"))]
     pub mods: Vec<ModConfig>,
 
     /// A profile can contain ordered individual mods mixed with mod groups.
-    #[obake(cfg("0.1.0"))]
+    #[obake(cfg(">=0.1.0"))]
     pub mods: Vec<ModOrGroup>,
+
+    /// Profile whose mods are inherited, loaded after the profile's own mods.
+    #[obake(cfg(">=0.2.0"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// URLs of inherited mods which are left out.
+    #[obake(cfg(">=0.2.0"))]
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub excluded: BTreeSet<String>,
+    /// Whether inherited mods are enabled, by URL, instead of their state in the parent.
+    #[obake(cfg(">=0.2.0"))]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overrides: BTreeMap<String, bool>,
 }
 
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ModOrGroup {
-    Group { group_name: String, enabled: bool },
+    Group {
+        group_name: String,
+        enabled: bool,
+        /// Whether mods inside the group are enabled where it is included, by mod URL, instead
+        /// of the `enabled` flag they have in the group. Overrides on an outer reference take
+        /// precedence over those of groups nested in it.
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        overrides: BTreeMap<String, bool>,
+    },
     Individual(ModConfig),
 }
 
+/// A mod reached by [`walk_mods`].
+#[derive(Debug, Clone)]
+pub struct WalkedMod<'a> {
+    pub config: &'a ModConfig,
+    /// `config.enabled` with the overrides of the group references it was reached through
+    /// applied.
+    pub enabled: bool,
+    /// Whether every group reference it was reached through is enabled.
+    pub groups_enabled: bool,
+    /// Groups it was reached through, outermost first.
+    pub groups: Vec<&'a str>,
+    /// Index of each entry it was reached through, starting with the walked list, see
+    /// [`entry_at_path_mut`].
+    pub path: Vec<usize>,
+    /// Profile it was inherited from when walking a profile, `path` then starts with the list of
+    /// that profile.
+    pub inherited_from: Option<&'a str>,
+}
+
+impl<'a> WalkedMod<'a> {
+    /// Whether the mod is integrated.
+    pub fn is_active(&self) -> bool {
+        self.enabled && self.groups_enabled
+    }
+
+    /// The config with overrides applied.
+    pub fn effective_config(&self) -> Cow<'a, ModConfig> {
+        if self.enabled == self.config.enabled {
+            Cow::Borrowed(self.config)
+        } else {
+            Cow::Owned(ModConfig {
+                enabled: self.enabled,
+                ..self.config.clone()
+            })
+        }
+    }
+}
+
+/// Every mod of `entries` in load order, descending into groups. References to groups which do
+/// not exist or which are already being walked are skipped, see [`ModData::integrity_problems`].
+pub fn walk_mods<'a>(
+    groups: &'a BTreeMap<String, ModGroup>,
+    entries: &'a [ModOrGroup],
+) -> Vec<WalkedMod<'a>> {
+    struct Walk<'a> {
+        groups: Vec<&'a str>,
+        overrides: Vec<&'a BTreeMap<String, bool>>,
+        groups_enabled: bool,
+        path: Vec<usize>,
+    }
+
+    fn walk<'a>(
+        groups: &'a BTreeMap<String, ModGroup>,
+        entries: &'a [ModOrGroup],
+        state: &mut Walk<'a>,
+        walked: &mut Vec<WalkedMod<'a>>,
+    ) {
+        for (index, entry) in entries.iter().enumerate() {
+            state.path.push(index);
+            match entry {
+                ModOrGroup::Individual(config) => walked.push(WalkedMod {
+                    config,
+                    enabled: state
+                        .overrides
+                        .iter()
+                        .find_map(|overrides| overrides.get(&config.spec.url).copied())
+                        .unwrap_or(config.enabled),
+                    groups_enabled: state.groups_enabled,
+                    groups: state.groups.clone(),
+                    path: state.path.clone(),
+                    inherited_from: None,
+                }),
+                ModOrGroup::Group {
+                    group_name,
+                    enabled,
+                    overrides,
+                } => {
+                    if let Some(group) = groups.get(group_name)
+                        && !state.groups.contains(&group_name.as_str())
+                    {
+                        let groups_enabled = state.groups_enabled;
+                        state.groups_enabled &= *enabled;
+                        state.groups.push(group_name);
+                        state.overrides.push(overrides);
+                        walk(groups, &group.mods, state, walked);
+                        state.overrides.pop();
+                        state.groups.pop();
+                        state.groups_enabled = groups_enabled;
+                    }
+                }
+            }
+            state.path.pop();
+        }
+    }
+
+    let mut walked = vec![];
+    walk(
+        groups,
+        entries,
+        &mut Walk {
+            groups: vec![],
+            overrides: vec![],
+            groups_enabled: true,
+            path: vec![],
+        },
+        &mut walked,
+    );
+    walked
+}
+
+/// The entry at a [`WalkedMod::path`] of `entries`, or of a group reference when given the path
+/// of a group reference's entry.
+pub fn entry_at_path_mut<'a>(
+    groups: &'a mut BTreeMap<String, ModGroup>,
+    entries: &'a mut [ModOrGroup],
+    path: &[usize],
+) -> Option<&'a mut ModOrGroup> {
+    let (&last, outer) = path.split_last()?;
+    let mut group_name: Option<String> = None;
+    for &index in outer {
+        let list = match &group_name {
+            None => &*entries,
+            Some(name) => &groups.get(name)?.mods[..],
+        };
+        let ModOrGroup::Group {
+            group_name: name, ..
+        } = list.get(index)?
+        else {
+            return None;
+        };
+        group_name = Some(name.clone());
+    }
+    let list = match group_name {
+        None => entries,
+        Some(name) => &mut groups.get_mut(&name)?.mods[..],
+    };
+    list.get_mut(last)
+}
+
+/// A dangling or cyclic reference between profiles and groups, see
+/// [`ModData::integrity_problems`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityProblem {
+    #[error("active profile `{0}` does not exist")]
+    MissingActiveProfile(String),
+    #[error("{owner} includes group `{group}` which does not exist")]
+    MissingGroup { owner: String, group: String },
+    #[error("group `{group}` includes group `{nested}` which contains `{group}`")]
+    GroupCycle { group: String, nested: String },
+    #[error("profile `{profile}` inherits from profile `{parent}` which does not exist")]
+    MissingParent { profile: String, parent: String },
+    #[error(
+        "profile `{profile}` inherits from profile `{parent}` which inherits from `{profile}`"
+    )]
+    ParentCycle { profile: String, parent: String },
+}
+
 impl From<ModProfile!["0.0.0"]> for ModProfile!["0.1.0"] {
     fn from(_legacy: ModProfile!["0.0.0"]) -> Self {
         // The migration requires `ModData` to handle instead.
@@ -64,9 +258,21 @@ impl From<ModProfile!["0.0.0"]> for ModProfile!["0.1.0"] {
     }
 }
 
+impl From<ModProfile!["0.1.0"]> for ModProfile!["0.2.0"] {
+    fn from(legacy: ModProfile!["0.1.0"]) -> Self {
+        Self {
+            mods: legacy.mods,
+            parent: None,
+            excluded: BTreeSet::new(),
+            overrides: BTreeMap::new(),
+        }
+    }
+}
+
 #[obake::versioned]
 #[obake(version("0.0.0"))]
 #[obake(version("0.1.0"))]
+#[obake(version("0.2.0"))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModData {
     pub active_profile: String,
@@ -74,11 +280,97 @@ pub struct ModData {
     pub profiles: BTreeMap<String, ModProfile!["0.0.0"]>,
     #[obake(cfg("0.1.0"))]
     pub profiles: BTreeMap<String, ModProfile!["0.1.0"]>,
-    #[obake(cfg("0.1.0"))]
+    #[obake(cfg("0.2.0"))]
+    pub profiles: BTreeMap<String, ModProfile!["0.2.0"]>,
+    #[obake(cfg(">=0.1.0"))]
     pub groups: BTreeMap<String, ModGroup>,
 }
 
-impl ModData!["0.1.0"] {
+impl ModData!["0.2.0"] {
+    /// Mods of `profile` in load order, see [`walk_mods`]. The profile's own mods come first,
+    /// followed by those inherited from its parent, then from the parent's parent and so on.
+    /// Inherited mods are left out if excluded or already contained by a profile closer in the
+    /// chain, and the overrides of the closest profile setting one apply to them.
+    pub fn walk_mods(&self, profile: &str) -> Vec<WalkedMod<'_>> {
+        let mut walked = vec![];
+        let mut chain: Vec<&str> = vec![];
+        let mut contained = HashSet::new();
+        let mut excluded = BTreeSet::new();
+        let mut overrides = vec![];
+
+        let mut next = self.profiles.get_key_value(profile);
+        while let Some((name, profile)) = next {
+            if chain.contains(&name.as_str()) {
+                break;
+            }
+            let inherited_from = (!chain.is_empty()).then_some(name.as_str());
+            let mut urls = vec![];
+            for mut m in walk_mods(&self.groups, &profile.mods) {
+                let config = m.config;
+                let url = config.spec.url.as_str();
+                urls.push(url);
+                if contained.contains(url) || excluded.contains(url) {
+                    continue;
+                }
+                if let Some(enabled) = overrides
+                    .iter()
+                    .find_map(|overrides: &&BTreeMap<String, bool>| overrides.get(url).copied())
+                {
+                    m.enabled = enabled;
+                }
+                m.inherited_from = inherited_from;
+                walked.push(m);
+            }
+
+            contained.extend(urls);
+            excluded.extend(profile.excluded.iter().map(String::as_str));
+            overrides.push(&profile.overrides);
+            chain.push(name);
+            next = profile
+                .parent
+                .as_ref()
+                .and_then(|parent| self.profiles.get_key_value(parent));
+        }
+        walked
+    }
+
+    /// Whether following the parents of profile `from` reaches profile `to`, including when they
+    /// are the same.
+    pub fn profile_reaches(&self, from: &str, to: &str) -> bool {
+        let mut visited = BTreeSet::new();
+        let mut next = Some(from);
+        while let Some(name) = next {
+            if name == to {
+                return true;
+            }
+            if !visited.insert(name) {
+                break;
+            }
+            next = self
+                .profiles
+                .get(name)
+                .and_then(|profile| profile.parent.as_deref());
+        }
+        false
+    }
+
+    /// Make `profile` inherit from `parent`, or stop inheriting if `None`.
+    pub fn set_parent(&mut self, profile: &str, parent: Option<&str>) -> Result<()> {
+        self.profile_mut(profile)?;
+        if let Some(parent) = parent {
+            if !self.profiles.contains_key(parent) {
+                bail!("profile `{parent}` does not exist");
+            }
+            if self.profile_reaches(parent, profile) {
+                bail!("profile `{profile}` cannot inherit from `{parent}` which inherits from it");
+            }
+        }
+        self.profile_mut(profile)?.parent = parent.map(str::to_owned);
+        Ok(())
+    }
+
+    /// Calls `f` with the mods for which `p` holds, skipping those in groups for which `g` does
+    /// not hold. Mods are passed with the overrides of their group references applied.
     pub fn for_each_mod_predicate<
         F: FnMut(&ModConfig),
         G: FnMut(bool /* mod group enabled? */) -> bool,
@@ -90,29 +382,19 @@ impl ModData!["0.1.0"] {
         mut g: G,
         mut p: P,
     ) {
-        for mod_or_group in &self.profiles.get(profile).unwrap().mods {
-            match mod_or_group {
-                ModOrGroup::Group {
-                    group_name,
-                    enabled,
-                } => {
-                    if g(*enabled) {
-                        for mc in &self.groups.get(group_name).unwrap().mods {
-                            if p(mc) {
-                                f(mc);
-                            }
-                        }
-                    }
-                }
-                ModOrGroup::Individual(mc) => {
-                    if p(mc) {
-                        f(mc);
-                    }
-                }
+        for walked in self.walk_mods(profile) {
+            if !walked.groups.is_empty() && !g(walked.groups_enabled) {
+                continue;
+            }
+            let mc = walked.effective_config();
+            if p(&*mc) {
+                f(&*mc);
             }
         }
     }
 
+    /// Like [`Self::for_each_mod_predicate`], but mods are passed as stored in the profile or
+    /// group without overrides applied. Inherited mods belong to the parent and are not visited.
     pub fn for_each_mod_predicate_mut<
         F: FnMut(&mut ModConfig),
         G: FnMut(bool /* mod group enabled? */) -> bool,
@@ -124,25 +406,30 @@ impl ModData!["0.1.0"] {
         mut g: G,
         mut p: P,
     ) {
-        for mod_or_group in &mut self.profiles.get_mut(profile).unwrap().mods {
-            match mod_or_group {
-                ModOrGroup::Group {
-                    group_name,
-                    enabled,
-                } => {
-                    if g(*enabled) {
-                        for mc in &mut self.groups.get_mut(group_name).unwrap().mods {
-                            if p(mc) {
-                                f(mc);
-                            }
-                        }
-                    }
-                }
-                ModOrGroup::Individual(mc) => {
-                    if p(mc) {
-                        f(mc);
-                    }
-                }
+        let Self {
+            profiles, groups, ..
+        } = self;
+        let Some(profile) = profiles.get_mut(profile) else {
+            return;
+        };
+        let walked = walk_mods(groups, &profile.mods)
+            .into_iter()
+            .map(|walked| {
+                (
+                    walked.path,
+                    (!walked.groups.is_empty()).then_some(walked.groups_enabled),
+                )
+            })
+            .collect::<Vec<_>>();
+        for (path, groups_enabled) in walked {
+            if groups_enabled.is_some_and(|enabled| !g(enabled)) {
+                continue;
+            }
+            if let Some(ModOrGroup::Individual(mc)) =
+                entry_at_path_mut(groups, &mut profile.mods, &path)
+                && p(mc)
+            {
+                f(mc);
             }
         }
     }
@@ -164,56 +451,144 @@ impl ModData!["0.1.0"] {
         profile: &str,
         mut f: F,
     ) -> bool {
-        self.profiles.get(profile).unwrap().mods.iter().any(|m| {
-            let f = &mut f;
-            match m {
-                ModOrGroup::Individual(mc) => f(mc, None),
-                ModOrGroup::Group {
-                    group_name,
-                    enabled,
-                } => self
-                    .groups
-                    .get(group_name)
-                    .unwrap()
-                    .mods
+        self.walk_mods(profile).iter().any(|walked| {
+            f(
+                &*walked.effective_config(),
+                (!walked.groups.is_empty()).then_some(walked.groups_enabled),
+            )
+        })
+    }
+
+    /// Whether walking group `from` reaches group `to`, including when they are the same.
+    pub fn group_reaches(&self, from: &str, to: &str) -> bool {
+        let mut stack = vec![from];
+        let mut visited = BTreeSet::new();
+        while let Some(name) = stack.pop() {
+            if name == to {
+                return true;
+            }
+            if !visited.insert(name) {
+                continue;
+            }
+            if let Some(group) = self.groups.get(name) {
+                stack.extend(group.mods.iter().filter_map(|m| match m {
+                    ModOrGroup::Group { group_name, .. } => Some(group_name.as_str()),
+                    ModOrGroup::Individual(_) => None,
+                }));
+            }
+        }
+        false
+    }
+
+    /// References to groups and parents which do not exist, groups which contain themselves,
+    /// profiles which inherit from themselves and a missing active profile. Such data can only be
+    /// produced by editing `mod_data.json` by hand.
+    pub fn integrity_problems(&self) -> Vec<IntegrityProblem> {
+        let mut problems = vec![];
+        if !self.profiles.contains_key(&self.active_profile) {
+            problems.push(IntegrityProblem::MissingActiveProfile(
+                self.active_profile.clone(),
+            ));
+        }
+        for (name, profile) in &self.profiles {
+            let Some(parent) = &profile.parent else {
+                continue;
+            };
+            if !self.profiles.contains_key(parent) {
+                problems.push(IntegrityProblem::MissingParent {
+                    profile: name.clone(),
+                    parent: parent.clone(),
+                });
+            } else if self.profile_reaches(parent, name) {
+                problems.push(IntegrityProblem::ParentCycle {
+                    profile: name.clone(),
+                    parent: parent.clone(),
+                });
+            }
+        }
+        let lists = self
+            .profiles
+            .iter()
+            .map(|(name, profile)| (format!("profile `{name}`"), None::<&String>, &profile.mods))
+            .chain(
+                self.groups
                     .iter()
-                    .any(|mc| f(mc, Some(*enabled))),
+                    .map(|(name, group)| (format!("group `{name}`"), Some(name), &group.mods)),
+            );
+        for (owner, owner_group, mods) in lists {
+            for m in mods {
+                let ModOrGroup::Group { group_name, .. } = m else {
+                    continue;
+                };
+                if !self.groups.contains_key(group_name) {
+                    problems.push(IntegrityProblem::MissingGroup {
+                        owner: owner.clone(),
+                        group: group_name.clone(),
+                    });
+                } else if let Some(owner_group) = owner_group
+                    && self.group_reaches(group_name, owner_group)
+                {
+                    problems.push(IntegrityProblem::GroupCycle {
+                        group: owner_group.clone(),
+                        nested: group_name.clone(),
+                    });
+                }
             }
-        })
+        }
+        problems
     }
 
-    pub fn any_mod_mut<
-        F: FnMut(&mut ModConfig, Option<&mut bool> /* mod group enabled? */) -> bool,
-    >(
-        &mut self,
-        profile: &str,
-        mut f: F,
-    ) -> bool {
-        self.profiles
-            .get_mut(profile)
-            .unwrap()
-            .mods
-            .iter_mut()
-            .any(|m| {
-                let f = &mut f;
-                match m {
-                    ModOrGroup::Individual(mc) => f(mc, None),
-                    ModOrGroup::Group {
-                        group_name,
-                        enabled,
-                    } => self
-                        .groups
-                        .get_mut(group_name)
-                        .unwrap()
-                        .mods
-                        .iter_mut()
-                        .any(|mc| f(mc, Some(enabled))),
+    /// Fix the problems found by [`Self::integrity_problems`] by dropping the offending group
+    /// references and parents, breaking each cycle at the first reference found to close it, and
+    /// activating the first profile. Returns the problems found.
+    pub fn repair(&mut self) -> Vec<IntegrityProblem> {
+        let problems = self.integrity_problems();
+        if problems.is_empty() {
+            return problems;
+        }
+
+        if !self.profiles.contains_key(&self.active_profile) {
+            match self.profiles.keys().next() {
+                Some(name) => self.active_profile = name.clone(),
+                None => {
+                    self.profiles
+                        .insert(self.active_profile.clone(), Default::default());
                 }
-            })
+            }
+        }
+        for profile in self.profiles.values_mut() {
+            profile.mods.retain(|m| match m {
+                ModOrGroup::Group { group_name, .. } => self.groups.contains_key(group_name),
+                ModOrGroup::Individual(_) => true,
+            });
+        }
+        let names = self.profiles.keys().cloned().collect::<Vec<_>>();
+        for name in names {
+            let parent = self.profiles[&name].parent.take();
+            // without its parent, a profile is reached again only through the parent being
+            // checked
+            self.profiles.get_mut(&name).unwrap().parent = parent.filter(|parent| {
+                self.profiles.contains_key(parent) && !self.profile_reaches(parent, &name)
+            });
+        }
+        let names = self.groups.keys().cloned().collect::<Vec<_>>();
+        for name in names {
+            let mut mods = std::mem::take(&mut self.groups.get_mut(&name).unwrap().mods);
+            // with its mods taken out, a group is reached again only through the reference
+            // being checked
+            mods.retain(|m| match m {
+                ModOrGroup::Group { group_name, .. } => {
+                    self.groups.contains_key(group_name) && !self.group_reaches(group_name, &name)
+                }
+                ModOrGroup::Individual(_) => true,
+            });
+            self.groups.get_mut(&name).unwrap().mods = mods;
+        }
+        problems
     }
 }
 
-impl Default for ModData!["0.1.0"] {
+impl Default for ModData!["0.2.0"] {
     fn default() -> Self {
         Self {
             active_profile: "default".to_string(),
@@ -249,6 +624,20 @@ impl From<ModData!["0.0.0"]> for ModData!["0.1.0"] {
     }
 }
 
+impl From<ModData!["0.1.0"]> for ModData!["0.2.0"] {
+    fn from(legacy: ModData!["0.1.0"]) -> Self {
+        Self {
+            active_profile: legacy.active_profile,
+            profiles: legacy
+                .profiles
+                .into_iter()
+                .map(|(name, profile)| (name, profile.into()))
+                .collect(),
+            groups: legacy.groups,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "version")]
 pub enum VersionAnnotatedModData {
@@ -256,13 +645,38 @@ pub enum VersionAnnotatedModData {
     V0_0_0(ModData!["0.0.0"]),
     #[serde(rename = "0.1.0")]
     V0_1_0(ModData!["0.1.0"]),
+    #[serde(rename = "0.2.0")]
+    V0_2_0(ModData!["0.2.0"]),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum MaybeVersionedModData {
-    Versioned(VersionAnnotatedModData),
-    Legacy(ModData!["0.0.0"]),
+impl VersionedFile for VersionAnnotatedModData {
+    const NAME: &'static str = "mod_data.json";
+    const VERSIONS: &'static [&'static str] = &["0.0.0", "0.1.0", "0.2.0"];
+
+    fn deserialize_version(version: Option<&str>, buf: &[u8]) -> Result<Self> {
+        Ok(match version {
+            None | Some("0.0.0") => VersionAnnotatedModData::V0_0_0(serde_json::from_slice(buf)?),
+            Some("0.1.0") => VersionAnnotatedModData::V0_1_0(serde_json::from_slice(buf)?),
+            Some("0.2.0") => VersionAnnotatedModData::V0_2_0(serde_json::from_slice(buf)?),
+            Some(version) => bail!("unsupported mod data version {version}"),
+        })
+    }
+
+    fn version(&self) -> &'static str {
+        match self {
+            VersionAnnotatedModData::V0_0_0(_) => "0.0.0",
+            VersionAnnotatedModData::V0_1_0(_) => "0.1.0",
+            VersionAnnotatedModData::V0_2_0(_) => "0.2.0",
+        }
+    }
+
+    fn migrate(self) -> Self {
+        match self {
+            VersionAnnotatedModData::V0_0_0(md) => VersionAnnotatedModData::V0_1_0(md.into()),
+            VersionAnnotatedModData::V0_1_0(md) => VersionAnnotatedModData::V0_2_0(md.into()),
+            VersionAnnotatedModData::V0_2_0(_) => self,
+        }
+    }
 }
 
 impl Default for ModData!["0.0.0"] {
@@ -276,25 +690,21 @@ impl Default for ModData!["0.0.0"] {
     }
 }
 
-impl Default for MaybeVersionedModData {
-    fn default() -> Self {
-        MaybeVersionedModData::Versioned(Default::default())
-    }
-}
-
 impl Default for VersionAnnotatedModData {
     fn default() -> Self {
-        VersionAnnotatedModData::V0_1_0(Default::default())
+        VersionAnnotatedModData::V0_2_0(Default::default())
     }
 }
 
 impl Deref for VersionAnnotatedModData {
-    type Target = ModData!["0.1.0"];
+    type Target = ModData!["0.2.0"];
 
     fn deref(&self) -> &Self::Target {
         match self {
-            VersionAnnotatedModData::V0_0_0(_) => unreachable!(),
-            VersionAnnotatedModData::V0_1_0(md) => md,
+            VersionAnnotatedModData::V0_0_0(_) | VersionAnnotatedModData::V0_1_0(_) => {
+                unreachable!()
+            }
+            VersionAnnotatedModData::V0_2_0(md) => md,
         }
     }
 }
@@ -302,71 +712,352 @@ impl Deref for VersionAnnotatedModData {
 impl DerefMut for VersionAnnotatedModData {
     fn deref_mut(&mut self) -> &mut Self::Target {
         match self {
-            VersionAnnotatedModData::V0_0_0(_) => unreachable!(),
-            VersionAnnotatedModData::V0_1_0(md) => md,
+            VersionAnnotatedModData::V0_0_0(_) | VersionAnnotatedModData::V0_1_0(_) => {
+                unreachable!()
+            }
+            VersionAnnotatedModData::V0_2_0(md) => md,
         }
     }
 }
 
-impl ModData!["0.1.0"] {
-    pub fn get_active_profile(&self) -> &ModProfile!["0.1.0"] {
+impl ModData!["0.2.0"] {
+    pub fn get_active_profile(&self) -> &ModProfile!["0.2.0"] {
         &self.profiles[&self.active_profile]
     }
 
-    pub fn get_active_profile_mut(&mut self) -> &mut ModProfile!["0.1.0"] {
+    pub fn get_active_profile_mut(&mut self) -> &mut ModProfile!["0.2.0"] {
         self.profiles.get_mut(&self.active_profile).unwrap()
     }
 
     pub fn remove_active_profile(&mut self) {
-        self.profiles.remove(&self.active_profile);
+        let name = self.active_profile.clone();
+        self.detach_profile(&name);
+        self.profiles.remove(&name);
         self.active_profile = self.profiles.keys().next().unwrap().to_string();
     }
+
+    /// Make the profiles inheriting from `name` inherit from its parent instead.
+    fn detach_profile(&mut self, name: &str) {
+        let parent = self.profiles.get(name).and_then(|p| p.parent.clone());
+        for profile in self.profiles.values_mut() {
+            if profile.parent.as_deref() == Some(name) {
+                profile.parent = parent.clone();
+            }
+        }
+    }
+
+    pub fn profile_mut(&mut self, name: &str) -> Result<&mut ModProfile!["0.2.0"]> {
+        self.profiles
+            .get_mut(name)
+            .with_context(|| format!("profile `{name}` does not exist"))
+    }
+
+    pub fn group_mut(&mut self, name: &str) -> Result<&mut ModGroup> {
+        self.groups
+            .get_mut(name)
+            .with_context(|| format!("group `{name}` does not exist"))
+    }
+
+    pub fn add_profile(&mut self, name: &str) -> Result<()> {
+        if self.profiles.contains_key(name) {
+            bail!("profile `{name}` already exists");
+        }
+        self.profiles.insert(name.to_owned(), Default::default());
+        Ok(())
+    }
+
+    /// Remove a profile. The last remaining profile cannot be removed. If the active profile is
+    /// removed, the first remaining profile becomes active. Profiles inheriting from it inherit
+    /// from its parent instead.
+    pub fn remove_profile(&mut self, name: &str) -> Result<()> {
+        self.profile_mut(name)?;
+        if self.profiles.len() == 1 {
+            bail!("cannot remove the only profile");
+        }
+        self.detach_profile(name);
+        self.profiles.remove(name);
+        if self.active_profile == name {
+            self.active_profile = self.profiles.keys().next().unwrap().to_string();
+        }
+        Ok(())
+    }
+
+    pub fn rename_profile(&mut self, name: &str, new_name: &str) -> Result<()> {
+        self.duplicate_profile(name, new_name)?;
+        self.profiles.remove(name);
+        if self.active_profile == name {
+            self.active_profile = new_name.to_owned();
+        }
+        for profile in self.profiles.values_mut() {
+            if profile.parent.as_deref() == Some(name) {
+                profile.parent = Some(new_name.to_owned());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn duplicate_profile(&mut self, name: &str, new_name: &str) -> Result<()> {
+        let profile = self.profile_mut(name)?.clone();
+        if self.profiles.contains_key(new_name) {
+            bail!("profile `{new_name}` already exists");
+        }
+        self.profiles.insert(new_name.to_owned(), profile);
+        Ok(())
+    }
+
+    pub fn set_active_profile(&mut self, name: &str) -> Result<()> {
+        self.profile_mut(name)?;
+        self.active_profile = name.to_owned();
+        Ok(())
+    }
+
+    pub fn add_group(&mut self, name: &str) -> Result<()> {
+        if self.groups.contains_key(name) {
+            bail!("group `{name}` already exists");
+        }
+        self.groups.insert(name.to_owned(), Default::default());
+        Ok(())
+    }
+
+    /// Every list of mods, those of the profiles and those of the groups.
+    fn mod_lists_mut(&mut self) -> impl Iterator<Item = &mut Vec<ModOrGroup>> + '_ {
+        self.profiles
+            .values_mut()
+            .map(|profile| &mut profile.mods)
+            .chain(self.groups.values_mut().map(|group| &mut group.mods))
+    }
+
+    /// Remove a group along with every reference to it from profiles and other groups.
+    pub fn remove_group(&mut self, name: &str) -> Result<()> {
+        self.groups
+            .remove(name)
+            .with_context(|| format!("group `{name}` does not exist"))?;
+        for mods in self.mod_lists_mut() {
+            mods.retain(
+                |m| !matches!(m, ModOrGroup::Group { group_name, .. } if group_name == name),
+            );
+        }
+        Ok(())
+    }
+
+    /// Rename a group and update every reference to it from profiles and other groups.
+    pub fn rename_group(&mut self, name: &str, new_name: &str) -> Result<()> {
+        if self.groups.contains_key(new_name) {
+            bail!("group `{new_name}` already exists");
+        }
+        let group = self
+            .groups
+            .remove(name)
+            .with_context(|| format!("group `{name}` does not exist"))?;
+        self.groups.insert(new_name.to_owned(), group);
+        for mods in self.mod_lists_mut() {
+            for m in mods {
+                if let ModOrGroup::Group { group_name, .. } = m
+                    && group_name == name
+                {
+                    *group_name = new_name.to_owned();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a freshly resolved mod to the top of `profile`. If it was only pulled in as a
+    /// dependency and the profile already contains a mod satisfying it, that mod (and the groups
+    /// containing it) is enabled instead. A mod inside a group or inherited from a parent is
+    /// enabled with an override in `profile` so other profiles are unaffected. Group references
+    /// nested in groups are shared by every profile including the outer group, so a mod behind a
+    /// disabled nested reference is added to the profile instead.
+    pub fn add_resolved_mod(
+        &mut self,
+        profile: &str,
+        resolved_spec: &ModSpecification,
+        info: &ModInfo,
+        is_dependency: bool,
+    ) {
+        let satisfying = is_dependency
+            .then(|| {
+                self.walk_mods(profile)
+                    .into_iter()
+                    .find(|walked| walked.config.spec.satisfies_dependency(resolved_spec))
+                    .map(|walked| {
+                        (
+                            walked.path,
+                            walked.config.spec.url.clone(),
+                            walked.inherited_from.is_some(),
+                            walked.groups_enabled,
+                        )
+                    })
+            })
+            .flatten();
+        // an inherited mod in a disabled group cannot be enabled without changing the parent, so
+        // the mod is added to the profile instead
+        if let Some((_, url, true, true)) = &satisfying {
+            let profile = self.profiles.get_mut(profile).unwrap();
+            profile.overrides.insert(url.clone(), true);
+            return;
+        }
+        if let Some((path, url, false, _)) = satisfying {
+            let Self {
+                profiles, groups, ..
+            } = self;
+            let mods = &mut profiles.get_mut(profile).unwrap().mods;
+            let nested_enabled = (2..path.len()).all(|len| {
+                matches!(
+                    entry_at_path_mut(groups, mods, &path[..len]),
+                    Some(ModOrGroup::Group { enabled: true, .. })
+                )
+            });
+            if nested_enabled {
+                match mods.get_mut(path[0]) {
+                    Some(ModOrGroup::Group {
+                        enabled, overrides, ..
+                    }) => {
+                        *enabled = true;
+                        overrides.insert(url, true);
+                    }
+                    Some(ModOrGroup::Individual(mc)) => mc.enabled = true,
+                    None => {}
+                }
+                return;
+            }
+        }
+        self.profiles.get_mut(profile).unwrap().mods.insert(
+            0,
+            ModOrGroup::Individual(ModConfig {
+                spec: info.spec.clone(),
+                required: info.suggested_require,
+                enabled: true,
+            }),
+        );
+    }
 }
 
 #[obake::versioned]
 #[obake(version("0.0.0"))]
+#[obake(version("0.1.0"))]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub provider_parameters: HashMap<String, HashMap<String, String>>,
     pub drg_pak_path: Option<PathBuf>,
     pub gui_theme: Option<GuiTheme>,
+    /// Refuse to install mods whose mod.io version tags do not include the installed game version.
+    #[obake(cfg(">=0.1.0"))]
+    #[serde(default)]
+    pub block_incompatible_mods: bool,
+    /// Refuse to install profiles for which a deny-level lint finds problems.
+    #[obake(cfg(">=0.1.0"))]
+    #[serde(default)]
+    pub block_denied_lints: bool,
+    /// Merge data tables modified by several mods instead of using only the first.
+    #[obake(cfg(">=0.1.0"))]
+    #[serde(default)]
+    pub merge_tables: bool,
+    /// Concurrency, rate limit and retry settings shared by all network requests.
+    #[obake(cfg(">=0.1.0"))]
+    #[serde(default)]
+    pub request_scheduler: SchedulerConfig,
+    /// Where secret provider parameters such as OAuth tokens are kept.
+    #[obake(cfg(">=0.1.0"))]
+    #[serde(default)]
+    pub secret_store: SecretStoreKind,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(tag = "version")]
-pub enum VersionAnnotatedConfig {
-    #[serde(rename = "0.0.0")]
+impl From<Config!["0.0.0"]> for Config!["0.1.0"] {
+    fn from(legacy: Config!["0.0.0"]) -> Self {
+        Self {
+            provider_parameters: legacy.provider_parameters,
+            drg_pak_path: legacy.drg_pak_path,
+            gui_theme: legacy.gui_theme,
+            block_incompatible_mods: false,
+            block_denied_lints: false,
+            merge_tables: false,
+            request_scheduler: Default::default(),
+            secret_store: Default::default(),
+        }
+    }
+}
+
+impl Default for Config!["0.1.0"] {
+    fn default() -> Self {
+        Self {
+            provider_parameters: Default::default(),
+            drg_pak_path: DRGInstallation::find()
+                .as_ref()
+                .map(DRGInstallation::main_pak),
+            gui_theme: None,
+            block_incompatible_mods: false,
+            block_denied_lints: false,
+            merge_tables: false,
+            request_scheduler: Default::default(),
+            secret_store: Default::default(),
+        }
+    }
+}
+
+/// Every readable version of `config.json`, migrated to [`VersionAnnotatedConfig`] on load.
+#[derive(Debug)]
+enum ConfigFile {
     V0_0_0(Config!["0.0.0"]),
-    #[serde(other)]
-    Unsupported,
+    V0_1_0(Config!["0.1.0"]),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum MaybeVersionedConfig {
-    Versioned(VersionAnnotatedConfig),
-    Legacy(Config!["0.0.0"]),
+impl VersionedFile for ConfigFile {
+    const NAME: &'static str = "config.json";
+    const VERSIONS: &'static [&'static str] = &["0.0.0", "0.1.0"];
+
+    fn deserialize_version(version: Option<&str>, buf: &[u8]) -> Result<Self> {
+        Ok(match version {
+            None | Some("0.0.0") => ConfigFile::V0_0_0(serde_json::from_slice(buf)?),
+            Some("0.1.0") => ConfigFile::V0_1_0(serde_json::from_slice(buf)?),
+            Some(version) => bail!("unsupported config version {version}"),
+        })
+    }
+
+    fn version(&self) -> &'static str {
+        match self {
+            ConfigFile::V0_0_0(_) => "0.0.0",
+            ConfigFile::V0_1_0(_) => "0.1.0",
+        }
+    }
+
+    fn migrate(self) -> Self {
+        match self {
+            ConfigFile::V0_0_0(cfg) => ConfigFile::V0_1_0(cfg.into()),
+            ConfigFile::V0_1_0(_) => self,
+        }
+    }
 }
 
-impl Default for MaybeVersionedConfig {
-    fn default() -> Self {
-        MaybeVersionedConfig::Versioned(Default::default())
+impl From<ConfigFile> for VersionAnnotatedConfig {
+    fn from(file: ConfigFile) -> Self {
+        match file {
+            ConfigFile::V0_0_0(cfg) => VersionAnnotatedConfig::V0_1_0(cfg.into()),
+            ConfigFile::V0_1_0(cfg) => VersionAnnotatedConfig::V0_1_0(cfg),
+        }
     }
 }
 
+/// `config.json` as held in memory and written, always the current version.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "version")]
+pub enum VersionAnnotatedConfig {
+    #[serde(rename = "0.1.0")]
+    V0_1_0(Config!["0.1.0"]),
+}
+
 impl Default for VersionAnnotatedConfig {
     fn default() -> Self {
-        VersionAnnotatedConfig::V0_0_0(Default::default())
+        VersionAnnotatedConfig::V0_1_0(Default::default())
     }
 }
 
 impl Deref for VersionAnnotatedConfig {
-    type Target = Config!["0.0.0"];
+    type Target = Config!["0.1.0"];
 
     fn deref(&self) -> &Self::Target {
         match self {
-            VersionAnnotatedConfig::V0_0_0(cfg) => cfg,
-            VersionAnnotatedConfig::Unsupported => unreachable!(),
+            VersionAnnotatedConfig::V0_1_0(cfg) => cfg,
         }
     }
 }
@@ -374,20 +1065,7 @@ impl Deref for VersionAnnotatedConfig {
 impl DerefMut for VersionAnnotatedConfig {
     fn deref_mut(&mut self) -> &mut Self::Target {
         match self {
-            VersionAnnotatedConfig::V0_0_0(cfg) => cfg,
-            VersionAnnotatedConfig::Unsupported => unreachable!(),
-        }
-    }
-}
-
-impl Default for Config!["0.0.0"] {
-    fn default() -> Self {
-        Self {
-            provider_parameters: Default::default(),
-            drg_pak_path: DRGInstallation::find()
-                .as_ref()
-                .map(DRGInstallation::main_pak),
-            gui_theme: None,
+            VersionAnnotatedConfig::V0_1_0(cfg) => cfg,
         }
     }
 }
@@ -397,99 +1075,305 @@ pub struct State {
     pub config: ConfigWrapper<VersionAnnotatedConfig>,
     pub mod_data: ConfigWrapper<VersionAnnotatedModData>,
     pub store: Arc<ModStore>,
+    secret_store: Box<dyn SecretStore>,
+    secrets: Secrets,
+}
+
+/// Returned by [`State::init`] when `config.json` or `mod_data.json` exists but cannot be parsed.
+#[derive(Error, Debug)]
+#[error("`{}` is corrupt: {reason}", path.display())]
+pub struct CorruptFile {
+    pub path: PathBuf,
+    pub reason: String,
+    /// Newest backup that can be loaded successfully, if any.
+    pub backup: Option<PathBuf>,
+}
+
+impl CorruptFile {
+    /// Move the corrupt file aside to `<file name>.corrupt` and replace it with the backup.
+    pub fn restore(&self) -> Result<()> {
+        let backup = self.backup.as_ref().context("no valid backup available")?;
+        let mut corrupt_name = self.path.file_name().unwrap_or_default().to_os_string();
+        corrupt_name.push(".corrupt");
+        let corrupt_path = self.path.with_file_name(corrupt_name);
+        std::fs::rename(&self.path, &corrupt_path).with_context(|| {
+            format!("failed to move corrupt file to {}", corrupt_path.display())
+        })?;
+        std::fs::copy(backup, &self.path)
+            .with_context(|| format!("failed to restore backup {}", backup.display()))?;
+        info!(
+            "restored {} from {}, corrupt file kept at {}",
+            self.path.display(),
+            backup.display(),
+            corrupt_path.display()
+        );
+        Ok(())
+    }
 }
 
 impl State {
     pub fn init(dirs: Dirs) -> Result<Self> {
+        Self::init_with_recovery(dirs, |_| false)
+    }
+
+    /// Like [`State::init`], but if a file is corrupt and has a valid backup `restore` is asked
+    /// whether to restore it. If it declines, a [`CorruptFile`] error is returned.
+    pub fn init_with_recovery(
+        dirs: Dirs,
+        mut restore: impl FnMut(&CorruptFile) -> bool,
+    ) -> Result<Self> {
         let config_path = dirs.config_dir.join("config.json");
 
-        let config = read_config_or_default(&config_path)?;
-        let config = ConfigWrapper::<VersionAnnotatedConfig>::new(&config_path, config);
-        config.save().unwrap();
+        let config = load_or_recover(&config_path, read_config_or_default, &mut restore)?;
+        let mut config =
+            ConfigWrapper::<VersionAnnotatedConfig>::with_backups(&config_path, config);
+        config.save()?;
+
+        let secret_store = config.secret_store.open(&dirs)?;
+        let mut secrets = match secret_store.load() {
+            Ok(secrets) => secrets,
+            Err(e) if e.is::<SecretsUnavailable>() => {
+                warn!("{e}, continuing without secret provider parameters");
+                Default::default()
+            }
+            Err(e) => return Err(e),
+        };
+        let mut parameters = config.provider_parameters.clone();
+        let mut moved = secrets.clone();
+        if move_secrets(&mut parameters, &mut moved) {
+            match secret_store.save(&moved) {
+                Ok(()) => {
+                    secrets = moved;
+                    config.provider_parameters = parameters;
+                    config.save()?;
+                    // backups taken before the move still hold the secrets in plain text
+                    let backups = config::backups(&config_path)
+                        .into_iter()
+                        .chain(migration::backups::<ConfigFile>(&config_path));
+                    for backup in backups {
+                        scrub_backup(&backup)
+                            .with_context(|| format!("failed to scrub {}", backup.display()))?;
+                    }
+                    info!("moved secret provider parameters from config.json to the secret store");
+                }
+                Err(e) if e.is::<SecretsUnavailable>() => {
+                    warn!("{e}, keeping secret provider parameters in config.json for now");
+                }
+                Err(e) => return Err(e),
+            }
+        }
 
         let legacy_mod_profiles_path = dirs.config_dir.join("profiles.json");
         let mod_data_path = dirs.config_dir.join("mod_data.json");
-        let mod_data = read_mod_data_or_default(&mod_data_path, legacy_mod_profiles_path)?;
-        let mod_data = ConfigWrapper::<VersionAnnotatedModData>::new(mod_data_path, mod_data);
-        mod_data.save().unwrap();
-
-        let store = ModStore::new(&dirs.cache_dir, &config.provider_parameters)?.into();
+        let mod_data = load_or_recover(
+            &mod_data_path,
+            |path| read_mod_data_or_default(path, legacy_mod_profiles_path.clone()),
+            &mut restore,
+        )?;
+        let mut mod_data =
+            ConfigWrapper::<VersionAnnotatedModData>::with_backups(mod_data_path, mod_data);
+        for problem in mod_data.repair() {
+            warn!("repaired mod data: {problem}");
+        }
+        mod_data.save()?;
+
+        RequestScheduler::global().configure(&config.request_scheduler);
+        let mut parameters = config.provider_parameters.clone();
+        for (provider, values) in &secrets {
+            parameters
+                .entry(provider.clone())
+                .or_default()
+                .extend(values.clone());
+        }
+        let store = ModStore::new(&dirs.cache_dir, &parameters)?.into();
 
         Ok(Self {
             dirs,
             config,
             mod_data,
             store,
+            secret_store,
+            secrets,
+        })
+    }
+
+    /// Lint config from `lints.json` in the config directory, with the overrides for `profile`
+    /// applied if given.
+    pub fn lint_config(&self, profile: Option<&str>) -> Result<LintConfig> {
+        let file = LintConfigFile::read(&self.dirs.config_dir.join("lints.json"))?;
+        Ok(match profile {
+            Some(profile) => file.for_profile(profile),
+            None => file.default,
         })
     }
+
+    /// Stored parameters for a provider, including those kept in the secret store.
+    pub fn provider_parameters(&self, provider_id: &str) -> HashMap<String, String> {
+        let mut parameters = self
+            .config
+            .provider_parameters
+            .get(provider_id)
+            .cloned()
+            .unwrap_or_default();
+        if let Some(secrets) = self.secrets.get(provider_id) {
+            parameters.extend(secrets.clone());
+        }
+        parameters
+    }
+
+    /// Replace the stored parameters for a provider. Parameters marked secret are written to the
+    /// secret store instead of `config.json`.
+    pub fn set_provider_parameters(
+        &mut self,
+        factory: &ProviderFactory,
+        parameters: HashMap<String, String>,
+    ) -> Result<()> {
+        let (secret, plain): (HashMap<_, _>, HashMap<_, _>) =
+            parameters.into_iter().partition(|(id, _)| {
+                factory
+                    .parameters
+                    .iter()
+                    .any(|p| p.secret && p.id == id.as_str())
+            });
+        let id = factory.id.to_owned();
+        if secret.is_empty() {
+            self.secrets.remove(&id);
+        } else {
+            self.secrets.insert(id.clone(), secret);
+        }
+        self.secret_store.save(&self.secrets)?;
+        if plain.is_empty() {
+            self.config.provider_parameters.remove(&id);
+        } else {
+            self.config.provider_parameters.insert(id, plain);
+        }
+        self.config.save()
+    }
+
+    /// Move all secrets to a different kind of secret store, clearing the old one.
+    pub fn set_secret_store(&mut self, kind: SecretStoreKind) -> Result<()> {
+        if kind == self.config.secret_store {
+            return Ok(());
+        }
+        // secrets which could not be loaded would be lost
+        self.secret_store.load()?;
+        let store = kind.open(&self.dirs)?;
+        store.save(&self.secrets)?;
+        self.config.secret_store = kind;
+        self.config.save()?;
+        std::mem::replace(&mut self.secret_store, store).save(&Default::default())
+    }
 }
 
-fn read_config_or_default(config_path: &PathBuf) -> Result<VersionAnnotatedConfig> {
-    Ok(match std::fs::read(config_path) {
-        Ok(buf) => {
-            let config = serde_json::from_slice::<MaybeVersionedConfig>(&buf)
-                .context("failed to deserialize user config into maybe versioned config")?;
-            match config {
-                MaybeVersionedConfig::Versioned(v) => match v {
-                    VersionAnnotatedConfig::V0_0_0(v) => VersionAnnotatedConfig::V0_0_0(v),
-                    VersionAnnotatedConfig::Unsupported => bail!("unsupported config version"),
-                },
-                MaybeVersionedConfig::Legacy(legacy) => {
-                    VersionAnnotatedConfig::V0_0_0(Config_v0_0_0 {
-                        provider_parameters: legacy.provider_parameters,
-                        drg_pak_path: legacy.drg_pak_path,
-                        gui_theme: None,
-                    })
-                }
+/// Move parameters marked secret from `parameters` into `secrets`. Returns whether any were moved.
+fn move_secrets(
+    parameters: &mut HashMap<String, HashMap<String, String>>,
+    secrets: &mut Secrets,
+) -> bool {
+    let mut moved = false;
+    for factory in ModStore::get_provider_factories() {
+        let Some(params) = parameters.get_mut(factory.id) else {
+            continue;
+        };
+        for p in factory.parameters.iter().filter(|p| p.secret) {
+            if let Some(value) = params.remove(p.id) {
+                secrets
+                    .entry(factory.id.to_owned())
+                    .or_default()
+                    .insert(p.id.to_owned(), value);
+                moved = true;
             }
         }
-        Err(e) if e.kind() == std::io::ErrorKind::NotFound => VersionAnnotatedConfig::default(),
-        Err(e) => Err(e).context("failed to read `config.json`")?,
-    })
+        if params.is_empty() {
+            parameters.remove(factory.id);
+        }
+    }
+    moved
+}
+
+/// Remove secret provider parameters from a config backup, leaving the rest of it as is so it can
+/// still be restored.
+fn scrub_backup(path: &Path) -> Result<()> {
+    let mut value = serde_json::from_slice::<serde_json::Value>(&std::fs::read(path)?)?;
+    let Some(parameters) = value.get_mut("provider_parameters") else {
+        return Ok(());
+    };
+    let mut scrubbed = serde_json::from_value(parameters.take())?;
+    if move_secrets(&mut scrubbed, &mut Secrets::default()) {
+        *parameters = serde_json::to_value(scrubbed)?;
+        std::fs::write(path, serde_json::to_vec_pretty(&value)?)?;
+    }
+    Ok(())
+}
+
+/// Load `path`, offering to restore a backup if it fails to parse. Other errors, such as the file
+/// being written by a newer version, are returned as is.
+fn load_or_recover<T>(
+    path: &Path,
+    load: impl Fn(&Path) -> Result<T>,
+    restore: &mut impl FnMut(&CorruptFile) -> bool,
+) -> Result<T> {
+    let e = match load(path) {
+        Ok(data) => return Ok(data),
+        Err(e) => e,
+    };
+    if !e.chain().any(|e| e.is::<serde_json::Error>()) {
+        return Err(e);
+    }
+    warn!("failed to load {}: {e:#}", path.display());
+    let corrupt = CorruptFile {
+        path: path.to_path_buf(),
+        reason: format!("{e:#}"),
+        backup: config::backups(path)
+            .into_iter()
+            .find(|backup| load(backup).is_ok()),
+    };
+    if corrupt.backup.is_some() && restore(&corrupt) {
+        corrupt.restore()?;
+        load(path)
+    } else {
+        Err(corrupt.into())
+    }
+}
+
+fn read_config_or_default(config_path: &Path) -> Result<VersionAnnotatedConfig> {
+    match std::fs::read(config_path) {
+        Ok(buf) => Ok(migration::load::<ConfigFile>(config_path, &buf)?.into()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Default::default()),
+        Err(e) => Err(e).context("failed to read `config.json`"),
+    }
 }
 
 fn read_mod_data_or_default(
-    mod_data_path: &PathBuf,
+    mod_data_path: &Path,
     legacy_mod_profiles_path: PathBuf,
 ) -> Result<VersionAnnotatedModData> {
-    let mod_data = match std::fs::read(mod_data_path) {
-        Ok(buf) => serde_json::from_slice::<MaybeVersionedModData>(&buf)
-            .context("failed to deserialize existing `mod_data.json`")?,
+    match std::fs::read(mod_data_path) {
+        Ok(buf) => migration::load(mod_data_path, &buf),
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
             match std::fs::read(&legacy_mod_profiles_path) {
                 Ok(buf) => {
-                    let mod_data = serde_json::from_slice::<MaybeVersionedModData>(&buf)
-                        .context("failed to deserialize legacy `profiles.json`")?;
+                    let mod_data = migration::load(&legacy_mod_profiles_path, &buf)
+                        .context("failed to migrate legacy `profiles.json`")?;
                     std::fs::remove_file(&legacy_mod_profiles_path)
                         .context("failed to remove legacy `profiles.json` while migrating")?;
-                    mod_data
+                    Ok(mod_data)
                 }
-                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
-                    MaybeVersionedModData::default()
-                }
-                Err(e) => Err(e).context("failed to read legacy `profiles`.json")?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Default::default()),
+                Err(e) => Err(e).context("failed to read legacy `profiles`.json"),
             }
         }
-        Err(e) => Err(e).context("failed to read `mod_data`.json")?,
-    };
-
-    let mod_data = match mod_data {
-        MaybeVersionedModData::Legacy(legacy) => VersionAnnotatedModData::V0_1_0(legacy.into()),
-        MaybeVersionedModData::Versioned(v) => match v {
-            VersionAnnotatedModData::V0_0_0(md) => VersionAnnotatedModData::V0_1_0(md.into()),
-            VersionAnnotatedModData::V0_1_0(md) => VersionAnnotatedModData::V0_1_0(md),
-        },
-    };
-
-    Ok(mod_data)
+        Err(e) => Err(e).context("failed to read `mod_data`.json"),
+    }
 }
 
 #[cfg(test)]
 mod mod_data_tests {
     use super::{
-        ModConfig, ModData_v0_1_0 as ModData, ModGroup, ModOrGroup, ModProfile_v0_1_0 as ModProfile,
+        IntegrityProblem, ModConfig, ModData_v0_2_0 as ModData, ModGroup, ModOrGroup,
+        ModProfile_v0_2_0 as ModProfile,
     };
-    use crate::providers::ModSpecification;
+    use crate::providers::{ModInfo, ModResolution, ModSpecification, ResolvableStatus};
 
     #[test]
     fn test_for_each_mod() {
@@ -521,15 +1405,17 @@ mod mod_data_tests {
                         ModOrGroup::Group {
                             group_name: "mg1".to_string(),
                             enabled: false,
+                            overrides: Default::default(),
                         },
                     ],
+                    ..Default::default()
                 },
             )]
             .into(),
             groups: [(
                 "mg1".to_string(),
                 ModGroup {
-                    mods: vec![mod_2, mod_3],
+                    mods: vec![ModOrGroup::Individual(mod_2), ModOrGroup::Individual(mod_3)],
                 },
             )]
             .into(),
@@ -572,15 +1458,17 @@ mod mod_data_tests {
                         ModOrGroup::Group {
                             group_name: "mg1".to_string(),
                             enabled: true,
+                            overrides: Default::default(),
                         },
                     ],
+                    ..Default::default()
                 },
             )]
             .into(),
             groups: [(
                 "mg1".to_string(),
                 ModGroup {
-                    mods: vec![mod_2, mod_3],
+                    mods: vec![ModOrGroup::Individual(mod_2), ModOrGroup::Individual(mod_3)],
                 },
             )]
             .into(),
@@ -623,15 +1511,17 @@ mod mod_data_tests {
                         ModOrGroup::Group {
                             group_name: "mg1".to_string(),
                             enabled: true,
+                            overrides: Default::default(),
                         },
                     ],
+                    ..Default::default()
                 },
             )]
             .into(),
             groups: [(
                 "mg1".to_string(),
                 ModGroup {
-                    mods: vec![mod_2, mod_3],
+                    mods: vec![ModOrGroup::Individual(mod_2), ModOrGroup::Individual(mod_3)],
                 },
             )]
             .into(),
@@ -640,4 +1530,505 @@ mod mod_data_tests {
         let any_required = mod_data.any_mod("default", |mc, _| mc.required);
         assert!(any_required);
     }
+
+    #[test]
+    fn test_edit_profiles_and_groups() {
+        let mut mod_data = ModData::default();
+        mod_data.add_profile("other").unwrap();
+        assert!(mod_data.add_profile("other").is_err());
+
+        mod_data.rename_profile("default", "main").unwrap();
+        assert_eq!(mod_data.active_profile, "main");
+        mod_data.duplicate_profile("main", "copy").unwrap();
+        assert!(mod_data.rename_profile("copy", "other").is_err());
+
+        mod_data
+            .profile_mut("main")
+            .unwrap()
+            .mods
+            .push(ModOrGroup::Group {
+                group_name: "default".to_string(),
+                enabled: true,
+                overrides: Default::default(),
+            });
+        mod_data.rename_group("default", "renamed").unwrap();
+        assert!(matches!(
+            &mod_data.profiles["main"].mods[..],
+            [ModOrGroup::Group { group_name, .. }] if group_name == "renamed"
+        ));
+        mod_data.remove_group("renamed").unwrap();
+        assert!(mod_data.profiles["main"].mods.is_empty());
+
+        mod_data.remove_profile("main").unwrap();
+        assert_eq!(mod_data.active_profile, "copy");
+        mod_data.remove_profile("other").unwrap();
+        assert!(mod_data.remove_profile("copy").is_err());
+    }
+
+    fn individual(url: &str, enabled: bool) -> ModOrGroup {
+        ModOrGroup::Individual(ModConfig {
+            spec: ModSpecification::new(url.to_string()),
+            required: false,
+            enabled,
+        })
+    }
+
+    fn group(name: &str, enabled: bool, overrides: &[(&str, bool)]) -> ModOrGroup {
+        ModOrGroup::Group {
+            group_name: name.to_string(),
+            enabled,
+            overrides: overrides
+                .iter()
+                .map(|(url, enabled)| (url.to_string(), *enabled))
+                .collect(),
+        }
+    }
+
+    /// Profile `a` includes group `outer` containing group `inner`.
+    fn nested_mod_data() -> ModData {
+        ModData {
+            active_profile: "a".to_string(),
+            profiles: [
+                (
+                    "a".to_string(),
+                    ModProfile {
+                        mods: vec![
+                            individual("x", true),
+                            group("outer", true, &[("y", false), ("z", true)]),
+                        ],
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "b".to_string(),
+                    ModProfile {
+                        mods: vec![group("outer", true, &[])],
+                        ..Default::default()
+                    },
+                ),
+            ]
+            .into(),
+            groups: [
+                (
+                    "outer".to_string(),
+                    ModGroup {
+                        mods: vec![individual("y", true), group("inner", true, &[])],
+                    },
+                ),
+                (
+                    "inner".to_string(),
+                    ModGroup {
+                        mods: vec![individual("z", false), individual("w", true)],
+                    },
+                ),
+            ]
+            .into(),
+        }
+    }
+
+    fn enabled_urls(mod_data: &ModData, profile: &str) -> Vec<String> {
+        let mut urls = vec![];
+        mod_data.for_each_enabled_mod(profile, |mc| urls.push(mc.spec.url.clone()));
+        urls
+    }
+
+    #[test]
+    fn test_nested_groups_and_overrides() {
+        let mut mod_data = nested_mod_data();
+        assert_eq!(enabled_urls(&mod_data, "a"), ["x", "z", "w"]);
+        assert_eq!(enabled_urls(&mod_data, "b"), ["y", "w"]);
+
+        let walked = mod_data.walk_mods("a");
+        assert_eq!(walked[3].groups, ["outer", "inner"]);
+        assert_eq!(walked[3].path, [1, 1, 1]);
+
+        if let ModOrGroup::Group { enabled, .. } =
+            &mut mod_data.groups.get_mut("outer").unwrap().mods[1]
+        {
+            *enabled = false;
+        }
+        assert_eq!(enabled_urls(&mod_data, "a"), ["x"]);
+        assert_eq!(enabled_urls(&mod_data, "b"), ["y"]);
+
+        mod_data.rename_group("inner", "renamed").unwrap();
+        assert!(matches!(
+            &mod_data.groups["outer"].mods[1],
+            ModOrGroup::Group { group_name, .. } if group_name == "renamed"
+        ));
+        mod_data.remove_group("renamed").unwrap();
+        assert_eq!(mod_data.groups["outer"].mods.len(), 1);
+    }
+
+    #[test]
+    fn test_add_resolved_dependency_in_nested_group() {
+        let info = |url: &str| ModInfo {
+            provider: "test",
+            name: url.to_string(),
+            spec: ModSpecification::new(url.to_string()),
+            versions: vec![],
+            resolution: ModResolution {
+                url: url.to_string(),
+                status: ResolvableStatus::Resolvable,
+            },
+            suggested_require: false,
+            suggested_dependencies: vec![],
+            modio_tags: None,
+            modio_id: None,
+        };
+        let mut mod_data = nested_mod_data();
+
+        // enabled through an override on the profile's own group reference
+        let z = ModSpecification::new("z".to_string());
+        mod_data.add_resolved_mod("b", &z, &info("z"), true);
+        assert_eq!(enabled_urls(&mod_data, "b"), ["y", "z", "w"]);
+        assert!(matches!(
+            &mod_data.groups["inner"].mods[0],
+            ModOrGroup::Individual(mc) if !mc.enabled
+        ));
+
+        // a disabled nested reference is shared with profile `a`, so the mod is added instead
+        if let ModOrGroup::Group { enabled, .. } =
+            &mut mod_data.groups.get_mut("outer").unwrap().mods[1]
+        {
+            *enabled = false;
+        }
+        let w = ModSpecification::new("w".to_string());
+        mod_data.add_resolved_mod("b", &w, &info("w"), true);
+        assert_eq!(enabled_urls(&mod_data, "b"), ["w", "y"]);
+        assert_eq!(enabled_urls(&mod_data, "a"), ["x"]);
+        assert!(matches!(
+            &mod_data.groups["outer"].mods[1],
+            ModOrGroup::Group { enabled: false, .. }
+        ));
+    }
+
+    #[test]
+    fn test_integrity() {
+        let mut mod_data = nested_mod_data();
+        assert!(mod_data.integrity_problems().is_empty());
+
+        mod_data
+            .groups
+            .get_mut("inner")
+            .unwrap()
+            .mods
+            .push(group("outer", true, &[]));
+        mod_data
+            .profile_mut("b")
+            .unwrap()
+            .mods
+            .push(group("missing", true, &[]));
+        mod_data.active_profile = "gone".to_string();
+        // dangling and cyclic references are skipped while walking
+        assert_eq!(enabled_urls(&mod_data, "a"), ["x", "z", "w"]);
+
+        let problems = mod_data.integrity_problems();
+        assert!(problems.contains(&IntegrityProblem::MissingActiveProfile("gone".to_string())));
+        assert!(problems.contains(&IntegrityProblem::MissingGroup {
+            owner: "profile `b`".to_string(),
+            group: "missing".to_string(),
+        }));
+        assert!(problems.contains(&IntegrityProblem::GroupCycle {
+            group: "inner".to_string(),
+            nested: "outer".to_string(),
+        }));
+
+        assert_eq!(mod_data.repair(), problems);
+        assert!(mod_data.integrity_problems().is_empty());
+        assert_eq!(mod_data.active_profile, "a");
+        assert_eq!(mod_data.profiles["b"].mods.len(), 1);
+        // only one reference of the cycle is removed
+        assert_eq!(
+            mod_data.groups["outer"].mods.len() + mod_data.groups["inner"].mods.len(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_inheritance() {
+        let mut mod_data = nested_mod_data();
+        mod_data.add_profile("c").unwrap();
+        mod_data.profile_mut("c").unwrap().mods =
+            vec![individual("v", true), individual("w", false)];
+        mod_data.set_parent("c", Some("a")).unwrap();
+        assert!(mod_data.set_parent("a", Some("c")).is_err());
+        assert!(mod_data.set_parent("c", Some("c")).is_err());
+
+        // own mods first, the child's `w` replaces the inherited one
+        assert_eq!(enabled_urls(&mod_data, "c"), ["v", "x", "z"]);
+        let walked = mod_data.walk_mods("c");
+        assert_eq!(walked[2].inherited_from, Some("a"));
+        assert_eq!(walked[2].path, [0]);
+
+        let profile = mod_data.profile_mut("c").unwrap();
+        profile.excluded.insert("x".to_string());
+        profile.overrides.insert("y".to_string(), true);
+        profile.overrides.insert("z".to_string(), false);
+        assert_eq!(enabled_urls(&mod_data, "c"), ["v", "y"]);
+        assert_eq!(enabled_urls(&mod_data, "a"), ["x", "z", "w"]);
+
+        mod_data.rename_profile("a", "base").unwrap();
+        assert_eq!(mod_data.profiles["c"].parent.as_deref(), Some("base"));
+        mod_data.remove_profile("base").unwrap();
+        assert_eq!(mod_data.profiles["c"].parent, None);
+        assert_eq!(enabled_urls(&mod_data, "c"), ["v"]);
+    }
+
+    #[test]
+    fn test_parent_integrity() {
+        let mut mod_data = nested_mod_data();
+        mod_data.profile_mut("a").unwrap().parent = Some("b".to_string());
+        mod_data.profile_mut("b").unwrap().parent = Some("a".to_string());
+        mod_data.add_profile("c").unwrap();
+        mod_data.profile_mut("c").unwrap().parent = Some("missing".to_string());
+        // inheritance cycles end the walk
+        assert_eq!(enabled_urls(&mod_data, "a"), ["x", "z", "w"]);
+
+        let problems = mod_data.integrity_problems();
+        assert!(problems.contains(&IntegrityProblem::MissingParent {
+            profile: "c".to_string(),
+            parent: "missing".to_string(),
+        }));
+        assert!(problems.contains(&IntegrityProblem::ParentCycle {
+            profile: "a".to_string(),
+            parent: "b".to_string(),
+        }));
+        mod_data.repair();
+        assert!(mod_data.integrity_problems().is_empty());
+        assert_eq!(mod_data.profiles["a"].parent, None);
+        assert_eq!(mod_data.profiles["b"].parent.as_deref(), Some("a"));
+        assert_eq!(mod_data.profiles["c"].parent, None);
+    }
+}
+
+#[cfg(test)]
+mod mod_data_migration_tests {
+    use std::path::Path;
+
+    use super::config::ConfigWrapper;
+    use super::migration::MigrationError;
+    use super::{
+        load_or_recover, read_mod_data_or_default, CorruptFile, ModOrGroup, VersionAnnotatedModData,
+    };
+
+    const PROFILES_V0_0_0: &str = r#""active_profile": "b",
+        "profiles": {
+            "a": {"mods": []},
+            "b": {"mods": [
+                {"spec": {"url": "https://mod.io/g/drg/m/x"}, "required": true},
+                {"spec": {"url": "https://mod.io/g/drg/m/y"}, "required": false, "enabled": false}
+            ]}
+        }"#;
+
+    fn read(dir: &Path, file: &str, json: &str) -> anyhow::Result<VersionAnnotatedModData> {
+        std::fs::write(dir.join(file), json).unwrap();
+        read_mod_data_or_default(&dir.join("mod_data.json"), dir.join("profiles.json"))
+    }
+
+    fn assert_migrated_profiles(mod_data: &VersionAnnotatedModData) {
+        assert!(matches!(mod_data, VersionAnnotatedModData::V0_2_0(_)));
+        assert_eq!(mod_data.active_profile, "b");
+        assert!(mod_data.profiles["a"].mods.is_empty());
+        let mods = &mod_data.profiles["b"].mods;
+        assert_eq!(mods.len(), 2);
+        assert!(matches!(&mods[0], ModOrGroup::Individual(mc) if mc.required && mc.enabled));
+        assert!(matches!(&mods[1], ModOrGroup::Individual(mc) if !mc.required && !mc.enabled));
+    }
+
+    #[test]
+    fn test_read_legacy_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let mod_data = read(
+            dir.path(),
+            "profiles.json",
+            &format!("{{{PROFILES_V0_0_0}}}"),
+        )
+        .unwrap();
+        assert_migrated_profiles(&mod_data);
+        assert!(!dir.path().join("profiles.json").exists());
+        assert!(dir.path().join("profiles.json.legacy.bak").exists());
+    }
+
+    #[test]
+    fn test_read_mod_data_v0_0_0() {
+        let dir = tempfile::tempdir().unwrap();
+        let json = format!(r#"{{"version": "0.0.0", {PROFILES_V0_0_0}}}"#);
+        let mod_data = read(dir.path(), "mod_data.json", &json).unwrap();
+        assert_migrated_profiles(&mod_data);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("mod_data.json.0.0.0.bak")).unwrap(),
+            json
+        );
+    }
+
+    #[test]
+    fn test_read_mod_data_v0_1_0() {
+        let dir = tempfile::tempdir().unwrap();
+        let json = r#"{
+            "version": "0.1.0",
+            "active_profile": "a",
+            "profiles": {"a": {"mods": [
+                {"group_name": "g", "enabled": true},
+                {"spec": {"url": "https://mod.io/g/drg/m/x"}, "required": false, "enabled": true}
+            ]}},
+            "groups": {"g": {"mods": [{"spec": {"url": "https://mod.io/g/drg/m/y"}, "required": true, "enabled": true}]}}
+        }"#;
+        let mod_data = read(dir.path(), "mod_data.json", json).unwrap();
+        assert!(matches!(mod_data, VersionAnnotatedModData::V0_2_0(_)));
+        assert_eq!(mod_data.groups["g"].mods.len(), 1);
+        assert!(matches!(
+            &mod_data.profiles["a"].mods[0],
+            ModOrGroup::Group { group_name, enabled: true, .. } if group_name == "g"
+        ));
+        assert_eq!(mod_data.profiles["a"].parent, None);
+        assert!(dir.path().join("mod_data.json.0.1.0.bak").exists());
+    }
+
+    #[test]
+    fn test_read_mod_data_v0_2_0() {
+        let dir = tempfile::tempdir().unwrap();
+        let json = r#"{
+            "version": "0.2.0",
+            "active_profile": "b",
+            "profiles": {
+                "a": {"mods": [{"spec": {"url": "https://mod.io/g/drg/m/x"}, "required": false, "enabled": true}]},
+                "b": {"mods": [], "parent": "a", "excluded": ["https://mod.io/g/drg/m/x"]}
+            },
+            "groups": {}
+        }"#;
+        let mod_data = read(dir.path(), "mod_data.json", json).unwrap();
+        assert!(matches!(mod_data, VersionAnnotatedModData::V0_2_0(_)));
+        assert_eq!(mod_data.profiles["b"].parent.as_deref(), Some("a"));
+        assert!(mod_data.profiles["b"]
+            .excluded
+            .contains("https://mod.io/g/drg/m/x"));
+        assert!(!dir.path().join("mod_data.json.0.2.0.bak").exists());
+    }
+
+    #[test]
+    fn test_read_newer_mod_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = read(
+            dir.path(),
+            "mod_data.json",
+            r#"{"version": "1.0.0", "active_profile": "a"}"#,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast::<MigrationError>(),
+            Ok(MigrationError::NewerVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_recover_corrupt_mod_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mod_data.json");
+        let load = |path: &Path| read_mod_data_or_default(path, dir.path().join("profiles.json"));
+
+        let mut mod_data = ConfigWrapper::with_backups(&path, VersionAnnotatedModData::default());
+        mod_data.save().unwrap();
+        mod_data.active_profile = "b".into();
+        mod_data.save().unwrap();
+        drop(mod_data);
+        std::fs::write(&path, r#"{"version": "0.1.0", "active_prof"#).unwrap();
+
+        let err = load_or_recover(&path, load, &mut |_| false).unwrap_err();
+        let corrupt = err.downcast_ref::<CorruptFile>().unwrap();
+        assert_eq!(
+            corrupt.backup.as_deref(),
+            Some(dir.path().join("mod_data.json.bak1").as_path())
+        );
+        assert!(path.exists());
+
+        let mod_data = load_or_recover(&path, load, &mut |_| true).unwrap();
+        assert_eq!(mod_data.active_profile, "default");
+        assert!(dir.path().join("mod_data.json.corrupt").exists());
+    }
+}
+
+#[cfg(test)]
+mod config_tests {
+    use super::migration::MigrationError;
+    use super::{read_config_or_default, scrub_backup, VersionAnnotatedConfig};
+
+    #[test]
+    fn test_read_legacy_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(
+            &path,
+            r#"{"provider_parameters": {"modio": {"oauth": "x"}}, "drg_pak_path": null}"#,
+        )
+        .unwrap();
+
+        let config = read_config_or_default(&path).unwrap();
+        assert_eq!(config.provider_parameters["modio"]["oauth"], "x");
+        assert!(!config.block_denied_lints);
+        assert!(dir.path().join("config.json.legacy.bak").exists());
+    }
+
+    #[test]
+    fn test_read_config_v0_0_0() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(
+            &path,
+            r#"{
+                "version": "0.0.0",
+                "provider_parameters": {},
+                "drg_pak_path": "FSD.pak",
+                "gui_theme": null
+            }"#,
+        )
+        .unwrap();
+
+        let config = read_config_or_default(&path).unwrap();
+        assert!(matches!(config, VersionAnnotatedConfig::V0_1_0(_)));
+        assert_eq!(
+            config.drg_pak_path.as_deref(),
+            Some(std::path::Path::new("FSD.pak"))
+        );
+        assert!(dir.path().join("config.json.0.0.0.bak").exists());
+
+        // the current version is read as is
+        std::fs::write(&path, serde_json::to_vec(&config).unwrap()).unwrap();
+        std::fs::remove_file(dir.path().join("config.json.0.0.0.bak")).unwrap();
+        read_config_or_default(&path).unwrap();
+        assert!(!dir.path().join("config.json.0.0.0.bak").exists());
+    }
+
+    #[test]
+    fn test_read_newer_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, r#"{"version": "1.0.0"}"#).unwrap();
+        let err = read_config_or_default(&path).unwrap_err();
+        assert!(matches!(
+            err.downcast::<MigrationError>(),
+            Ok(MigrationError::NewerVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_scrub_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json.bak1");
+        std::fs::write(
+            &path,
+            r#"{
+                "version": "0.0.0",
+                "provider_parameters": {"modio": {"oauth": "secret"}},
+                "drg_pak_path": null,
+                "gui_theme": null
+            }"#,
+        )
+        .unwrap();
+
+        scrub_backup(&path).unwrap();
+        let value =
+            serde_json::from_slice::<serde_json::Value>(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(value["version"], "0.0.0");
+        assert_eq!(value["provider_parameters"], serde_json::json!({}));
+    }
 }
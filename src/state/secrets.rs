@@ -0,0 +1,371 @@
+//! Storage for secret provider parameters such as OAuth tokens so they never land in
+//! `config.json`.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use aes::cipher::{KeyIvInit, StreamCipher};
+use anyhow::{bail, Context, Result};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::Dirs;
+
+/// Secret values by provider id, then parameter id.
+pub type Secrets = HashMap<String, HashMap<String, String>>;
+
+/// Environment variable holding the passphrase for [`SecretStoreKind::Passphrase`].
+pub const PASSPHRASE_ENV_VAR: &str = "DRG_MOD_INTEGRATION_SECRET_PASSPHRASE";
+
+/// Which backend secrets are kept in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretStoreKind {
+    /// Encrypted file with a key derived from a random key file unique to this machine.
+    #[default]
+    MachineKey,
+    /// Encrypted file with a key derived from a passphrase read from [`PASSPHRASE_ENV_VAR`].
+    Passphrase,
+    /// Unencrypted file, for systems where neither of the above is workable.
+    Plain,
+}
+
+/// Returned by stores which cannot be opened in this environment, such as a passphrase store
+/// without a passphrase. Secrets are then unavailable but everything else keeps working.
+#[derive(Error, Debug)]
+#[error("secrets are protected by a passphrase, set {PASSPHRASE_ENV_VAR}")]
+pub struct SecretsUnavailable;
+
+pub trait SecretStore: Send + Sync {
+    fn load(&self) -> Result<Secrets>;
+    fn save(&self, secrets: &Secrets) -> Result<()>;
+}
+
+impl SecretStoreKind {
+    /// Open the store. Secrets are kept in the config directory, the machine key separately in
+    /// the data directory. A passphrase store opened without the passphrase set fails every load
+    /// and save with [`SecretsUnavailable`].
+    pub fn open(self, dirs: &Dirs) -> Result<Box<dyn SecretStore>> {
+        let dir = &dirs.config_dir;
+        Ok(match self {
+            SecretStoreKind::MachineKey => Box::new(EncryptedFileStore::with_key(
+                dir.join("secrets.bin"),
+                machine_key(&dirs.data_dir.join("secret.key"))?,
+            )),
+            SecretStoreKind::Passphrase => match std::env::var(PASSPHRASE_ENV_VAR) {
+                Ok(passphrase) => Box::new(EncryptedFileStore::with_passphrase(
+                    dir.join("secrets.passphrase.bin"),
+                    passphrase.into_bytes(),
+                )),
+                Err(_) => Box::new(UnavailableStore),
+            },
+            SecretStoreKind::Plain => Box::new(PlainFileStore {
+                path: dir.join("secrets.json"),
+            }),
+        })
+    }
+}
+
+/// Read the machine key, generating it on first use.
+fn machine_key(path: &Path) -> Result<Vec<u8>> {
+    match std::fs::read(path) {
+        Ok(key) => Ok(key),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let mut key = vec![0; 32];
+            getrandom::getrandom(&mut key)?;
+            write_private(path, &key).context("failed to write machine key")?;
+            Ok(key)
+        }
+        Err(e) => Err(e).context("failed to read machine key"),
+    }
+}
+
+/// Atomically write a file only readable by the current user.
+fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+    let mut file = tempfile::NamedTempFile::new_in(path.parent().unwrap())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.as_file()
+            .set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(data)?;
+    file.persist(path)?;
+    Ok(())
+}
+
+struct UnavailableStore;
+
+impl SecretStore for UnavailableStore {
+    fn load(&self) -> Result<Secrets> {
+        Err(SecretsUnavailable.into())
+    }
+
+    fn save(&self, _secrets: &Secrets) -> Result<()> {
+        Err(SecretsUnavailable.into())
+    }
+}
+
+pub struct PlainFileStore {
+    path: PathBuf,
+}
+
+impl SecretStore for PlainFileStore {
+    fn load(&self) -> Result<Secrets> {
+        match std::fs::read(&self.path) {
+            Ok(buf) => serde_json::from_slice(&buf).context("failed to parse `secrets.json`"),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Default::default()),
+            Err(e) => Err(e).context("failed to read `secrets.json`"),
+        }
+    }
+
+    fn save(&self, secrets: &Secrets) -> Result<()> {
+        write_private(&self.path, &serde_json::to_vec_pretty(secrets)?)
+    }
+}
+
+type Aes256Ctr = ctr::Ctr128BE<aes::Aes256>;
+type HmacSha256 = Hmac<Sha256>;
+
+const MAGIC: &[u8; 4] = b"DRGS";
+const FORMAT_VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 16;
+const TAG_LEN: usize = 32;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4 + SALT_LEN + NONCE_LEN;
+const DEFAULT_ITERATIONS: u32 = 600_000;
+/// Accepted PBKDF2 iteration counts, so a corrupted header can neither hang nor weaken decryption.
+const ITERATIONS: std::ops::RangeInclusive<u32> = 1_000..=10_000_000;
+
+/// How the encryption and MAC keys are derived from the secret of an [`EncryptedFileStore`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyDerivation {
+    /// The secret is already a random key, so the keys are derived from it with HMAC-SHA256 under
+    /// distinct labels. Stored as an iteration count of zero.
+    Key,
+    /// The secret is a passphrase, stretched with PBKDF2-HMAC-SHA256.
+    Passphrase { iterations: u32 },
+}
+
+impl KeyDerivation {
+    fn iterations(self) -> u32 {
+        match self {
+            KeyDerivation::Key => 0,
+            KeyDerivation::Passphrase { iterations } => iterations,
+        }
+    }
+}
+
+/// Secrets encrypted with AES-256-CTR and authenticated with HMAC-SHA256 (encrypt-then-MAC), both
+/// keys derived from `secret` and a fresh random salt on every save: with HMAC-SHA256 for random
+/// keys and PBKDF2-HMAC-SHA256 for passphrases.
+///
+/// Layout: magic, format version, iteration count (u32 LE), salt, nonce, ciphertext, tag.
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    secret: Vec<u8>,
+    derivation: KeyDerivation,
+}
+
+impl EncryptedFileStore {
+    /// Store protected by a random key such as the machine key.
+    pub fn with_key(path: PathBuf, key: Vec<u8>) -> Self {
+        Self {
+            path,
+            secret: key,
+            derivation: KeyDerivation::Key,
+        }
+    }
+
+    /// Store protected by a passphrase.
+    pub fn with_passphrase(path: PathBuf, passphrase: Vec<u8>) -> Self {
+        Self {
+            path,
+            secret: passphrase,
+            derivation: KeyDerivation::Passphrase {
+                iterations: DEFAULT_ITERATIONS,
+            },
+        }
+    }
+
+    fn derive_keys(&self, salt: &[u8], derivation: KeyDerivation) -> ([u8; 32], [u8; 32]) {
+        match derivation {
+            KeyDerivation::Key => {
+                let derive = |label: &[u8]| -> [u8; 32] {
+                    let mut mac = <HmacSha256 as Mac>::new_from_slice(&self.secret)
+                        .expect("HMAC takes keys of any size");
+                    mac.update(salt);
+                    mac.update(label);
+                    mac.finalize().into_bytes().as_slice().try_into().unwrap()
+                };
+                (
+                    derive(b"secrets encryption key"),
+                    derive(b"secrets mac key"),
+                )
+            }
+            KeyDerivation::Passphrase { iterations } => {
+                let mut keys = [0; 64];
+                pbkdf2::pbkdf2_hmac::<Sha256>(&self.secret, salt, iterations, &mut keys);
+                let (enc, mac) = keys.split_at(32);
+                (enc.try_into().unwrap(), mac.try_into().unwrap())
+            }
+        }
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut salt = [0; SALT_LEN];
+        let mut nonce = [0; NONCE_LEN];
+        getrandom::getrandom(&mut salt)?;
+        getrandom::getrandom(&mut nonce)?;
+        let (enc_key, mac_key) = self.derive_keys(&salt, self.derivation);
+
+        let mut out = Vec::with_capacity(HEADER_LEN + plaintext.len() + TAG_LEN);
+        out.extend_from_slice(MAGIC);
+        out.push(FORMAT_VERSION);
+        out.extend_from_slice(&self.derivation.iterations().to_le_bytes());
+        out.extend_from_slice(&salt);
+        out.extend_from_slice(&nonce);
+        let start = out.len();
+        out.extend_from_slice(plaintext);
+        Aes256Ctr::new(&enc_key.into(), &nonce.into()).apply_keystream(&mut out[start..]);
+
+        let mut mac =
+            <HmacSha256 as Mac>::new_from_slice(&mac_key).expect("HMAC takes keys of any size");
+        mac.update(&out);
+        out.extend_from_slice(&mac.finalize().into_bytes());
+        Ok(out)
+    }
+
+    fn decrypt(&self, buf: &[u8]) -> Result<Vec<u8>> {
+        if buf.len() < HEADER_LEN + TAG_LEN || &buf[..MAGIC.len()] != MAGIC {
+            bail!("not a secrets file");
+        }
+        if buf[MAGIC.len()] != FORMAT_VERSION {
+            bail!("unsupported secrets file version {}", buf[MAGIC.len()]);
+        }
+        let mut pos = MAGIC.len() + 1;
+        let iterations = u32::from_le_bytes(buf[pos..pos + 4].try_into().unwrap());
+        pos += 4;
+        let derivation = match (self.derivation, iterations) {
+            (KeyDerivation::Key, 0) => KeyDerivation::Key,
+            (KeyDerivation::Key, _) => bail!("secrets file is protected by a passphrase"),
+            (KeyDerivation::Passphrase { .. }, 0) => {
+                bail!("secrets file is not protected by a passphrase")
+            }
+            (KeyDerivation::Passphrase { .. }, iterations) if ITERATIONS.contains(&iterations) => {
+                KeyDerivation::Passphrase { iterations }
+            }
+            (_, iterations) => bail!("invalid iteration count {iterations} in secrets file"),
+        };
+        let salt = &buf[pos..pos + SALT_LEN];
+        pos += SALT_LEN;
+        let nonce: [u8; NONCE_LEN] = buf[pos..pos + NONCE_LEN].try_into().unwrap();
+
+        let (body, tag) = buf.split_at(buf.len() - TAG_LEN);
+        let (enc_key, mac_key) = self.derive_keys(salt, derivation);
+        let mut mac =
+            <HmacSha256 as Mac>::new_from_slice(&mac_key).expect("HMAC takes keys of any size");
+        mac.update(body);
+        mac.verify_slice(tag)
+            .ok()
+            .context("wrong key or corrupted secrets file")?;
+
+        let mut plaintext = body[HEADER_LEN..].to_vec();
+        Aes256Ctr::new(&enc_key.into(), &nonce.into()).apply_keystream(&mut plaintext);
+        Ok(plaintext)
+    }
+}
+
+impl SecretStore for EncryptedFileStore {
+    fn load(&self) -> Result<Secrets> {
+        match std::fs::read(&self.path) {
+            Ok(buf) => {
+                let plaintext = self
+                    .decrypt(&buf)
+                    .with_context(|| format!("failed to decrypt {}", self.path.display()))?;
+                Ok(serde_json::from_slice(&plaintext)?)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Default::default()),
+            Err(e) => Err(e).with_context(|| format!("failed to read {}", self.path.display())),
+        }
+    }
+
+    fn save(&self, secrets: &Secrets) -> Result<()> {
+        write_private(&self.path, &self.encrypt(&serde_json::to_vec(secrets)?)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn store(dir: &Path, secret: &str) -> EncryptedFileStore {
+        EncryptedFileStore {
+            path: dir.join("secrets.bin"),
+            secret: secret.as_bytes().to_vec(),
+            derivation: KeyDerivation::Passphrase { iterations: 1000 },
+        }
+    }
+
+    fn key_store(dir: &Path, key: &str) -> EncryptedFileStore {
+        EncryptedFileStore::with_key(dir.join("secrets.bin"), key.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_encrypted_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let secrets = Secrets::from([(
+            "modio".to_string(),
+            HashMap::from([("oauth".to_string(), "hunter2".to_string())]),
+        )]);
+
+        store(dir.path(), "key").save(&secrets).unwrap();
+        let buf = std::fs::read(dir.path().join("secrets.bin")).unwrap();
+        assert!(!buf.windows(7).any(|w| w == b"hunter2"));
+        assert_eq!(store(dir.path(), "key").load().unwrap(), secrets);
+
+        assert!(store(dir.path(), "wrong").load().is_err());
+        let mut tampered = buf;
+        tampered[HEADER_LEN] ^= 1;
+        std::fs::write(dir.path().join("secrets.bin"), tampered).unwrap();
+        assert!(store(dir.path(), "key").load().is_err());
+    }
+
+    #[test]
+    fn test_key_derivation() {
+        let dir = tempfile::tempdir().unwrap();
+        let secrets = Secrets::from([(
+            "modio".to_string(),
+            HashMap::from([("oauth".to_string(), "hunter2".to_string())]),
+        )]);
+        let path = dir.path().join("secrets.bin");
+        let iterations = |buf: &[u8]| {
+            u32::from_le_bytes(buf[MAGIC.len() + 1..MAGIC.len() + 5].try_into().unwrap())
+        };
+
+        // key stores only accept files written without key stretching
+        store(dir.path(), "key").save(&secrets).unwrap();
+        let err = key_store(dir.path(), "key").load().unwrap_err();
+        assert!(format!("{err:#}").contains("protected by a passphrase"));
+        key_store(dir.path(), "key").save(&secrets).unwrap();
+        let buf = std::fs::read(&path).unwrap();
+        assert_eq!(iterations(&buf), 0);
+        assert_eq!(key_store(dir.path(), "key").load().unwrap(), secrets);
+        assert!(key_store(dir.path(), "wrong").load().is_err());
+
+        // passphrase stores refuse to skip key stretching
+        assert!(store(dir.path(), "key").load().is_err());
+
+        // out of range iteration counts are rejected before deriving keys
+        for count in [u32::MAX, 1] {
+            let mut tampered = buf.clone();
+            tampered[MAGIC.len() + 1..MAGIC.len() + 5].copy_from_slice(&count.to_le_bytes());
+            std::fs::write(&path, tampered).unwrap();
+            let err = store(dir.path(), "key").load().unwrap_err();
+            assert!(format!("{err:#}").contains("invalid iteration count"));
+        }
+    }
+}
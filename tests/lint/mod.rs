@@ -1,8 +1,81 @@
-use std::path::PathBuf;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-use drg_mod_integration::mod_lints::{LintId, LintReport, SplitAssetPair};
+use drg_mod_integration::mod_lints::{
+    check_denied_lints, fix_mods, run_lints_on_reader, run_lints_with_config, Fix, LintConfig,
+    LintCtxt, LintId, LintLevel, LintReport, ReportFormat,
+};
 use drg_mod_integration::providers::ModSpecification;
+use tokio_util::sync::CancellationToken;
+
+/// Paths reported by `lint` for `mod_spec`.
+fn reported_paths(
+    report: &LintReport,
+    lint: LintId,
+    mod_spec: &ModSpecification,
+) -> BTreeSet<String> {
+    report
+        .for_lint(lint)
+        .filter(|d| d.mods.contains(mod_spec))
+        .filter_map(|d| d.path.clone())
+        .collect()
+}
+
+/// Build an in-memory pak containing `files`.
+fn build_pak(files: &[(&str, &[u8])]) -> Vec<u8> {
+    build_pak_with(repak::Version::V11, "../../../", files)
+}
+
+fn build_pak_with(version: repak::Version, mount_point: &str, files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = repak::PakWriter::new(
+        std::io::Cursor::new(vec![]),
+        version,
+        mount_point.to_string(),
+        None,
+    );
+    for (path, data) in files {
+        writer
+            .write_file(path, &mut std::io::Cursor::new(data))
+            .unwrap();
+    }
+    writer.write_index().unwrap().into_inner()
+}
+
+/// Lint an in-memory pak as the only mod, returning the report and the mod's spec.
+fn lint_pak(lint: LintId, files: &[(&str, &[u8])]) -> (LintReport, ModSpecification) {
+    let spec = ModSpecification {
+        url: "in_memory".to_string(),
+    };
+    let config = LintConfig::with_enabled(&[lint].into());
+    let report = run_lints_on_reader(
+        &config,
+        spec.clone(),
+        std::io::Cursor::new(build_pak(files)),
+        None,
+    )
+    .unwrap();
+    (report, spec)
+}
+
+/// Version and lowercased file names of a pak.
+fn read_pak(data: &[u8]) -> (repak::Version, BTreeSet<String>) {
+    let reader = repak::PakReader::new_any(&mut std::io::Cursor::new(data)).unwrap();
+    let files = reader
+        .files()
+        .into_iter()
+        .map(|f| f.to_ascii_lowercase())
+        .collect();
+    (reader.version(), files)
+}
+
+/// Mods reported by `lint`.
+fn reported_mods(report: &LintReport, lint: LintId) -> BTreeSet<ModSpecification> {
+    report
+        .for_lint(lint)
+        .flat_map(|d| d.mods.iter().cloned())
+        .collect()
+}
 
 #[test]
 pub fn test_lint_conflicting_files() {
@@ -21,17 +94,71 @@ pub fn test_lint_conflicting_files() {
     };
     let mods = [(a_spec.clone(), a_path), (b_spec.clone(), b_path)];
 
-    let LintReport {
-        conflicting_mods, ..
-    } = drg_mod_integration::mod_lints::run_lints(&[LintId::CONFLICTING].into(), mods.into(), None)
+    let report =
+        drg_mod_integration::mod_lints::run_lints(&[LintId::CONFLICTING].into(), mods.into(), None)
+            .unwrap();
+
+    println!("{:#?}", report);
+
+    let conflict = report
+        .for_lint(LintId::CONFLICTING)
+        .find(|d| d.path.as_deref() == Some("fsd/content/a.uexp"))
         .unwrap();
+    assert_eq!(conflict.mods, vec![a_spec, b_spec]);
+    assert_eq!(conflict.level, LintLevel::Warn);
+}
 
-    println!("{:#?}", conflicting_mods);
+#[test]
+pub fn test_lint_config_levels_and_allowed_paths() {
+    let base_path = PathBuf::from_str("test_assets/lints/").unwrap();
+    let a_spec = ModSpecification {
+        url: "A".to_string(),
+    };
+    let b_spec = ModSpecification {
+        url: "B".to_string(),
+    };
+    let mods = [
+        (a_spec, base_path.join("A.pak")),
+        (b_spec, base_path.join("B.pak")),
+    ];
 
+    let mut config: LintConfig = serde_json::from_str(
+        r#"{
+            "levels": { "conflicting": "deny", "shader_files": "warn" },
+            "allowed_paths": { "shader_files": ["fsd/content/c"] }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(config.level(LintId::CONFLICTING), LintLevel::Deny);
     assert_eq!(
-        conflicting_mods.unwrap().get("fsd/content/a.uexp"),
-        Some(&[a_spec, b_spec].into())
+        config.level(LintId::UNMODIFIED_GAME_ASSETS),
+        LintLevel::Allow
     );
+    for factory in drg_mod_integration::mod_lints::lint_factories() {
+        config.levels.entry(factory.id).or_insert(LintLevel::Allow);
+    }
+
+    let report = run_lints_with_config(
+        &config,
+        mods.clone().into(),
+        None,
+        Default::default(),
+        &CancellationToken::new(),
+    )
+    .unwrap();
+
+    assert_eq!(report.for_lint(LintId::SHADER_FILES).count(), 0);
+    assert_eq!(report.denied(), [LintId::CONFLICTING].into());
+    assert!(check_denied_lints(
+        &config,
+        mods.into(),
+        None,
+        Default::default(),
+        &CancellationToken::new()
+    )
+    .is_err());
+
+    assert!(serde_json::from_str::<LintConfig>(r#"{ "levels": { "nope": "deny" } }"#).is_err());
 }
 
 #[test]
@@ -50,21 +177,22 @@ pub fn test_lint_shader() {
     };
     let mods = [(a_spec.clone(), a_path), (b_spec.clone(), b_path)];
 
-    let LintReport {
-        shader_file_mods, ..
-    } = drg_mod_integration::mod_lints::run_lints(
+    let report = drg_mod_integration::mod_lints::run_lints(
         &[LintId::SHADER_FILES].into(),
         mods.into(),
         None,
     )
     .unwrap();
 
-    println!("{:#?}", shader_file_mods);
+    println!("{:#?}", report);
 
     assert_eq!(
-        shader_file_mods.unwrap().get(&a_spec),
-        Some(&["fsd/content/c.ushaderbytecode".to_string()].into())
+        reported_paths(&report, LintId::SHADER_FILES, &a_spec),
+        ["fsd/content/c.ushaderbytecode".to_string()].into()
     );
+    assert!(report
+        .for_lint(LintId::SHADER_FILES)
+        .all(|d| d.fix == Some(Fix::RemoveFile)));
 }
 
 #[test]
@@ -83,21 +211,18 @@ pub fn test_lint_asset_registry_bin() {
     };
     let mods = [(a_spec.clone(), a_path), (b_spec.clone(), b_path)];
 
-    let LintReport {
-        asset_register_bin_mods,
-        ..
-    } = drg_mod_integration::mod_lints::run_lints(
+    let report = drg_mod_integration::mod_lints::run_lints(
         &[LintId::ASSET_REGISTRY_BIN].into(),
         mods.into(),
         None,
     )
     .unwrap();
 
-    println!("{:#?}", asset_register_bin_mods);
+    println!("{:#?}", report);
 
     assert_eq!(
-        asset_register_bin_mods.unwrap().get(&a_spec),
-        Some(&["fsd/content/assetregistry.bin".to_string()].into())
+        reported_paths(&report, LintId::ASSET_REGISTRY_BIN, &a_spec),
+        ["fsd/content/assetregistry.bin".to_string()].into()
     );
 }
 
@@ -112,22 +237,22 @@ pub fn test_lint_outdated_pak_version() {
     };
     let mods = [(outdated_spec.clone(), outdated_pak_path)];
 
-    let LintReport {
-        outdated_pak_version_mods,
-        ..
-    } = drg_mod_integration::mod_lints::run_lints(
+    let report = drg_mod_integration::mod_lints::run_lints(
         &[LintId::OUTDATED_PAK_VERSION].into(),
         mods.into(),
         None,
     )
     .unwrap();
 
-    println!("{:#?}", outdated_pak_version_mods);
+    println!("{:#?}", report);
 
-    assert_eq!(
-        outdated_pak_version_mods.unwrap().get(&outdated_spec),
-        Some(&repak::Version::V10)
-    );
+    let diagnostic = report
+        .for_lint(LintId::OUTDATED_PAK_VERSION)
+        .next()
+        .unwrap();
+    assert_eq!(diagnostic.mods, vec![outdated_spec]);
+    assert!(diagnostic.message.contains("V10"));
+    assert_eq!(diagnostic.fix, Some(Fix::Repack));
 }
 
 #[test]
@@ -141,18 +266,16 @@ pub fn test_lint_empty_archive() {
     };
     let mods = [(empty_archive_spec.clone(), empty_archive_path)];
 
-    let LintReport {
-        empty_archive_mods, ..
-    } = drg_mod_integration::mod_lints::run_lints(
+    let report = drg_mod_integration::mod_lints::run_lints(
         &[LintId::EMPTY_ARCHIVE].into(),
         mods.into(),
         None,
     )
     .unwrap();
 
-    println!("{:#?}", empty_archive_mods);
+    println!("{:#?}", report);
 
-    assert!(empty_archive_mods.unwrap().contains(&empty_archive_spec));
+    assert!(reported_mods(&report, LintId::EMPTY_ARCHIVE).contains(&empty_archive_spec));
 }
 
 #[test]
@@ -174,21 +297,19 @@ pub fn test_lint_only_non_pak_files() {
         (only_non_pak_spec.clone(), only_non_pak_path),
     ];
 
-    let LintReport {
-        archive_with_only_non_pak_files_mods,
-        ..
-    } = drg_mod_integration::mod_lints::run_lints(
+    let report = drg_mod_integration::mod_lints::run_lints(
         &[LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES].into(),
         mods.into(),
         None,
     )
     .unwrap();
 
-    println!("{:#?}", archive_with_only_non_pak_files_mods);
+    println!("{:#?}", report);
 
-    assert!(archive_with_only_non_pak_files_mods
-        .unwrap()
-        .contains(&only_non_pak_spec));
+    assert!(
+        reported_mods(&report, LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES)
+            .contains(&only_non_pak_spec)
+    );
 }
 
 #[test]
@@ -202,21 +323,18 @@ pub fn test_lint_multi_pak_archive() {
     };
     let mods = [(multiple_paks_spec.clone(), multiple_paks_archive_path)];
 
-    let LintReport {
-        archive_with_multiple_paks_mods,
-        ..
-    } = drg_mod_integration::mod_lints::run_lints(
+    let report = drg_mod_integration::mod_lints::run_lints(
         &[LintId::ARCHIVE_WITH_MULTIPLE_PAKS].into(),
         mods.into(),
         None,
     )
     .unwrap();
 
-    println!("{:#?}", archive_with_multiple_paks_mods);
+    println!("{:#?}", report);
 
-    assert!(archive_with_multiple_paks_mods
-        .unwrap()
-        .contains(&multiple_paks_spec));
+    assert!(
+        reported_mods(&report, LintId::ARCHIVE_WITH_MULTIPLE_PAKS).contains(&multiple_paks_spec)
+    );
 }
 
 #[test]
@@ -230,21 +348,18 @@ pub fn test_lint_non_asset_files() {
     };
     let mods = [(non_asset_files_spec.clone(), non_asset_files_path)];
 
-    let LintReport {
-        non_asset_file_mods,
-        ..
-    } = drg_mod_integration::mod_lints::run_lints(
+    let report = drg_mod_integration::mod_lints::run_lints(
         &[LintId::NON_ASSET_FILES].into(),
         mods.into(),
         None,
     )
     .unwrap();
 
-    println!("{:#?}", non_asset_file_mods);
+    println!("{:#?}", report);
 
     assert_eq!(
-        non_asset_file_mods.unwrap().get(&non_asset_files_spec),
-        Some(&["never_gonna_give_you_up.txt".to_string()].into())
+        reported_paths(&report, LintId::NON_ASSET_FILES, &non_asset_files_spec),
+        ["never_gonna_give_you_up.txt".to_string()].into()
     );
 }
 
@@ -259,33 +374,32 @@ pub fn test_lint_split_asset_pairs() {
     };
     let mods = [(split_asset_pairs_spec.clone(), split_asset_pairs_path)];
 
-    let LintReport {
-        split_asset_pairs_mods,
-        ..
-    } = drg_mod_integration::mod_lints::run_lints(
+    let report = drg_mod_integration::mod_lints::run_lints(
         &[LintId::SPLIT_ASSET_PAIRS].into(),
         mods.into(),
         None,
     )
     .unwrap();
 
-    println!("{:#?}", split_asset_pairs_mods);
+    println!("{:#?}", report);
 
+    let split = report
+        .for_lint(LintId::SPLIT_ASSET_PAIRS)
+        .map(|d| (d.path.clone().unwrap(), d.message.clone()))
+        .collect::<BTreeSet<_>>();
     assert_eq!(
-        split_asset_pairs_mods.unwrap().get(&split_asset_pairs_spec),
-        Some(
-            &[
-                (
-                    "missing_uasset/a.uexp".to_string(),
-                    SplitAssetPair::MissingUasset
-                ),
-                (
-                    "missing_uexp/b.uasset".to_string(),
-                    SplitAssetPair::MissingUexp
-                )
-            ]
-            .into()
-        )
+        split,
+        [
+            (
+                "missing_uasset/a.uexp".to_string(),
+                "missing matching `.uasset`".to_string()
+            ),
+            (
+                "missing_uexp/b.uasset".to_string(),
+                "missing matching `.uexp`".to_string()
+            )
+        ]
+        .into()
     );
 }
 
@@ -305,22 +419,310 @@ pub fn test_lint_unmodified_game_assets() {
         unmodified_game_assets_path,
     )];
 
-    let LintReport {
-        unmodified_game_assets_mods,
-        ..
-    } = drg_mod_integration::mod_lints::run_lints(
+    let report = drg_mod_integration::mod_lints::run_lints(
         &[LintId::UNMODIFIED_GAME_ASSETS].into(),
         mods.into(),
         Some(reference_pak_path),
     )
     .unwrap();
 
-    println!("{:#?}", unmodified_game_assets_mods);
+    println!("{:#?}", report);
+
+    assert_eq!(
+        reported_paths(
+            &report,
+            LintId::UNMODIFIED_GAME_ASSETS,
+            &unmodified_game_assets_spec
+        ),
+        ["a.uexp".to_string(), "a.uasset".to_string()].into()
+    );
+}
+
+#[test]
+pub fn test_lint_report_export() {
+    let split_asset_pairs_spec = ModSpecification {
+        url: "split_asset_pairs".to_string(),
+    };
+    let mods = [(
+        split_asset_pairs_spec,
+        PathBuf::from("test_assets/lints/split_asset_pairs.pak"),
+    )];
+
+    let report = drg_mod_integration::mod_lints::run_lints(
+        &[LintId::SPLIT_ASSET_PAIRS].into(),
+        mods.into(),
+        None,
+    )
+    .unwrap();
 
     assert_eq!(
-        unmodified_game_assets_mods
-            .unwrap()
-            .get(&unmodified_game_assets_spec),
-        Some(&["a.uexp".to_string(), "a.uasset".to_string()].into())
+        ReportFormat::from_path(Path::new("report.HTML")),
+        ReportFormat::Html
+    );
+    assert_eq!(
+        ReportFormat::from_path(Path::new("report.md")),
+        ReportFormat::Markdown
     );
+
+    let markdown = report.render(ReportFormat::Markdown);
+    assert!(markdown.contains("| `split_asset_pairs` | warn | 2 |"));
+    assert!(markdown.contains("### `split_asset_pairs` — 2"));
+    assert!(markdown
+        .contains("- `split_asset_pairs`: `missing_uasset/a.uexp` missing matching `.uasset`"));
+
+    let html = report.render(ReportFormat::Html);
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<code>missing_uexp/b.uasset</code> missing matching `.uexp`"));
+}
+
+#[test]
+pub fn test_lint_from_reader() {
+    let spec = ModSpecification {
+        url: "split_asset_pairs".to_string(),
+    };
+    let data = std::fs::read("test_assets/lints/split_asset_pairs.pak").unwrap();
+    let config = LintConfig::with_enabled(&[LintId::SPLIT_ASSET_PAIRS].into());
+
+    let report =
+        run_lints_on_reader(&config, spec.clone(), std::io::Cursor::new(data), None).unwrap();
+
+    assert_eq!(
+        reported_paths(&report, LintId::SPLIT_ASSET_PAIRS, &spec),
+        [
+            "missing_uasset/a.uexp".to_string(),
+            "missing_uexp/b.uasset".to_string()
+        ]
+        .into()
+    );
+}
+
+/// Start of a package file summary with the given legacy file and object versions, padded with
+/// zeroes.
+fn package_summary(legacy_file_version: i32, object_version: i32) -> Vec<u8> {
+    [
+        0x9E2A83C1u32 as i32,
+        legacy_file_version,
+        864,
+        object_version,
+        0,
+    ]
+    .into_iter()
+    .flat_map(i32::to_le_bytes)
+    .chain([0; 256])
+    .collect()
+}
+
+#[test]
+pub fn test_lint_unparsable_assets() {
+    let unversioned = package_summary(-7, 0);
+    let ue5 = package_summary(-8, 522);
+    let mismatched = package_summary(-7, 500);
+    let empty: &[u8] = &[];
+    let (report, _) = lint_pak(
+        LintId::UNPARSABLE_ASSETS,
+        &[
+            ("unversioned/a.uasset", unversioned.as_slice()),
+            ("unversioned/a.uexp", empty),
+            ("ue5/b.uasset", ue5.as_slice()),
+            ("ue5/b.uexp", empty),
+            ("mismatched/c.uasset", mismatched.as_slice()),
+            ("mismatched/c.uexp", empty),
+            ("not_a_package/d.uasset", b"d".as_slice()),
+            ("not_a_package/d.uexp", empty),
+        ],
+    );
+    let message = |path: &str| {
+        report
+            .for_lint(LintId::UNPARSABLE_ASSETS)
+            .find(|d| d.path.as_deref() == Some(path))
+            .map(|d| d.message.clone())
+    };
+
+    // being unversioned is not a problem by itself, the asset is still parsed
+    if let Some(message) = message("unversioned/a.uasset") {
+        assert!(message.starts_with("failed to parse"), "{message}");
+    }
+    assert!(message("ue5/b.uasset")
+        .unwrap()
+        .contains("cooked for Unreal Engine 5"));
+    assert!(message("mismatched/c.uasset")
+        .unwrap()
+        .contains("package version 500"));
+    assert!(message("not_a_package/d.uasset").is_some());
+}
+
+#[test]
+pub fn test_fix_mods() {
+    let base_path = PathBuf::from_str("test_assets/lints/").unwrap();
+    let outdated_spec = ModSpecification {
+        url: "outdated".to_string(),
+    };
+    let non_asset_files_spec = ModSpecification {
+        url: "non_asset_files".to_string(),
+    };
+    let mods = [
+        (
+            outdated_spec.clone(),
+            base_path.join("outdated_pak_version.pak"),
+        ),
+        (
+            non_asset_files_spec.clone(),
+            base_path.join("non_asset_files.pak"),
+        ),
+    ];
+
+    let lint_ctxt = LintCtxt::init(
+        mods.into(),
+        None,
+        Default::default(),
+        LintConfig::with_enabled(&[LintId::OUTDATED_PAK_VERSION, LintId::NON_ASSET_FILES].into()),
+        CancellationToken::new(),
+    )
+    .unwrap();
+    let report = lint_ctxt.lint().unwrap();
+    let fixed = fix_mods(&report, &lint_ctxt).unwrap();
+    assert_eq!(fixed.len(), 2);
+
+    let outdated = fixed.iter().find(|f| f.spec == outdated_spec).unwrap();
+    assert!(outdated.removed.is_empty());
+    let (version, files) = read_pak(&outdated.data);
+    assert!(matches!(version, repak::Version::V11));
+    assert!(files.iter().any(|f| f.ends_with("foo.uexp")));
+
+    let non_asset_files = fixed
+        .iter()
+        .find(|f| f.spec == non_asset_files_spec)
+        .unwrap();
+    assert_eq!(
+        non_asset_files.removed,
+        ["never_gonna_give_you_up.txt".to_string()].into()
+    );
+    let (version, files) = read_pak(&non_asset_files.data);
+    assert!(matches!(version, repak::Version::V11));
+    assert!(!files
+        .iter()
+        .any(|f| f.ends_with("never_gonna_give_you_up.txt")));
+}
+
+#[test]
+pub fn test_fix_mods_lists_unmounted_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("unmounted.pak");
+    std::fs::write(
+        &path,
+        build_pak_with(
+            repak::Version::V10,
+            "../../",
+            &[("outside.txt", b"outside".as_slice())],
+        ),
+    )
+    .unwrap();
+    let spec = ModSpecification {
+        url: "unmounted".to_string(),
+    };
+    let mods = [(spec.clone(), path)];
+
+    let lint_ctxt = LintCtxt::init(
+        mods.into(),
+        None,
+        Default::default(),
+        LintConfig::with_enabled(&[LintId::OUTDATED_PAK_VERSION].into()),
+        CancellationToken::new(),
+    )
+    .unwrap();
+    let report = lint_ctxt.lint().unwrap();
+    let fixed = fix_mods(&report, &lint_ctxt).unwrap();
+
+    assert_eq!(fixed[0].removed, ["../../outside.txt".to_string()].into());
+    assert!(read_pak(&fixed[0].data).1.is_empty());
+}
+
+#[test]
+pub fn test_lint_invalid_paths() {
+    let (report, spec) = lint_pak(
+        LintId::INVALID_PATHS,
+        &[
+            ("FSD/Content/A.uexp", b"a".as_slice()),
+            ("fsd/content/a.uexp", b"a".as_slice()),
+            ("FSD\\Content\\B.uexp", b"b".as_slice()),
+            ("FSD/Content/Ç.uexp", b"c".as_slice()),
+            ("FSD/Content/D.uexp", b"d".as_slice()),
+        ],
+    );
+    let message = |path: &str| {
+        report
+            .for_lint(LintId::INVALID_PATHS)
+            .filter(|d| d.path.as_deref() == Some(path))
+            .map(|d| d.message.clone())
+            .collect::<Vec<_>>()
+    };
+
+    let collision = message("fsd/content/a.uexp");
+    assert_eq!(collision.len(), 1);
+    assert!(collision[0].starts_with("contains paths which differ only in case"));
+    assert!(collision[0].contains("`FSD/Content/A.uexp`, `fsd/content/a.uexp`"));
+    assert_eq!(message("fsd/content/b.uexp"), ["path contains backslashes"]);
+    assert!(message("fsd/content/Ç.uexp")[0].contains("non-ASCII"));
+    assert!(message("fsd/content/d.uexp").is_empty());
+    assert!(report
+        .for_lint(LintId::INVALID_PATHS)
+        .all(|d| d.mods == [spec.clone()]));
+}
+
+#[test]
+pub fn test_lint_invalid_paths_unmounted() {
+    let spec = ModSpecification {
+        url: "unmounted".to_string(),
+    };
+    let config = LintConfig::with_enabled(&[LintId::INVALID_PATHS].into());
+    let data = build_pak_with(
+        repak::Version::V11,
+        "../../",
+        &[("outside.txt", b"outside".as_slice())],
+    );
+
+    let report =
+        run_lints_on_reader(&config, spec.clone(), std::io::Cursor::new(data), None).unwrap();
+
+    assert_eq!(
+        reported_paths(&report, LintId::INVALID_PATHS, &spec),
+        ["../../outside.txt".to_string()].into()
+    );
+}
+
+#[test]
+pub fn test_lint_invalid_paths_across_mods() {
+    let dir = tempfile::tempdir().unwrap();
+    let mod_a = ModSpecification {
+        url: "a".to_string(),
+    };
+    let mod_b = ModSpecification {
+        url: "b".to_string(),
+    };
+    let mut mods = vec![];
+    for (spec, path) in [
+        (&mod_a, "FSD/Content/Shared.uexp"),
+        (&mod_b, "fsd/content/shared.uexp"),
+    ] {
+        let pak_path = dir.path().join(format!("{}.pak", spec.url));
+        std::fs::write(&pak_path, build_pak(&[(path, b"shared".as_slice())])).unwrap();
+        mods.push((spec.clone(), pak_path));
+    }
+
+    let report = drg_mod_integration::mod_lints::run_lints(
+        &[LintId::INVALID_PATHS].into(),
+        mods.into_iter().collect(),
+        None,
+    )
+    .unwrap();
+
+    let collision = report
+        .for_lint(LintId::INVALID_PATHS)
+        .find(|d| d.path.as_deref() == Some("fsd/content/shared.uexp"))
+        .unwrap();
+    assert!(collision
+        .message
+        .starts_with("mods contain paths which differ only in case"));
+    assert_eq!(collision.mods.len(), 2);
+    assert!(collision.mods.contains(&mod_a) && collision.mods.contains(&mod_b));
 }